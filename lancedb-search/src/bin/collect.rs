@@ -0,0 +1,37 @@
+use anyhow::Result;
+use lancedb_search::{
+    collect::Collector,
+    config::{CollectConfig, Config},
+    embedding_service::EmbeddingSearchService,
+};
+use std::sync::Arc;
+
+/// Backfills the index from the relays in `COLLECT_RELAYS`, using the same
+/// embedding and store settings as the server.
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let config = Config::from_env()?;
+    let collect_config = CollectConfig::from_env()?;
+    println!(
+        "Collecting kinds {:?} from {} relays, {}..{}",
+        collect_config.kinds,
+        collect_config.relays.len(),
+        collect_config.since,
+        collect_config.until
+    );
+
+    let service = Arc::new(EmbeddingSearchService::from_config(&config).await?);
+    let progress = Collector::new(service.clone(), collect_config)?
+        .run()
+        .await?;
+
+    service.create_index().await.ok();
+
+    println!(
+        "Collected {} events in {} windows: {} indexed, {} failed",
+        progress.fetched, progress.windows, progress.indexed, progress.failed
+    );
+    Ok(())
+}
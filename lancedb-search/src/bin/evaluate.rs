@@ -0,0 +1,99 @@
+use anyhow::Result;
+use lancedb_search::{
+    config::Config,
+    embedding_service::EmbeddingSearchService,
+    evaluation::{EvaluationReport, EvaluationSet, Metrics, evaluate},
+};
+
+/// Compares search quality of two configurations on labeled queries, e.g.
+/// a new embedding model, `min_score` or ranking mode against the current
+/// setup, before rolling the change out.
+///
+/// Usage: `evaluate EVALUATION_FILE [--output REPORT]`. The file holds
+/// `queries` (each with a `query`, the ids of its `relevant` events and
+/// optional `grades`), a `candidate` and optionally a `baseline`, which
+/// override settings of the server configuration. Prints precision,
+/// recall and NDCG at `k` for both and writes the full report as JSON to
+/// `REPORT` when given.
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut input_path = None;
+    let mut output_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                output_path = Some(
+                    args.next()
+                        .ok_or_else(|| anyhow::anyhow!("--output needs a file"))?,
+                );
+            }
+            flag if flag.starts_with("--") => anyhow::bail!("Unknown option {}", flag),
+            path => input_path = Some(path.to_string()),
+        }
+    }
+    let Some(input_path) = input_path else {
+        anyhow::bail!("Usage: evaluate EVALUATION_FILE [--output REPORT]");
+    };
+
+    let contents = std::fs::read_to_string(&input_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", input_path, e))?;
+    let set: EvaluationSet = serde_json::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Invalid evaluation file {}: {}", input_path, e))?;
+    if set.queries.is_empty() {
+        anyhow::bail!("{} has no queries", input_path);
+    }
+
+    let config = Config::from_env()?;
+    let baseline = EmbeddingSearchService::from_config(&set.baseline.config(&config)?).await?;
+    let candidate = EmbeddingSearchService::from_config(&set.candidate.config(&config)?).await?;
+
+    let report = evaluate(&set, &baseline, &candidate).await?;
+    print_report(&report);
+
+    if let Some(path) = &output_path {
+        std::fs::write(path, serde_json::to_vec_pretty(&report)?)?;
+        println!("Wrote report to {}", path);
+    }
+    Ok(())
+}
+
+fn print_report(report: &EvaluationReport) {
+    let row = |name: &str, metrics: &Metrics| {
+        println!(
+            "{:<12} {:>10.4} {:>10.4} {:>10.4}",
+            name, metrics.precision, metrics.recall, metrics.ndcg
+        );
+    };
+    println!(
+        "{} queries at k={}: {} vs {}",
+        report.queries.len(),
+        report.k,
+        report.baseline_name,
+        report.candidate_name
+    );
+    println!(
+        "{:<12} {:>10} {:>10} {:>10}",
+        "", "precision", "recall", "ndcg"
+    );
+    row(&report.baseline_name, &report.baseline);
+    row(&report.candidate_name, &report.candidate);
+    println!(
+        "{:<12} {:>+10.4} {:>+10.4} {:>+10.4}",
+        "diff", report.diff.precision, report.diff.recall, report.diff.ndcg
+    );
+
+    let regressions = report.regressions();
+    if !regressions.is_empty() {
+        println!("NDCG dropped for {} queries:", regressions.len());
+        for query in regressions {
+            println!(
+                "  {:+.4} {}",
+                query.candidate.ndcg - query.baseline.ndcg,
+                query.query
+            );
+        }
+    }
+}
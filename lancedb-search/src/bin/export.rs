@@ -0,0 +1,64 @@
+use anyhow::Result;
+use lancedb_search::{
+    config::{Config, StoreBackend},
+    lancedb_store::LanceDBStore,
+    vector_store::VectorStore,
+};
+use std::io::Write;
+
+/// Writes every stored row as one JSON object per line, for offline
+/// analysis or moving the index to another store.
+///
+/// Usage: `export [--no-content] [--no-vectors] [OUTPUT]`; writes to stdout
+/// when no output file is given.
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut include_content = true;
+    let mut include_vectors = true;
+    let mut output_path = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--no-content" => include_content = false,
+            "--no-vectors" => include_vectors = false,
+            flag if flag.starts_with("--") => anyhow::bail!("Unknown option {}", flag),
+            path => output_path = Some(path.to_string()),
+        }
+    }
+
+    let config = Config::from_env()?;
+    if config.store_backend != StoreBackend::LanceDb {
+        anyhow::bail!("export reads LanceDB tables only");
+    }
+    // Opened as it is: creating or migrating the table here would change
+    // the data being exported
+    let mut store =
+        LanceDBStore::open_existing(&config.db_path, &config.table_name, &config.storage_options)
+            .await?;
+    // Rows of a table predating model ids were embedded by the configured model
+    store.set_model_id(&config.embedding.model_id);
+
+    let output: Box<dyn Write> = match &output_path {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut output = std::io::BufWriter::new(output);
+
+    let count = store
+        .export_rows(include_content, include_vectors, |row| {
+            serde_json::to_writer(&mut output, &row)?;
+            output.write_all(b"\n")?;
+            Ok(())
+        })
+        .await?;
+    output.flush()?;
+
+    eprintln!(
+        "Exported {} rows from {}{}",
+        count,
+        config.table_name,
+        output_path
+            .map(|path| format!(" to {}", path))
+            .unwrap_or_default()
+    );
+    Ok(())
+}
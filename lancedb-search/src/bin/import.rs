@@ -0,0 +1,49 @@
+use anyhow::Result;
+use lancedb_search::{
+    config::Config, embedding_service::EmbeddingSearchService, import::import_events,
+};
+use std::io::BufRead;
+
+/// Seeds the index from newline-delimited event JSON, e.g. the output of
+/// `strfry export`.
+///
+/// Usage: `import [--batch-size N] [FILE]`; reads stdin when no file is
+/// given. Signatures are checked unless `VERIFY_SIGNATURES=false`.
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let mut batch_size = 100;
+    let mut input_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch-size" => {
+                batch_size = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .filter(|&size: &usize| size > 0)
+                    .ok_or_else(|| anyhow::anyhow!("--batch-size needs a positive number"))?;
+            }
+            flag if flag.starts_with("--") => anyhow::bail!("Unknown option {}", flag),
+            path => input_path = Some(path.to_string()),
+        }
+    }
+
+    let config = Config::from_env()?;
+    let service = EmbeddingSearchService::from_config(&config).await?;
+
+    let input: Box<dyn BufRead> = match &input_path {
+        Some(path) => Box::new(std::io::BufReader::new(std::fs::File::open(path)?)),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let progress = import_events(&service, input, batch_size, config.verify_signatures).await?;
+
+    service.create_index().await.ok();
+
+    println!(
+        "Read {} events: {} imported, {} invalid, {} failed",
+        progress.read, progress.imported, progress.invalid, progress.failed
+    );
+    Ok(())
+}
@@ -1,107 +1,539 @@
 use anyhow::Result;
 use axum::{
     Router,
-    extract::{Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    extract::{Path, Query, State, WebSocketUpgrade, rejection::JsonRejection},
+    http::{HeaderMap, StatusCode, header},
+    middleware,
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post},
 };
 use lancedb_search::{
-    EventSearchRequest,
+    EventSearchRequest, FieldError, ProfileMatch, RankingMode, ResultField, ResultHit, SearchMode,
+    SeedFusion, SimilarEventsRequest, StoredEvent, VectorSpace,
+    clustering::TopicCluster,
+    config::{Config, EmbeddingProvider, MaintenanceConfig, ModelSpec, RelaySearchConfig},
+    dead_letter::{DeadLetter, DeadLetterStore},
     embedding_service::EmbeddingSearchService,
-    embeddings::EmbeddingService,
-    event_queue::{EventProcessor, EventQueue},
+    error::{ApiError, ErrorCode},
+    etag::etag,
+    event_queue::{EnqueueError, EventProcessor, EventQueue},
+    event_wal::EventWal,
+    health::{self, DependencyHealth, HealthReport, HealthStatus},
+    list_publish::{ListPublisher, PublishListRequest, PublishListResponse, list_identifier},
+    live::{LiveFeed, LiveMessage, LiveQuery, serve_live_search},
+    maintenance::MaintenanceTask,
+    migration::{CompareRequest, RankChange, RankingComparison, compare_models},
     nostr::NostrEvent,
+    query_stats::{QueryStat, QueryStats},
+    relay_search::{CombinedHit, RelaySearcher, ResultSource, merge_results},
+    retention::RetentionTask,
+    saved_search::{Delivery, Notification, SavedSearch, SavedSearchRequest, SavedSearches},
+    spam::SpamFilter,
+    suggest::{HashtagCache, Suggestion, SuggestionSource, suggestions},
+    thread::{ThreadContext, ThreadEvent, expand_threads},
+    vector_store::{self, TableVersion},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tower_http::cors::CorsLayer;
+use std::time::{Duration, Instant};
+use tower_http::{compression::CompressionLayer, cors::CorsLayer};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(Clone)]
 struct AppState {
     embedding_service: Arc<EmbeddingSearchService>,
     event_queue: EventQueue,
+    dead_letters: Arc<DeadLetterStore>,
+    admin_token: Option<String>,
+    verify_signatures: bool,
+    maintenance: MaintenanceConfig,
+    models: Vec<ModelSpec>,
+    relay_searcher: Arc<RelaySearcher>,
+    query_stats: Option<Arc<QueryStats>>,
+    hashtags: Arc<HashtagCache>,
+    saved_searches: Option<Arc<SavedSearches>>,
+    live_feed: LiveFeed,
+    live_min_score: f32,
+    list_publisher: Option<Arc<ListPublisher>>,
+    /// Candidate model of a model migration, written alongside the current one
+    migration_target: Option<Arc<EmbeddingSearchService>>,
+    /// Deadline for search requests, unset when disabled
+    search_timeout: Option<Duration>,
+    /// Rejects event ingestion and admin writes
+    read_only: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct SemanticSearchRequest {
     query: String,
     limit: Option<usize>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct SemanticSearchResponse {
     event_ids: Vec<String>,
     total_found: usize,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    snippets: HashMap<String, String>,
+    /// Root and parent of results that are replies, keyed by result ID,
+    /// when `expand_thread` is set
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    threads: HashMap<String, ThreadContext>,
+    /// Results in rank order with the attributes selected by `fields`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hits: Vec<ResultHit>,
+    /// The embedding provider failed and the results are keyword matches
+    /// on stored content
+    #[serde(default)]
+    degraded: bool,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HealthParams {
+    /// Also probe the embedding provider, vector store and event queue
+    deep: Option<String>,
+}
+
+/// Parameters of `/search/clusters` on top of the search filters.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ClusterParams {
+    /// Maximum number of clusters (default 5, at most 20)
+    clusters: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct ClusterSearchResponse {
+    /// Largest cluster first
+    clusters: Vec<TopicCluster>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SuggestParams {
+    /// What the user has typed so far; a leading `#` completes hashtags only
+    q: String,
+    /// Maximum number of suggestions (default 10)
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SuggestResponse {
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct QueryStatsParams {
+    /// Queries returned per list (default 20)
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct QueryStatsResponse {
+    /// Most frequent queries
+    top: Vec<QueryStat>,
+    /// Queries that most often returned nothing
+    zero_results: Vec<QueryStat>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct MaintenanceResponse {
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct RollbackRequest {
+    version: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct ModelStatus {
+    #[serde(flatten)]
+    spec: ModelSpec,
+    /// Whether new events and queries use this model
+    active: bool,
+    /// Stored events embedded with this model
+    stored_events: usize,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "lancedb-search",
+        description = "Semantic search over Nostr events"
+    ),
+    paths(
+        get_events,
+        post_event,
+        semantic_search,
+        search_profiles,
+        combined_search,
+        similar_events,
+        cluster_search,
+        live_search,
+        suggest,
+        health_check,
+        metrics,
+        query_stats,
+        optimize_table,
+        list_versions,
+        rollback_table,
+        list_models,
+        list_dead_letters,
+        requeue_dead_letters,
+        reload_relays,
+        list_saved_searches,
+        create_saved_search,
+        delete_saved_search,
+        publish_list,
+        compare_migration
+    ),
+    components(schemas(
+        SemanticSearchResponse,
+        ResultHit,
+        ResultField,
+        StoredEvent,
+        ThreadContext,
+        ThreadEvent,
+        ProfileSearchResponse,
+        ProfileMatch,
+        CombinedSearchResponse,
+        CombinedHit,
+        ResultSource,
+        SimilarEventsRequest,
+        SeedFusion,
+        ClusterSearchResponse,
+        TopicCluster,
+        LiveQuery,
+        LiveMessage,
+        SuggestResponse,
+        Suggestion,
+        SuggestionSource,
+        QueryStatsResponse,
+        QueryStat,
+        MaintenanceResponse,
+        RollbackRequest,
+        ModelStatus,
+        ModelSpec,
+        EmbeddingProvider,
+        DeadLetter,
+        RequeueRequest,
+        RequeueResponse,
+        RelaySearchConfig,
+        PublishListRequest,
+        PublishListResponse,
+        CompareRequest,
+        RankingComparison,
+        RankChange,
+        SavedSearch,
+        SavedSearchRequest,
+        Delivery,
+        Notification,
+        TableVersion,
+        NostrEvent,
+        RankingMode,
+        VectorSpace,
+        SearchMode,
+        ApiError,
+        ErrorCode,
+        FieldError,
+        HealthReport,
+        HealthStatus,
+        DependencyHealth
+    ))
+)]
+struct ApiDoc;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
-    let embedding_service = EmbeddingService::new()?;
+    let config = Config::from_env()?;
 
-    let embedding_service = Arc::new(
-        EmbeddingSearchService::new(embedding_service, "./lancedb_data", "nostr_events").await?,
-    );
+    if config.read_only {
+        println!("Read-only replica: not accepting events or writing to the index");
+    }
 
-    embedding_service.create_index().await.ok();
+    let embedding_service = Arc::new(EmbeddingSearchService::from_config(&config).await?);
 
-    let (event_queue, receiver) = EventQueue::new();
-    let processor = EventProcessor::new(embedding_service.clone(), receiver);
+    if !config.read_only {
+        embedding_service.create_index().await.ok();
+    }
 
-    tokio::spawn(async move {
-        processor.start_processing().await;
+    let (mut event_queue, receiver) = EventQueue::new(config.event_queue_capacity);
+    let mut processor = EventProcessor::new(
+        embedding_service.clone(),
+        receiver,
+        config.processor.clone(),
+    )
+    .with_metrics(event_queue.metrics());
+    if let Some(path) = &config.event_queue_wal_path
+        && !config.read_only
+    {
+        let wal = Arc::new(EventWal::open(path)?);
+        println!("Event WAL: {} ({} pending)", path, wal.len());
+        event_queue = event_queue.with_wal(wal.clone());
+        processor = processor.with_wal(wal);
+    }
+
+    let migration_target = match config.migration_target()? {
+        Some(target_config) => {
+            println!(
+                "Migrating to model {}: also writing to table {}",
+                target_config.embedding.model_id, target_config.table_name
+            );
+            let migration_target =
+                Arc::new(EmbeddingSearchService::from_config(&target_config).await?);
+            if !config.read_only {
+                migration_target.create_index().await.ok();
+                processor = processor.with_migration_target(migration_target.clone());
+            }
+            Some(migration_target)
+        }
+        None => None,
+    };
+
+    if let Some(spam_config) = config.spam_filter.clone()
+        && !config.read_only
+    {
+        println!(
+            "Spam filter enabled: quarantining events scoring {} or more in table {}",
+            spam_config.threshold, spam_config.quarantine_table
+        );
+        let quarantine = vector_store::open(
+            &config,
+            &spam_config.quarantine_table,
+            embedding_service.dimensions(),
+            &config.embedding.model_id,
+        )
+        .await?;
+        processor = processor.with_spam_filter(Arc::new(SpamFilter::new(spam_config, quarantine)));
+    }
+
+    let live_feed = LiveFeed::new(config.live_channel_capacity);
+    processor = processor.with_live_feed(live_feed.clone());
+
+    let saved_searches = match config.saved_searches.clone().filter(|_| !config.read_only) {
+        Some(saved_search_config) => {
+            let saved_searches =
+                Arc::new(SavedSearches::open(saved_search_config, &embedding_service).await?);
+            println!("Saved searches: {} registered", saved_searches.list().len());
+            processor = processor.with_saved_searches(saved_searches.clone());
+            Some(saved_searches)
+        }
+        None => None,
+    };
+
+    let dead_letters = processor.dead_letters();
+
+    // Replicas drop the processor, which closes the queue
+    let processor_handle = (!config.read_only).then(|| {
+        tokio::spawn(async move {
+            processor.start_processing().await;
+        })
     });
 
+    if let Some(retention) = config.retention.clone()
+        && !config.read_only
+    {
+        let retention_task = RetentionTask::new(embedding_service.clone(), retention);
+        tokio::spawn(async move {
+            retention_task.start().await;
+        });
+    }
+
+    if config.maintenance.interval_secs > 0 && !config.read_only {
+        let maintenance_task =
+            MaintenanceTask::new(embedding_service.clone(), config.maintenance.clone());
+        tokio::spawn(async move {
+            maintenance_task.start().await;
+        });
+    }
+
+    let query_stats = match &config.query_stats_path {
+        Some(path) => {
+            println!("Recording query stats in {}", path);
+            Some(Arc::new(QueryStats::open(path)?))
+        }
+        None => None,
+    };
+
+    let list_publisher = match &config.list_publishing {
+        Some(list_config) => {
+            println!(
+                "Publishing NIP-51 lists to {} relays",
+                list_config.relays.len()
+            );
+            Some(Arc::new(ListPublisher::new(list_config).await?))
+        }
+        None => None,
+    };
+
     let state = AppState {
         embedding_service,
         event_queue,
+        dead_letters,
+        admin_token: config.admin_token.clone(),
+        verify_signatures: config.verify_signatures,
+        maintenance: config.maintenance.clone(),
+        models: config.embedding.registry(),
+        relay_searcher: Arc::new(RelaySearcher::new(config.relay_search.clone())),
+        query_stats,
+        hashtags: Arc::new(HashtagCache::new(Duration::from_secs(
+            config.hashtag_cache_ttl_secs,
+        ))),
+        saved_searches,
+        live_feed,
+        live_min_score: config.live_min_score,
+        list_publisher,
+        migration_target,
+        search_timeout: (config.search.timeout_secs > 0)
+            .then(|| Duration::from_secs(config.search.timeout_secs)),
+        read_only: config.read_only,
     };
 
     let app = Router::new()
         .route("/events", get(get_events))
         .route("/events", post(post_event))
         .route("/search", get(semantic_search))
+        .route("/search/profiles", get(search_profiles))
+        .route("/search/combined", get(combined_search))
+        .route("/search/similar", post(similar_events))
+        .route("/search/clusters", get(cluster_search))
+        .route("/search/live", get(live_search))
+        .route("/suggest", get(suggest))
+        .route("/health", get(health_check))
+        .route("/metrics", get(metrics))
+        .route("/stats/queries", get(query_stats))
+        .route("/admin/optimize", post(optimize_table))
+        .route("/admin/versions", get(list_versions))
+        .route("/admin/rollback", post(rollback_table))
+        .route("/admin/models", get(list_models))
+        .route("/admin/dead-letters", get(list_dead_letters))
+        .route("/admin/dead-letters/requeue", post(requeue_dead_letters))
+        .route("/admin/relays/reload", post(reload_relays))
+        .route("/admin/saved-searches", get(list_saved_searches))
+        .route("/admin/saved-searches", post(create_saved_search))
+        .route("/admin/saved-searches/{id}", delete(delete_saved_search))
+        .route("/admin/lists/publish", post(publish_list))
+        .route("/admin/migration/compare", post(compare_migration))
+        .merge(SwaggerUi::new("/swagger-ui").url("/openapi.json", ApiDoc::openapi()))
         .with_state(state)
+        .layer(middleware::from_fn(etag))
+        .layer(CompressionLayer::new())
         .layer(CorsLayer::permissive());
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3009").await?;
-    println!("Server running on http://0.0.0.0:3009");
+    let bind_address = config.bind_address();
+    let listener = tokio::net::TcpListener::bind(&bind_address).await?;
+    println!("Server running on http://{}", bind_address);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
 
-    axum::serve(listener, app).await?;
+    // The router (and with it every queue handle) is dropped once the server
+    // has stopped, so the processor exits after storing the remaining backlog.
+    let Some(processor_handle) = processor_handle else {
+        println!("Server stopped");
+        return Ok(());
+    };
+    println!("Server stopped, draining event queue");
+    let shutdown_timeout = Duration::from_secs(config.shutdown_timeout_secs);
+    match tokio::time::timeout(shutdown_timeout, processor_handle).await {
+        Ok(Ok(())) => println!("Event queue drained"),
+        Ok(Err(e)) => eprintln!("Event processor failed during shutdown: {}", e),
+        Err(_) => eprintln!(
+            "Event queue not drained after {}s, exiting with events still pending",
+            shutdown_timeout.as_secs()
+        ),
+    }
 
     Ok(())
 }
 
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    println!("Shutdown signal received, no longer accepting requests");
+}
+
+/// Semantic search with filters. Tag filters are passed NIP-01 style,
+/// e.g. `#t=bitcoin,nostr`. `fields=id,score,event` returns `hits` with
+/// just those attributes, e.g. scores without fetching the events.
+#[utoipa::path(
+    get,
+    path = "/events",
+    params(EventSearchRequest),
+    responses(
+        (status = 200, description = "Matching event IDs", body = SemanticSearchResponse),
+        (status = 400, description = "Invalid parameters or filters", body = ApiError),
+        (status = 500, description = "Embedding or vector store failure", body = ApiError),
+        (status = 504, description = "Search didn't finish within SEARCH_TIMEOUT_SECS", body = ApiError)
+    )
+)]
 async fn get_events(
     State(state): State<AppState>,
     Query(params): Query<serde_json::Value>,
-) -> Result<Json<SemanticSearchResponse>, StatusCode> {
-    let request: EventSearchRequest =
-        serde_json::from_value(params).map_err(|_| StatusCode::BAD_REQUEST)?;
-
-    match state.embedding_service.semantic_search(&request).await {
-        Ok(response) => {
-            let search_response = SemanticSearchResponse {
-                total_found: response.total_found,
-                event_ids: response.event_ids,
-            };
-            Ok(Json(search_response))
-        }
-        Err(e) => {
-            eprintln!("Search error: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
-        }
-    }
+) -> Result<Json<SemanticSearchResponse>, ApiError> {
+    let request = EventSearchRequest::from_query(params)
+        .map_err(|e| ApiError::invalid_request(format!("Invalid search parameters: {}", e)))?;
+
+    search(&state, &request).await
 }
 
+/// Queue a Nostr event for embedding and storage.
+#[utoipa::path(
+    post,
+    path = "/events",
+    request_body = NostrEvent,
+    responses(
+        (status = 200, description = "Event queued"),
+        (status = 400, description = "Malformed event or invalid signature", body = ApiError),
+        (status = 403, description = "Read-only replica", body = ApiError),
+        (status = 429, description = "Event queue full, retry later", body = ApiError),
+        (status = 503, description = "Event queue unavailable", body = ApiError)
+    )
+)]
 async fn post_event(
     State(state): State<AppState>,
-    Json(request): Json<NostrEvent>,
-) -> Result<(), StatusCode> {
+    payload: Result<Json<NostrEvent>, JsonRejection>,
+) -> Result<(), ApiError> {
+    require_writable(&state)?;
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+
     println!("Received event for queueing: {}", request.id);
 
+    if state.verify_signatures {
+        request
+            .verify()
+            .map_err(|e| ApiError::invalid_request(e.to_string()))?;
+    }
+
     match state.event_queue.enqueue(request) {
         Ok(()) => {
             println!("Event queued successfully");
@@ -109,46 +541,952 @@ async fn post_event(
         }
         Err(e) => {
             eprintln!("Failed to queue event: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            let code = match e {
+                EnqueueError::Full => ErrorCode::QueueFull,
+                EnqueueError::Closed | EnqueueError::Wal => ErrorCode::QueueUnavailable,
+            };
+            Err(ApiError::new(code, e.to_string()))
         }
     }
 }
 
+/// Plain semantic search by query text.
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SemanticSearchRequest),
+    responses(
+        (status = 200, description = "Matching event IDs", body = SemanticSearchResponse),
+        (status = 400, description = "Invalid parameters", body = ApiError),
+        (status = 500, description = "Embedding or vector store failure", body = ApiError),
+        (status = 504, description = "Search didn't finish within SEARCH_TIMEOUT_SECS", body = ApiError)
+    )
+)]
 async fn semantic_search(
     State(state): State<AppState>,
     Query(params): Query<serde_json::Value>,
-) -> Result<Json<SemanticSearchResponse>, StatusCode> {
+) -> Result<Json<SemanticSearchResponse>, ApiError> {
     let request: SemanticSearchRequest = serde_json::from_value(params).map_err(|e| {
         eprintln!("Failed to parse SemanticSearchRequest: {}", e);
-        eprintln!("Expected fields: query, limit");
-        StatusCode::BAD_REQUEST
+        ApiError::invalid_request(format!(
+            "Invalid search parameters: {} (expected fields: query, limit)",
+            e
+        ))
     })?;
 
     println!("Parsed semantic search request: {:?}", request);
 
     let search_request = EventSearchRequest {
-        language: None,
-        author: None,
         limit: request.limit,
-        event_kinds: None,
         search: Some(request.query),
+        ..Default::default()
     };
 
-    match state
-        .embedding_service
-        .semantic_search(&search_request)
+    search(&state, &search_request).await
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct ProfileSearchResponse {
+    profiles: Vec<ProfileMatch>,
+}
+
+/// Find people by what they write about themselves, e.g. "developers into
+/// embedded Rust". Ranks kind-0 profiles by semantic similarity of their
+/// name and `about` text.
+#[utoipa::path(
+    get,
+    path = "/search/profiles",
+    params(SemanticSearchRequest),
+    responses(
+        (status = 200, description = "Matching profiles, most similar first", body = ProfileSearchResponse),
+        (status = 400, description = "Invalid parameters", body = ApiError),
+        (status = 500, description = "Embedding or vector store failure", body = ApiError),
+        (status = 504, description = "Search didn't finish within SEARCH_TIMEOUT_SECS", body = ApiError)
+    )
+)]
+async fn search_profiles(
+    State(state): State<AppState>,
+    Query(params): Query<serde_json::Value>,
+) -> Result<Json<ProfileSearchResponse>, ApiError> {
+    let request: SemanticSearchRequest = serde_json::from_value(params).map_err(|e| {
+        ApiError::invalid_request(format!(
+            "Invalid search parameters: {} (expected fields: query, limit)",
+            e
+        ))
+    })?;
+
+    let profiles = with_deadline(&state, async {
+        state
+            .embedding_service
+            .search_profiles(&request.query, request.limit)
+            .await
+            .map_err(|e| ApiError::backend(format!("Profile search failed: {}", e)))
+    })
+    .await?;
+
+    Ok(Json(ProfileSearchResponse { profiles }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct CombinedSearchResponse {
+    results: Vec<CombinedHit>,
+}
+
+/// Semantic search over the local index and NIP-50 search on public relays
+/// at the same time, merged with local hits first. Useful while the local
+/// index is still small.
+#[utoipa::path(
+    get,
+    path = "/search/combined",
+    params(SemanticSearchRequest),
+    responses(
+        (status = 200, description = "Merged results labelled by source", body = CombinedSearchResponse),
+        (status = 400, description = "Invalid parameters", body = ApiError),
+        (status = 500, description = "Both the local index and the relays failed", body = ApiError),
+        (status = 504, description = "Search didn't finish within SEARCH_TIMEOUT_SECS", body = ApiError)
+    )
+)]
+async fn combined_search(
+    State(state): State<AppState>,
+    Query(params): Query<serde_json::Value>,
+) -> Result<Json<CombinedSearchResponse>, ApiError> {
+    let request: SemanticSearchRequest = serde_json::from_value(params).map_err(|e| {
+        ApiError::invalid_request(format!(
+            "Invalid search parameters: {} (expected fields: query, limit)",
+            e
+        ))
+    })?;
+    let search_request = EventSearchRequest {
+        limit: request.limit,
+        search: Some(request.query.clone()),
+        ..Default::default()
+    };
+    let limit = request.limit.unwrap_or(20);
+
+    let (local, relay) = with_deadline(&state, async {
+        Ok(tokio::join!(
+            state.embedding_service.semantic_search(&search_request),
+            state.relay_searcher.search(&request.query, limit)
+        ))
+    })
+    .await?;
+
+    let (local_ids, snippets) = match local {
+        Ok(response) => (response.event_ids, response.snippets),
+        Err(e) if relay.is_ok() => {
+            eprintln!(
+                "Warning: Local search failed, using relay results only: {}",
+                e
+            );
+            (Vec::new(), HashMap::new())
+        }
+        Err(e) => return Err(ApiError::backend(format!("Search failed: {}", e))),
+    };
+    let relay_events = relay.unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: Relay search failed, using local results only: {}",
+            e
+        );
+        Vec::new()
+    });
+    let limit = limit.max(local_ids.len());
+
+    Ok(Json(CombinedSearchResponse {
+        results: merge_results(local_ids, snippets, relay_events, limit),
+    }))
+}
+
+/// Stream newly indexed events matching a query over a WebSocket. After
+/// connecting, send a `LiveQuery` as a text message; the server answers
+/// `subscribed`, then sends a `match` message for every new event at least
+/// `min_score` similar to the query. Sending another query replaces it.
+#[utoipa::path(
+    get,
+    path = "/search/live",
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol; messages are LiveMessage JSON")
+    )
+)]
+async fn live_search(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| {
+        serve_live_search(
+            socket,
+            state.embedding_service.clone(),
+            state.live_feed.clone(),
+            state.live_min_score,
+        )
+    })
+}
+
+/// Search, then group the results into topics with k-means over their
+/// stored vectors, for "explore by topic" views. Accepts the same filters
+/// as `/events`; `limit` sets how many results are clustered.
+#[utoipa::path(
+    get,
+    path = "/search/clusters",
+    params(EventSearchRequest, ClusterParams),
+    responses(
+        (status = 200, description = "Labeled clusters with representative events", body = ClusterSearchResponse),
+        (status = 400, description = "Invalid parameters or filters", body = ApiError),
+        (status = 500, description = "Embedding or vector store failure", body = ApiError),
+        (status = 504, description = "Search didn't finish within SEARCH_TIMEOUT_SECS", body = ApiError)
+    )
+)]
+async fn cluster_search(
+    State(state): State<AppState>,
+    Query(cluster_params): Query<ClusterParams>,
+    Query(params): Query<serde_json::Value>,
+) -> Result<Json<ClusterSearchResponse>, ApiError> {
+    let clusters = cluster_params.clusters.unwrap_or(5);
+    if !(1..=20).contains(&clusters) {
+        return Err(ApiError::invalid_request(
+            "clusters must be between 1 and 20",
+        ));
+    }
+    let request = EventSearchRequest::from_query(params)
+        .map_err(|e| ApiError::invalid_request(format!("Invalid search parameters: {}", e)))?;
+    let field_errors = request.field_errors();
+    if !field_errors.is_empty() {
+        return Err(ApiError::invalid_filters(field_errors));
+    }
+
+    let clusters = with_deadline(&state, async {
+        state
+            .embedding_service
+            .cluster_search(&request, clusters)
+            .await
+            .map_err(|e| ApiError::backend(format!("Cluster search failed: {}", e)))
+    })
+    .await?;
+
+    Ok(Json(ClusterSearchResponse { clusters }))
+}
+
+/// "More like this" for several seeds: stored events and/or free text.
+/// Useful for building topical feeds from a handful of example notes.
+#[utoipa::path(
+    post,
+    path = "/search/similar",
+    request_body = SimilarEventsRequest,
+    responses(
+        (status = 200, description = "Events near the seeds, seeds excluded", body = SemanticSearchResponse),
+        (status = 400, description = "Malformed request or no seeds", body = ApiError),
+        (status = 500, description = "Embedding or vector store failure", body = ApiError),
+        (status = 504, description = "Search didn't finish within SEARCH_TIMEOUT_SECS", body = ApiError)
+    )
+)]
+async fn similar_events(
+    State(state): State<AppState>,
+    payload: Result<Json<SimilarEventsRequest>, JsonRejection>,
+) -> Result<Json<SemanticSearchResponse>, ApiError> {
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+    if request.event_ids.is_empty() && request.texts.iter().all(|text| text.trim().is_empty()) {
+        return Err(ApiError::invalid_request(
+            "Provide at least one seed in event_ids or texts",
+        ));
+    }
+
+    let response = with_deadline(&state, async {
+        state
+            .embedding_service
+            .similar_events(&request)
+            .await
+            .map_err(|e| ApiError::backend(format!("Similar search failed: {}", e)))
+    })
+    .await?;
+
+    Ok(Json(SemanticSearchResponse {
+        total_found: response.total_found,
+        event_ids: response.event_ids,
+        snippets: response.snippets,
+        threads: HashMap::new(),
+        hits: Vec::new(),
+        degraded: response.degraded,
+    }))
+}
+
+/// Completions for a search box, from queries other users searched for and
+/// hashtags used by indexed events.
+#[utoipa::path(
+    get,
+    path = "/suggest",
+    params(SuggestParams),
+    responses(
+        (status = 200, description = "Completions, most frequent first", body = SuggestResponse),
+        (status = 400, description = "Missing `q` parameter")
+    )
+)]
+async fn suggest(
+    State(state): State<AppState>,
+    Query(params): Query<SuggestParams>,
+) -> Json<SuggestResponse> {
+    let limit = params.limit.unwrap_or(10);
+
+    let queries = match &state.query_stats {
+        Some(query_stats) => query_stats.complete(&params.q, limit).unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to read query completions: {}", e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let hashtags = state
+        .hashtags
+        .get(&state.embedding_service)
         .await
-    {
-        Ok(response) => {
-            let search_response = SemanticSearchResponse {
-                total_found: response.total_found,
-                event_ids: response.event_ids,
-            };
-            Ok(Json(search_response))
+        .unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to count hashtags: {}", e);
+            Arc::new(Vec::new())
+        });
+
+    Json(SuggestResponse {
+        suggestions: suggestions(&params.q, queries, &hashtags, limit),
+    })
+}
+
+async fn search(
+    state: &AppState,
+    request: &EventSearchRequest,
+) -> Result<Json<SemanticSearchResponse>, ApiError> {
+    let field_errors = request.field_errors();
+    if !field_errors.is_empty() {
+        eprintln!("Invalid search request: {:?}", field_errors);
+        return Err(ApiError::invalid_filters(field_errors));
+    }
+
+    with_deadline(state, async {
+        let started = Instant::now();
+        match state.embedding_service.semantic_search(request).await {
+            Ok(response) => {
+                if let (Some(query_stats), Some(query)) = (&state.query_stats, &request.search)
+                    && let Err(e) = query_stats.record(
+                        query,
+                        started.elapsed().as_millis() as u64,
+                        response.event_ids.len(),
+                    )
+                {
+                    eprintln!("Warning: Failed to record query stats: {}", e);
+                }
+
+                let threads = if request.expand_thread.unwrap_or(false) {
+                    expand_threads(
+                        &state.embedding_service,
+                        &state.relay_searcher,
+                        &response.event_ids,
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Warning: Failed to expand threads: {}", e);
+                        HashMap::new()
+                    })
+                } else {
+                    HashMap::new()
+                };
+
+                let hits = match &request.fields {
+                    Some(fields) => state
+                        .embedding_service
+                        .project_hits(&response, fields)
+                        .await
+                        .map_err(|e| {
+                            ApiError::backend(format!("Failed to load result events: {}", e))
+                        })?,
+                    None => Vec::new(),
+                };
+
+                let search_response = SemanticSearchResponse {
+                    total_found: response.total_found,
+                    event_ids: response.event_ids,
+                    snippets: response.snippets,
+                    threads,
+                    hits,
+                    degraded: response.degraded,
+                };
+                Ok(Json(search_response))
+            }
+            Err(e) => {
+                eprintln!("Search error: {}", e);
+                Err(ApiError::backend(format!("Search failed: {}", e)))
+            }
         }
-        Err(e) => {
-            eprintln!("Semantic search error: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    })
+    .await
+}
+
+/// Runs the work of a search request under `SEARCH_TIMEOUT_SECS`. Dropping
+/// `work`, on timeout or when the client disconnects and the server drops
+/// the handler, cancels its embedding request and vector query; only a
+/// local model's embedding, which runs on a blocking thread, finishes in
+/// the background.
+async fn with_deadline<T>(
+    state: &AppState,
+    work: impl Future<Output = Result<T, ApiError>>,
+) -> Result<T, ApiError> {
+    let Some(timeout) = state.search_timeout else {
+        return work.await;
+    };
+    tokio::time::timeout(timeout, work)
+        .await
+        .unwrap_or_else(|_| {
+            eprintln!("Search cancelled after {}s", timeout.as_secs());
+            Err(ApiError::timeout(format!(
+                "Search did not finish within {}s",
+                timeout.as_secs()
+            )))
+        })
+}
+
+/// Liveness check, with optional dependency probes.
+#[utoipa::path(
+    get,
+    path = "/health",
+    params(HealthParams),
+    responses(
+        (status = 200, description = "Service and probed dependencies are healthy", body = HealthReport),
+        (status = 503, description = "A probed dependency is unhealthy", body = HealthReport)
+    )
+)]
+async fn health_check(
+    State(state): State<AppState>,
+    Query(params): Query<HealthParams>,
+) -> (StatusCode, Json<HealthReport>) {
+    let mut report = HealthReport::alive();
+
+    let deep = params
+        .deep
+        .as_deref()
+        .is_some_and(|deep| deep.is_empty() || deep == "true" || deep == "1");
+
+    if deep {
+        let embedding_service = &state.embedding_service;
+        report.add(
+            "embedding_provider",
+            health::probe(embedding_service.check_embedding_provider()).await,
+        );
+        report.add(
+            "vector_store",
+            health::probe(embedding_service.check_store()).await,
+        );
+        if !state.read_only {
+            report.add(
+                "event_queue",
+                health::probe(async {
+                    if state.event_queue.is_alive() {
+                        Ok(())
+                    } else {
+                        Err(anyhow::anyhow!("event processor is not running"))
+                    }
+                })
+                .await,
+            );
         }
     }
+
+    let status = if report.is_healthy() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(report))
+}
+
+/// Ingest queue depth, lag, throughput and failure counters in the
+/// Prometheus text format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain")
+    )
+)]
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let text = state
+        .event_queue
+        .snapshot()
+        .to_prometheus(state.dead_letters.len());
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text)
+}
+
+/// Most frequent queries and queries that found nothing, for tuning
+/// thresholds and models. Requires the admin token.
+#[utoipa::path(
+    get,
+    path = "/stats/queries",
+    params(QueryStatsParams),
+    responses(
+        (status = 200, description = "Query statistics", body = QueryStatsResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "Query stats are disabled or unreadable", body = ApiError)
+    )
+)]
+async fn query_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QueryStatsParams>,
+) -> Result<Json<QueryStatsResponse>, ApiError> {
+    require_admin(&state, &headers)?;
+    let Some(query_stats) = &state.query_stats else {
+        return Err(ApiError::backend(
+            "Query stats are disabled; set QUERY_STATS_PATH to enable them",
+        ));
+    };
+
+    let limit = params.limit.unwrap_or(20);
+    let read = || -> anyhow::Result<QueryStatsResponse> {
+        Ok(QueryStatsResponse {
+            top: query_stats.top_queries(limit)?,
+            zero_results: query_stats.zero_result_queries(limit)?,
+        })
+    };
+    read()
+        .map(Json)
+        .map_err(|e| ApiError::backend(format!("Failed to read query stats: {}", e)))
+}
+
+/// Rejects requests that would write to the index on read replicas.
+fn require_writable(state: &AppState) -> Result<(), ApiError> {
+    if state.read_only {
+        Err(ApiError::new(
+            ErrorCode::ReadOnly,
+            "This server is a read-only replica; send writes to the writer",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks the bearer token for `/admin` routes. Admin routes are disabled
+/// when no `ADMIN_TOKEN` is configured.
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(admin_token) = state.admin_token.as_deref() else {
+        return Err(ApiError::new(
+            ErrorCode::Unauthorized,
+            "Admin endpoints are disabled; set ADMIN_TOKEN to enable them",
+        ));
+    };
+
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if provided == Some(admin_token) {
+        Ok(())
+    } else {
+        Err(ApiError::new(
+            ErrorCode::Unauthorized,
+            "Missing or invalid admin token",
+        ))
+    }
+}
+
+/// Compact the table, prune old versions and re-optimize indexes now.
+#[utoipa::path(
+    post,
+    path = "/admin/optimize",
+    responses(
+        (status = 200, description = "Maintenance finished", body = MaintenanceResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 403, description = "Read-only replica", body = ApiError),
+        (status = 500, description = "Maintenance failed", body = ApiError)
+    )
+)]
+async fn optimize_table(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<MaintenanceResponse>, ApiError> {
+    require_admin(&state, &headers)?;
+    require_writable(&state)?;
+
+    let started = Instant::now();
+    state
+        .embedding_service
+        .optimize(chrono::Duration::hours(
+            state.maintenance.prune_older_than_hours,
+        ))
+        .await
+        .map_err(|e| ApiError::backend(format!("Maintenance failed: {}", e)))?;
+
+    Ok(Json(MaintenanceResponse {
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
+/// List the committed versions of the events table.
+#[utoipa::path(
+    get,
+    path = "/admin/versions",
+    responses(
+        (status = 200, description = "Table versions, oldest first", body = [TableVersion]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "Failed to read versions", body = ApiError)
+    )
+)]
+async fn list_versions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TableVersion>>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    state
+        .embedding_service
+        .list_versions()
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::backend(format!("Failed to list versions: {}", e)))
+}
+
+/// Roll the events table back to an earlier version, e.g. after a bad bulk
+/// ingest.
+#[utoipa::path(
+    post,
+    path = "/admin/rollback",
+    request_body = RollbackRequest,
+    responses(
+        (status = 200, description = "Table restored"),
+        (status = 400, description = "Malformed request", body = ApiError),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 403, description = "Read-only replica", body = ApiError),
+        (status = 500, description = "Rollback failed", body = ApiError)
+    )
+)]
+async fn rollback_table(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Result<Json<RollbackRequest>, JsonRejection>,
+) -> Result<(), ApiError> {
+    require_admin(&state, &headers)?;
+    require_writable(&state)?;
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+
+    println!("Rolling table back to version {}", request.version);
+    state
+        .embedding_service
+        .rollback(request.version)
+        .await
+        .map_err(|e| ApiError::backend(format!("Rollback failed: {}", e)))
+}
+
+/// List the registered embedding models and how many stored events each one
+/// embedded, to follow a migration from one model to another.
+#[utoipa::path(
+    get,
+    path = "/admin/models",
+    responses(
+        (status = 200, description = "Registered embedding models", body = [ModelStatus]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "Failed to count events", body = ApiError)
+    )
+)]
+async fn list_models(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ModelStatus>>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    let mut models = Vec::with_capacity(state.models.len());
+    for spec in &state.models {
+        let stored_events = state
+            .embedding_service
+            .count_events_for_model(&spec.id)
+            .await
+            .map_err(|e| ApiError::backend(format!("Failed to count events: {}", e)))?;
+        models.push(ModelStatus {
+            active: spec.id == state.embedding_service.model_id(),
+            spec: spec.clone(),
+            stored_events,
+        });
+    }
+
+    Ok(Json(models))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RequeueRequest {
+    /// Event ids to requeue; send `{}` to requeue every dead-lettered event
+    #[serde(default)]
+    ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct RequeueResponse {
+    requeued: usize,
+    /// Events still dead-lettered, e.g. because the queue filled up
+    remaining: usize,
+}
+
+/// List events that failed processing after every retry.
+#[utoipa::path(
+    get,
+    path = "/admin/dead-letters",
+    responses(
+        (status = 200, description = "Dead-lettered events", body = [DeadLetter]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError)
+    )
+)]
+async fn list_dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<DeadLetter>>, ApiError> {
+    require_admin(&state, &headers)?;
+    Ok(Json(state.dead_letters.list()))
+}
+
+/// Put dead-lettered events back on the event queue, e.g. after fixing the
+/// embedding provider.
+#[utoipa::path(
+    post,
+    path = "/admin/dead-letters/requeue",
+    request_body = RequeueRequest,
+    responses(
+        (status = 200, description = "Events requeued", body = RequeueResponse),
+        (status = 400, description = "Malformed request", body = ApiError),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 403, description = "Read-only replica", body = ApiError)
+    )
+)]
+async fn requeue_dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Result<Json<RequeueRequest>, JsonRejection>,
+) -> Result<Json<RequeueResponse>, ApiError> {
+    require_admin(&state, &headers)?;
+    require_writable(&state)?;
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+
+    let mut requeued = 0;
+    for letter in state.dead_letters.take(request.ids.as_deref()) {
+        match state.event_queue.enqueue(letter.event.clone()) {
+            Ok(()) => requeued += 1,
+            Err(e) => {
+                eprintln!("Failed to requeue event {}: {}", letter.event.id, e);
+                if let Err(e) = state.dead_letters.push(letter) {
+                    eprintln!("Failed to keep dead letter: {}", e);
+                }
+            }
+        }
+    }
+
+    println!("Requeued {} dead-lettered events", requeued);
+    Ok(Json(RequeueResponse {
+        requeued,
+        remaining: state.dead_letters.len(),
+    }))
+}
+
+/// Re-read `RELAY_SEARCH_CONFIG` and the `RELAY_SEARCH_*` variables and
+/// switch relay search over to the result.
+#[utoipa::path(
+    post,
+    path = "/admin/relays/reload",
+    responses(
+        (status = 200, description = "Relay search configuration now in use", body = RelaySearchConfig),
+        (status = 400, description = "The configuration is invalid", body = ApiError),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "Failed to apply the configuration", body = ApiError)
+    )
+)]
+async fn reload_relays(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<RelaySearchConfig>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    let config = RelaySearchConfig::from_env()
+        .map_err(|e| ApiError::invalid_request(format!("Invalid relay configuration: {}", e)))?;
+    state
+        .relay_searcher
+        .reload(config.clone())
+        .await
+        .map_err(|e| ApiError::backend(format!("Failed to reload relays: {}", e)))?;
+
+    println!("Relay search now uses {} relays", config.relays.len());
+    Ok(Json(config))
+}
+
+fn saved_searches(state: &AppState) -> Result<&SavedSearches, ApiError> {
+    state
+        .saved_searches
+        .as_deref()
+        .ok_or_else(|| ApiError::backend("Saved searches are disabled; set SAVED_SEARCHES_PATH"))
+}
+
+/// List saved searches.
+#[utoipa::path(
+    get,
+    path = "/admin/saved-searches",
+    responses(
+        (status = 200, description = "Saved searches, oldest first", body = [SavedSearch]),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "Saved searches are disabled", body = ApiError)
+    )
+)]
+async fn list_saved_searches(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SavedSearch>>, ApiError> {
+    require_admin(&state, &headers)?;
+    Ok(Json(saved_searches(&state)?.list()))
+}
+
+/// Register a query to be notified about. Every newly ingested event
+/// similar enough to it is POSTed to the webhook or sent as a direct
+/// message.
+#[utoipa::path(
+    post,
+    path = "/admin/saved-searches",
+    request_body = SavedSearchRequest,
+    responses(
+        (status = 200, description = "The registered search", body = SavedSearch),
+        (status = 400, description = "Malformed or invalid request", body = ApiError),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "Saved searches are disabled or the query could not be embedded", body = ApiError)
+    )
+)]
+async fn create_saved_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Result<Json<SavedSearchRequest>, JsonRejection>,
+) -> Result<Json<SavedSearch>, ApiError> {
+    require_admin(&state, &headers)?;
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+    let saved_searches = saved_searches(&state)?;
+
+    let request = saved_searches
+        .validate(request)
+        .map_err(|e| ApiError::invalid_request(e.to_string()))?;
+    let search = saved_searches
+        .add(request, &state.embedding_service)
+        .await
+        .map_err(|e| ApiError::backend(format!("Failed to save search: {}", e)))?;
+
+    println!("Registered saved search {}", search.id);
+    Ok(Json(search))
+}
+
+/// Stop notifying about a saved search.
+#[utoipa::path(
+    delete,
+    path = "/admin/saved-searches/{id}",
+    params(("id" = String, Path, description = "Saved search ID")),
+    responses(
+        (status = 204, description = "The search was removed"),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 404, description = "No saved search with this ID", body = ApiError),
+        (status = 500, description = "Saved searches are disabled", body = ApiError)
+    )
+)]
+async fn delete_saved_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&state, &headers)?;
+
+    let removed = saved_searches(&state)?
+        .remove(&id)
+        .map_err(|e| ApiError::backend(format!("Failed to remove saved search: {}", e)))?;
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::new(
+            ErrorCode::NotFound,
+            format!("No saved search with ID {}", id),
+        ))
+    }
+}
+
+/// Publish the top results of a query as a signed NIP-51 bookmark set
+/// (kind 30003), so the curated search can be followed from any Nostr
+/// client. Publishing under an existing identifier replaces that list.
+#[utoipa::path(
+    post,
+    path = "/admin/lists/publish",
+    request_body = PublishListRequest,
+    responses(
+        (status = 200, description = "The published list event", body = PublishListResponse),
+        (status = 400, description = "Malformed request", body = ApiError),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "List publishing is disabled, or the search or publishing failed", body = ApiError)
+    )
+)]
+async fn publish_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Result<Json<PublishListRequest>, JsonRejection>,
+) -> Result<Json<PublishListResponse>, ApiError> {
+    require_admin(&state, &headers)?;
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+    let Some(list_publisher) = &state.list_publisher else {
+        return Err(ApiError::backend(
+            "List publishing is disabled; set LIST_NSEC",
+        ));
+    };
+    let query = request.query.trim();
+    if query.is_empty() {
+        return Err(ApiError::invalid_request("query must not be empty"));
+    }
+
+    let search_request = EventSearchRequest {
+        limit: request.limit,
+        search: Some(query.to_string()),
+        ..Default::default()
+    };
+    let Json(results) = search(&state, &search_request).await?;
+
+    let identifier = request.identifier.unwrap_or_else(|| list_identifier(query));
+    let title = request.title.unwrap_or_else(|| query.to_string());
+    let published = list_publisher
+        .publish(&identifier, &title, &results.event_ids)
+        .await
+        .map_err(|e| ApiError::backend(format!("Failed to publish list: {}", e)))?;
+
+    println!(
+        "Published list {} with {} events to {} relays",
+        identifier,
+        results.event_ids.len(),
+        published.relays.len()
+    );
+    Ok(Json(published))
+}
+
+/// Run a query against the current and the migration's candidate model and
+/// show how their rankings differ, before switching reads over.
+#[utoipa::path(
+    post,
+    path = "/admin/migration/compare",
+    request_body = CompareRequest,
+    responses(
+        (status = 200, description = "Rankings of both models", body = RankingComparison),
+        (status = 400, description = "Malformed request", body = ApiError),
+        (status = 401, description = "Missing or invalid admin token", body = ApiError),
+        (status = 500, description = "No migration is configured, or a search failed", body = ApiError)
+    )
+)]
+async fn compare_migration(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    payload: Result<Json<CompareRequest>, JsonRejection>,
+) -> Result<Json<RankingComparison>, ApiError> {
+    require_admin(&state, &headers)?;
+    let Json(request) = payload.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+    let Some(migration_target) = &state.migration_target else {
+        return Err(ApiError::backend(
+            "Model migration is disabled; set MIGRATION_MODEL_ID",
+        ));
+    };
+    let query = request.query.trim();
+    if query.is_empty() {
+        return Err(ApiError::invalid_request("query must not be empty"));
+    }
+
+    let comparison = compare_models(
+        &state.embedding_service,
+        migration_target,
+        query,
+        request.limit,
+    )
+    .await
+    .map_err(|e| ApiError::backend(format!("Failed to compare models: {}", e)))?;
+    Ok(Json(comparison))
 }
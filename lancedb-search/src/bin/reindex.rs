@@ -0,0 +1,119 @@
+use anyhow::Result;
+use lancedb_search::{
+    config::{Config, StoreBackend},
+    embedding_service::EmbeddingSearchService,
+    lancedb_store::LanceDBStore,
+    reindex::{ReindexOptions, Reindexer},
+    relay_search::RelaySearcher,
+};
+use std::sync::Arc;
+
+/// Re-embeds every event of an existing table with the currently
+/// configured embedding model, writing them into a fresh table. Content is
+/// taken from the source table where it was stored in full and fetched
+/// from the relays in `RELAY_SEARCH_RELAYS` otherwise.
+///
+/// Usage: `reindex [--target TABLE] [--source TABLE] [--batch-size N]
+/// [--rate EVENTS_PER_SEC] [--no-relays]`; the source defaults to
+/// `LANCEDB_TABLE_NAME`. During a migration (`MIGRATION_MODEL_ID`) events
+/// are embedded with the candidate model and the target defaults to
+/// `MIGRATION_TABLE_NAME`; otherwise `--target` is required. Point
+/// `LANCEDB_TABLE_NAME` at the target once it is complete.
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let config = Config::from_env()?;
+    let mut source = config.table_name.clone();
+    let mut target = None;
+    let mut options = ReindexOptions {
+        batch_size: 100,
+        max_events_per_sec: None,
+    };
+    let mut use_relays = true;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--source" => {
+                source = args
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("--source needs a table name"))?;
+            }
+            "--target" => {
+                target = Some(
+                    args.next()
+                        .ok_or_else(|| anyhow::anyhow!("--target needs a table name"))?,
+                );
+            }
+            "--batch-size" => {
+                options.batch_size = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .filter(|&size: &usize| size > 0)
+                    .ok_or_else(|| anyhow::anyhow!("--batch-size needs a positive number"))?;
+            }
+            "--rate" => {
+                options.max_events_per_sec = Some(
+                    args.next()
+                        .and_then(|value| value.parse().ok())
+                        .filter(|&rate: &f64| rate > 0.0)
+                        .ok_or_else(|| anyhow::anyhow!("--rate needs a positive number"))?,
+                );
+            }
+            "--no-relays" => use_relays = false,
+            other => anyhow::bail!("Unknown argument {}", other),
+        }
+    }
+    let migration_target = config.migration_target()?;
+    let Some(target) = target.or_else(|| {
+        migration_target
+            .as_ref()
+            .map(|migration_target| migration_target.table_name.clone())
+    }) else {
+        anyhow::bail!("--target is required");
+    };
+    if target == source {
+        anyhow::bail!(
+            "The target table must differ from the source table {}",
+            source
+        );
+    }
+
+    if config.store_backend != StoreBackend::LanceDb {
+        anyhow::bail!("reindex reads its source from LanceDB tables only");
+    }
+    let source_store =
+        LanceDBStore::open_existing(&config.db_path, &source, &config.storage_options).await?;
+    let mut target_config = migration_target.unwrap_or_else(|| config.clone());
+    target_config.table_name = target.clone();
+    let service = Arc::new(EmbeddingSearchService::from_config(&target_config).await?);
+    let relay_searcher = use_relays.then(|| RelaySearcher::new(config.relay_search.clone()));
+
+    println!(
+        "Reindexing {} into {} with model {}",
+        source, target, target_config.embedding.model_id
+    );
+    let progress = Reindexer::new(source_store, service.clone(), relay_searcher, options)
+        .run()
+        .await?;
+
+    service.create_index().await.ok();
+
+    println!(
+        "Reindexed {} of {} events into {}: {} refetched from relays, {} unavailable, {} failed",
+        progress.reindexed,
+        progress.events,
+        target,
+        progress.refetched,
+        progress.unavailable,
+        progress.failed
+    );
+    if progress.reindexed < progress.events {
+        eprintln!(
+            "Warning: {} events are missing from {}",
+            progress.events - progress.reindexed,
+            target
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+/// Minimal client for an OpenAI-compatible chat completions endpoint.
+pub struct ChatClient {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl ChatClient {
+    pub fn new(api_url: String, api_key: Option<String>, model: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url,
+            api_key,
+            model,
+        }
+    }
+
+    /// Sends `prompt` as a single user message and returns the trimmed reply.
+    pub async fn complete(
+        &self,
+        prompt: &str,
+        max_tokens: u32,
+        temperature: f32,
+    ) -> Result<String> {
+        let payload = serde_json::json!({
+            "model": self.model,
+            "messages": [
+                {
+                    "role": "user",
+                    "content": prompt
+                }
+            ],
+            "max_tokens": max_tokens,
+            "temperature": temperature
+        });
+
+        let mut request = self
+            .client
+            .post(self.chat_completions_url())
+            .header("Content-Type", "application/json")
+            .json(&payload);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            anyhow::bail!(
+                "Chat completion request failed (status {}): {}",
+                status,
+                error_text
+            );
+        }
+
+        let response: ChatResponse = response.json().await?;
+        response
+            .choices
+            .first()
+            .map(|c| c.message.content.trim().to_string())
+            .filter(|content| !content.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Chat completion returned no content"))
+    }
+
+    fn chat_completions_url(&self) -> String {
+        let api_url = &self.api_url;
+        if api_url.ends_with("/chat/completions") {
+            api_url.clone()
+        } else if api_url.ends_with("/v1") {
+            format!("{}/chat/completions", api_url)
+        } else {
+            format!("{}/v1/chat/completions", api_url.trim_end_matches('/'))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: String,
+}
@@ -0,0 +1,101 @@
+/// Splits `text` into word-aligned chunks of at most `max_chars` characters,
+/// each starting with up to `overlap_chars` characters from the end of the
+/// previous chunk. Text that already fits is returned as a single chunk.
+pub fn chunk_text(text: &str, max_chars: usize, overlap_chars: usize) -> Vec<String> {
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return vec![text.to_string()];
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return vec![text.to_string()];
+    }
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < words.len() {
+        let mut end = start;
+        let mut len = 0;
+        while end < words.len() {
+            let word_len = words[end].chars().count() + usize::from(end > start);
+            if end > start && len + word_len > max_chars {
+                break;
+            }
+            len += word_len;
+            end += 1;
+        }
+
+        chunks.push(words[start..end].join(" "));
+        if end == words.len() {
+            break;
+        }
+
+        // Step back over whole words for the overlap, always moving forward
+        let mut next = end;
+        let mut overlap = 0;
+        while next > start + 1 {
+            let word_len = words[next - 1].chars().count() + 1;
+            if overlap + word_len > overlap_chars {
+                break;
+            }
+            overlap += word_len;
+            next -= 1;
+        }
+        start = next;
+    }
+
+    chunks
+}
+
+/// Averages chunk embeddings into one vector for the whole text.
+pub fn mean_pool(embeddings: &[Vec<f32>]) -> Vec<f32> {
+    let Some(first) = embeddings.first() else {
+        return Vec::new();
+    };
+
+    let mut pooled = vec![0.0; first.len()];
+    for embedding in embeddings {
+        for (sum, value) in pooled.iter_mut().zip(embedding) {
+            *sum += value;
+        }
+    }
+
+    let count = embeddings.len() as f32;
+    pooled.iter_mut().for_each(|value| *value /= count);
+    pooled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_one_chunk() {
+        assert_eq!(chunk_text("hello world", 100, 10), vec!["hello world"]);
+        assert_eq!(chunk_text("hello world", 0, 10), vec!["hello world"]);
+    }
+
+    #[test]
+    fn test_chunks_respect_limit_and_overlap() {
+        let chunks = chunk_text("one two three four five six", 10, 5);
+
+        assert_eq!(
+            chunks,
+            vec!["one two", "two three", "four five", "five six"]
+        );
+        assert!(chunks.iter().all(|chunk| chunk.chars().count() <= 10));
+    }
+
+    #[test]
+    fn test_long_word_still_progresses() {
+        let chunks = chunk_text("abcdefghijkl xy", 5, 2);
+        assert_eq!(chunks, vec!["abcdefghijkl", "xy"]);
+    }
+
+    #[test]
+    fn test_mean_pool() {
+        let pooled = mean_pool(&[vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert_eq!(pooled, vec![0.5, 0.5]);
+        assert!(mean_pool(&[]).is_empty());
+    }
+}
@@ -0,0 +1,286 @@
+use crate::embeddings::{cosine_similarity, l2_normalize};
+use crate::ranking::mean_vector;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Representative events listed per cluster.
+const REPRESENTATIVES: usize = 3;
+/// Upper bound on k-means refinement rounds.
+const MAX_ITERATIONS: usize = 20;
+/// Terms used to label a cluster.
+const LABEL_TERMS: usize = 3;
+
+/// Words too common to say anything about a topic.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "because",
+    "been", "but", "by", "can", "could", "did", "do", "does", "for", "from", "get", "got", "had",
+    "has", "have", "he", "her", "here", "him", "his", "how", "i", "if", "in", "into", "is", "it",
+    "its", "just", "like", "me", "more", "my", "no", "not", "now", "of", "on", "one", "only", "or",
+    "our", "out", "she", "so", "some", "than", "that", "the", "their", "them", "then", "there",
+    "these", "they", "this", "to", "too", "up", "us", "was", "we", "were", "what", "when", "which",
+    "who", "why", "will", "with", "would", "you", "your",
+];
+
+/// A group of search results about the same topic.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TopicCluster {
+    /// The most distinctive terms of the members' stored content, joined;
+    /// absent when content storage is disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Member event ids, in search rank order
+    pub event_ids: Vec<String>,
+    /// Members closest to the cluster centroid, most central first
+    pub representatives: Vec<String>,
+}
+
+/// A search result to cluster: event id, vector and stored content.
+pub struct ClusterInput {
+    pub id: String,
+    pub embedding: Vec<f32>,
+    pub content: Option<String>,
+}
+
+/// Splits ranked results into at most `k` topics, largest first.
+pub fn cluster_results(results: &[ClusterInput], k: usize) -> Vec<TopicCluster> {
+    let vectors: Vec<&[f32]> = results
+        .iter()
+        .map(|result| result.embedding.as_slice())
+        .collect();
+    let assignments = kmeans(&vectors, k);
+
+    let mut clusters: Vec<TopicCluster> = Vec::new();
+    for cluster in 0..k.min(results.len()) {
+        let members: Vec<&ClusterInput> = results
+            .iter()
+            .zip(&assignments)
+            .filter(|(_, assigned)| **assigned == cluster)
+            .map(|(result, _)| result)
+            .collect();
+        let Some(centroid) = mean_vector(
+            &members
+                .iter()
+                .map(|member| member.embedding.as_slice())
+                .collect::<Vec<_>>(),
+        ) else {
+            continue;
+        };
+
+        let mut by_centrality: Vec<(&str, f32)> = members
+            .iter()
+            .map(|member| {
+                (
+                    member.id.as_str(),
+                    cosine_similarity(&member.embedding, &centroid),
+                )
+            })
+            .collect();
+        by_centrality.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let texts: Vec<&str> = members
+            .iter()
+            .filter_map(|member| member.content.as_deref())
+            .collect();
+        let background: Vec<&str> = results
+            .iter()
+            .zip(&assignments)
+            .filter(|(_, assigned)| **assigned != cluster)
+            .filter_map(|(result, _)| result.content.as_deref())
+            .collect();
+        let terms = if texts.is_empty() {
+            Vec::new()
+        } else {
+            label_terms(&texts, &background)
+        };
+
+        clusters.push(TopicCluster {
+            label: (!terms.is_empty()).then(|| terms.join(", ")),
+            event_ids: members.iter().map(|member| member.id.clone()).collect(),
+            representatives: by_centrality
+                .into_iter()
+                .take(REPRESENTATIVES)
+                .map(|(id, _)| id.to_string())
+                .collect(),
+        });
+    }
+
+    clusters.sort_by(|a, b| b.event_ids.len().cmp(&a.event_ids.len()));
+    clusters
+}
+
+/// Groups `vectors` into at most `k` clusters by spherical k-means
+/// (cosine similarity to unit-length centroids). Initial centroids are
+/// picked farthest-first starting from the first vector, so results are
+/// deterministic and the top-ranked hit anchors a cluster. Returns the
+/// cluster index of every vector.
+pub fn kmeans(vectors: &[&[f32]], k: usize) -> Vec<usize> {
+    if vectors.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    let k = k.min(vectors.len());
+
+    let mut centroids: Vec<Vec<f32>> = vec![unit(vectors[0])];
+    while centroids.len() < k {
+        let farthest = (0..vectors.len())
+            .min_by(|&a, &b| {
+                nearest(vectors[a], &centroids)
+                    .1
+                    .total_cmp(&nearest(vectors[b], &centroids).1)
+            })
+            .unwrap_or(0);
+        centroids.push(unit(vectors[farthest]));
+    }
+
+    let mut assignments: Vec<usize> = vectors
+        .iter()
+        .map(|vector| nearest(vector, &centroids).0)
+        .collect();
+    for _ in 0..MAX_ITERATIONS {
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let mut sum = vec![0.0; centroid.len()];
+            for (vector, _) in vectors
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, assigned)| **assigned == cluster)
+            {
+                for (total, value) in sum.iter_mut().zip(unit(vector)) {
+                    *total += value;
+                }
+            }
+            // Keep the old centroid for a cluster that lost all members
+            if sum.iter().any(|value| *value != 0.0) {
+                l2_normalize(&mut sum);
+                *centroid = sum;
+            }
+        }
+
+        let updated: Vec<usize> = vectors
+            .iter()
+            .map(|vector| nearest(vector, &centroids).0)
+            .collect();
+        if updated == assignments {
+            break;
+        }
+        assignments = updated;
+    }
+
+    assignments
+}
+
+/// Index of the most similar centroid and the similarity to it.
+fn nearest(vector: &[f32], centroids: &[Vec<f32>]) -> (usize, f32) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(index, centroid)| (index, cosine_similarity(vector, centroid)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+fn unit(vector: &[f32]) -> Vec<f32> {
+    let mut vector = vector.to_vec();
+    l2_normalize(&mut vector);
+    vector
+}
+
+/// Terms that are frequent in `texts` but rare in `background`, for naming
+/// a cluster against the rest of the results.
+pub fn label_terms(texts: &[&str], background: &[&str]) -> Vec<String> {
+    let member_counts = term_document_counts(texts);
+    let background_counts = term_document_counts(background);
+
+    let mut scored: Vec<(String, f32)> = member_counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1 || texts.len() == 1)
+        .map(|(term, count)| {
+            let elsewhere = background_counts.get(&term).copied().unwrap_or(0);
+            let score = count as f32 / texts.len() as f32
+                - elsewhere as f32 / background.len().max(1) as f32;
+            (term, score)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    scored
+        .into_iter()
+        .take(LABEL_TERMS)
+        .map(|(term, _)| term)
+        .collect()
+}
+
+/// Number of texts each term appears in.
+fn term_document_counts(texts: &[&str]) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for text in texts {
+        let mut terms: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric() && c != '#')
+            .map(|term| term.trim_start_matches('#').to_lowercase())
+            .filter(|term| term.chars().count() > 2 && !STOPWORDS.contains(&term.as_str()))
+            // Links and bech32 entities (npub1..., note1...) aren't topics
+            .filter(|term| !term.starts_with("http") && !is_bech32_entity(term))
+            .collect();
+        terms.sort();
+        terms.dedup();
+        for term in terms {
+            *counts.entry(term).or_default() += 1;
+        }
+    }
+    counts
+}
+
+fn is_bech32_entity(term: &str) -> bool {
+    ["npub1", "nsec1", "note1", "nevent1", "nprofile1", "naddr1"]
+        .iter()
+        .any(|prefix| term.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmeans_separates_distinct_directions() {
+        let a1 = [1.0, 0.0, 0.0];
+        let a2 = [0.9, 0.1, 0.0];
+        let b1 = [0.0, 1.0, 0.0];
+        let b2 = [0.1, 0.9, 0.0];
+
+        let assignments = kmeans(&[&a1, &b1, &a2, &b2], 2);
+        assert_eq!(assignments[0], assignments[2]);
+        assert_eq!(assignments[1], assignments[3]);
+        assert_ne!(assignments[0], assignments[1]);
+        assert_eq!(kmeans(&[&a1], 3), vec![0]);
+    }
+
+    #[test]
+    fn test_cluster_results_labels_and_orders_clusters() {
+        let result = |id: &str, embedding: [f32; 2], content: &str| ClusterInput {
+            id: id.to_string(),
+            embedding: embedding.to_vec(),
+            content: Some(content.to_string()),
+        };
+        let results = vec![
+            result("a", [1.0, 0.0], "bitcoin mining fees"),
+            result("b", [0.0, 1.0], "sourdough bread recipe"),
+            result("c", [0.9, 0.1], "bitcoin fees spike"),
+            result("d", [0.95, 0.05], "bitcoin halving"),
+        ];
+
+        let clusters = cluster_results(&results, 2);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].event_ids, vec!["a", "c", "d"]);
+        assert_eq!(clusters[0].label.as_deref(), Some("bitcoin, fees"));
+        assert_eq!(clusters[0].representatives.len(), 3);
+        assert_eq!(clusters[1].event_ids, vec!["b"]);
+    }
+
+    #[test]
+    fn test_label_terms_prefer_distinctive_words() {
+        let members = ["Rust async runtimes", "tokio is an async runtime for Rust"];
+        let others = ["Bitcoin price today", "Rust in the kernel"];
+
+        let terms = label_terms(&members, &others);
+        assert_eq!(terms.first().map(String::as_str), Some("async"));
+        assert!(!terms.iter().any(|term| term == "the"));
+    }
+}
@@ -1,51 +1,232 @@
-use std::time::Duration;
+use crate::collect_checkpoint::CollectCheckpoints;
+use crate::config::CollectConfig;
+use crate::embedding_service::EmbeddingSearchService;
+use crate::nostr::NostrEvent;
+use anyhow::Result;
+use nostr_sdk::{Client, Filter, Kind, PublicKey, Timestamp};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use nostr_sdk::prelude::*;
+/// Backfills the index from relays. `[since, until)` is walked in
+/// `window_secs` windows, oldest first. For each window every relay is
+/// asked for its events, the merged events are embedded and stored, and
+/// then each relay's checkpoint is moved to the end of the window.
+pub struct Collector {
+    service: Arc<EmbeddingSearchService>,
+    client: Client,
+    config: CollectConfig,
+    authors: Vec<PublicKey>,
+    checkpoints: CollectCheckpoints,
+}
+
+/// Running totals, reported after every window.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CollectProgress {
+    pub windows: usize,
+    pub fetched: usize,
+    pub indexed: usize,
+    /// Events in batches that could not be embedded or stored
+    pub failed: usize,
+}
+
+impl Collector {
+    pub fn new(service: Arc<EmbeddingSearchService>, config: CollectConfig) -> Result<Self> {
+        let authors = config
+            .authors
+            .iter()
+            .map(|author| {
+                PublicKey::parse(author)
+                    .map_err(|e| anyhow::anyhow!("Invalid author {}: {}", author, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let checkpoints = CollectCheckpoints::open(&config.checkpoint_path)?;
+
+        Ok(Self {
+            service,
+            client: Client::default(),
+            config,
+            authors,
+            checkpoints,
+        })
+    }
+
+    /// Collects the range, starting each relay after its checkpoint.
+    /// Checkpoints only advance over windows that were fully indexed, so
+    /// after a failure the next run starts again at the failed window.
+    pub async fn run(&self) -> Result<CollectProgress> {
+        // Where each relay continues from
+        let mut relays: HashMap<String, i64> = HashMap::new();
+        for relay in &self.config.relays {
+            let resume_at = match self.checkpoints.get(relay)? {
+                Some(mark) => (mark + 1).max(self.config.since),
+                None => self.config.since,
+            };
+            if resume_at < self.config.until {
+                self.client.add_relay(relay.as_str()).await?;
+                relays.insert(relay.clone(), resume_at);
+            } else {
+                println!("{} is already collected up to {}", relay, self.config.until);
+            }
+        }
+        let Some(&first) = relays.values().min() else {
+            return Ok(CollectProgress::default());
+        };
+        if first > self.config.since {
+            println!("Resuming from checkpoints at {}", first);
+        }
+        self.client.connect().await;
 
-pub async fn collect() -> Result<()> {
-    tracing_subscriber::fmt::init();
+        let CollectConfig {
+            until, window_secs, ..
+        } = self.config;
+        let total_windows = (until - first + window_secs - 1) / window_secs;
+        let started = Instant::now();
+        let mut progress = CollectProgress::default();
+        // Cleared after the first failed batch, so checkpoints never skip
+        // over events that weren't indexed
+        let mut checkpointing = true;
 
-    let _public_key =
-        PublicKey::from_bech32("npub1080l37pfvdpyuzasyuy2ytjykjvq3ylr5jlqlg7tvzjrh9r8vn3sf5yaph")?;
+        let mut start = first;
+        while start < until && !relays.is_empty() {
+            let end = (start + window_secs).min(until);
+            let due: Vec<(String, i64)> = relays
+                .iter()
+                .filter(|(_, resume_at)| **resume_at < end)
+                .map(|(relay, resume_at)| (relay.clone(), (*resume_at).max(start)))
+                .collect();
 
-    let keys = Keys::parse("nsec1ufnus6pju578ste3v90xd5m2decpuzpql2295m3sknqcjzyys9ls0qlc85")?;
-    let client = Client::new(keys);
+            let fetches = due.iter().map(|(relay, since)| async move {
+                (relay, self.fetch_window(relay, *since, end).await)
+            });
+            let mut seen = HashSet::new();
+            let mut events = Vec::new();
+            let mut fetched_from = Vec::new();
+            for (relay, result) in futures::future::join_all(fetches).await {
+                match result {
+                    Ok(found) => {
+                        events.extend(
+                            found
+                                .into_iter()
+                                .filter(|event| seen.insert(event.id.clone())),
+                        );
+                        fetched_from.push(relay.clone());
+                    }
+                    Err(e) => {
+                        // Later windows would move its checkpoint past the
+                        // missing one, so the relay is left for the next run
+                        eprintln!(
+                            "Failed to fetch {}..{} from {}, skipping it for the rest of this run: {}",
+                            start, end, relay, e
+                        );
+                        relays.remove(relay);
+                    }
+                }
+            }
+            events.sort_by_key(|event| event.created_at);
+            progress.windows += 1;
+            progress.fetched += events.len();
 
-    client.add_relay("ws://localhost:8080").await?;
+            let mut window_failed = false;
+            for batch in events.chunks(self.config.batch_size) {
+                match self.service.embed_and_store_events(batch).await {
+                    Ok(()) => progress.indexed += batch.len(),
+                    Err(e) => {
+                        eprintln!(
+                            "Failed to index {} events from window {}..{}: {}",
+                            batch.len(),
+                            start,
+                            end,
+                            e
+                        );
+                        progress.failed += batch.len();
+                        window_failed = true;
+                    }
+                }
+            }
 
-    client.connect().await;
+            if window_failed && checkpointing {
+                eprintln!(
+                    "Checkpoints stay at {} until a run indexes this window",
+                    start
+                );
+                checkpointing = false;
+            }
+            if checkpointing {
+                for relay in &fetched_from {
+                    self.checkpoints.advance(relay, end - 1)?;
+                }
+            }
 
-    // Publish a text note
-    let builder = EventBuilder::text_note("Hello world");
-    let output = client.send_event_builder(builder).await?;
-    println!("Event ID: {}", output.id().to_bech32()?);
-    println!("Sent to: {:?}", output.success);
-    println!("Not sent to: {:?}", output.failed);
+            println!(
+                "Window {}/{} ({}..{}): {} events from {} relays; {} indexed, {} failed, {:.1} events/s",
+                progress.windows,
+                total_windows,
+                start,
+                end,
+                events.len(),
+                fetched_from.len(),
+                progress.indexed,
+                progress.failed,
+                progress.indexed as f64 / started.elapsed().as_secs_f64().max(1.0)
+            );
+            start = end;
+        }
 
-    // Create a text note POW event to relays
-    let builder = EventBuilder::text_note("POW text note from rust-nostr").pow(20);
-    client.send_event_builder(builder).await?;
+        self.client.disconnect().await;
+        Ok(progress)
+    }
 
-    // Send a text note POW event to specific relays
-    let builder = EventBuilder::text_note("POW text note from rust-nostr 16").pow(16);
-    client
-        .send_event_builder_to(["ws://localhost:8080"], builder)
-        .await?;
+    /// Fetches every event `relay` has from `[start, end)`.
+    /// While the relay answers with full pages, the older rest of the
+    /// window is requested again.
+    async fn fetch_window(&self, relay: &str, start: i64, end: i64) -> Result<Vec<NostrEvent>> {
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let mut seen = HashSet::new();
+        let mut events = Vec::new();
+        // `until` is inclusive in Nostr filters
+        let mut until = end - 1;
 
-    let client = Client::default();
-    client.add_relay("ws://localhost:8080").await?;
+        loop {
+            let filter = self
+                .filter()
+                .since(Timestamp::from(start as u64))
+                .until(Timestamp::from(until as u64))
+                .limit(self.config.page_limit);
+            let page = self
+                .client
+                .fetch_events_from([relay], filter, timeout)
+                .await?;
 
-    client.connect().await;
+            let full_page = page.len() >= self.config.page_limit;
+            let mut oldest = until;
+            let mut new_events = 0;
+            for event in page {
+                let event = NostrEvent::from(&event);
+                oldest = oldest.min(event.created_at);
+                if seen.insert(event.id.clone()) {
+                    events.push(event);
+                    new_events += 1;
+                }
+            }
 
-    // let filter = Filter::new().author(public_key).kind(Kind::Metadata);
-    // let events = client.fetch_events(filter, Duration::from_secs(10)).await?;
-    // println!("{events:#?}");
+            // Pages overlap by one second, so a page without new events
+            // means the rest of the window has been seen
+            if !full_page || new_events == 0 || oldest <= start {
+                break;
+            }
+            until = oldest;
+        }
 
-    let filter = Filter::new().limit(3);
-    let events = client
-        .fetch_events_from(["ws://localhost:8080"], filter, Duration::from_secs(10))
-        .await?;
-    println!("{events:#?}");
+        Ok(events)
+    }
 
-    Ok(())
+    fn filter(&self) -> Filter {
+        let filter = Filter::new().kinds(self.config.kinds.iter().map(|&kind| Kind::from(kind)));
+        if self.authors.is_empty() {
+            filter
+        } else {
+            filter.authors(self.authors.clone())
+        }
+    }
 }
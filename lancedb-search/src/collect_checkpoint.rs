@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+/// Per-relay high-water marks of the `collect` backfill. A relay's mark is
+/// the `created_at` up to which everything it returned has been indexed,
+/// so a restarted collector continues from there instead of fetching and
+/// embedding the whole range again.
+pub struct CollectCheckpoints {
+    db: sled::Db,
+}
+
+impl CollectCheckpoints {
+    pub fn open(path: &str) -> Result<Self> {
+        let db = sled::open(path).map_err(|e| {
+            anyhow::anyhow!("Failed to open collect checkpoints at {}: {}", path, e)
+        })?;
+        Ok(Self { db })
+    }
+
+    pub fn get(&self, relay: &str) -> Result<Option<i64>> {
+        let Some(value) = self.db.get(relay.as_bytes())? else {
+            return Ok(None);
+        };
+        let bytes: [u8; 8] = value
+            .as_ref()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Corrupt collect checkpoint for {}", relay))?;
+        Ok(Some(i64::from_be_bytes(bytes)))
+    }
+
+    /// Moves `relay`'s mark forward to `created_at`; marks never move back.
+    pub fn advance(&self, relay: &str, created_at: i64) -> Result<()> {
+        if self.get(relay)?.is_some_and(|mark| mark >= created_at) {
+            return Ok(());
+        }
+        self.db
+            .insert(relay.as_bytes(), &created_at.to_be_bytes())?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks_only_move_forward() {
+        let checkpoints = CollectCheckpoints {
+            db: sled::Config::new().temporary(true).open().unwrap(),
+        };
+
+        assert_eq!(checkpoints.get("wss://nos.lol").unwrap(), None);
+        checkpoints.advance("wss://nos.lol", 200).unwrap();
+        checkpoints.advance("wss://nos.lol", 100).unwrap();
+        checkpoints.advance("wss://relay.damus.io", 50).unwrap();
+
+        assert_eq!(checkpoints.get("wss://nos.lol").unwrap(), Some(200));
+        assert_eq!(checkpoints.get("wss://relay.damus.io").unwrap(), Some(50));
+    }
+}
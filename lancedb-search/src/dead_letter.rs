@@ -0,0 +1,180 @@
+use crate::event_wal::EventWal;
+use crate::nostr::NostrEvent;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+/// An event the processor gave up on after exhausting its retries.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetter {
+    pub event: NostrEvent,
+    /// Error from the last attempt
+    pub error: String,
+    pub attempts: u32,
+    /// Unix timestamp of the last attempt
+    pub failed_at: i64,
+}
+
+/// Failed events kept for inspection and requeueing through the admin API.
+/// In memory it holds at most `capacity` entries and drops the oldest beyond
+/// that; persisted in a WAL, every entry is kept until it is taken. A
+/// capacity of 0 discards failed events either way.
+pub struct DeadLetterStore {
+    entries: Mutex<VecDeque<DeadLetter>>,
+    capacity: usize,
+    wal: Option<Arc<EventWal>>,
+}
+
+impl DeadLetterStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            capacity,
+            wal: None,
+        }
+    }
+
+    /// Keeps entries in `wal`, starting with those it holds from previous
+    /// runs.
+    pub fn persisted(capacity: usize, wal: Arc<EventWal>) -> Self {
+        let entries = wal.dead_letters().unwrap_or_else(|e| {
+            eprintln!("Failed to read dead letters from the WAL: {}", e);
+            Vec::new()
+        });
+        Self {
+            entries: Mutex::new(entries.into()),
+            capacity,
+            wal: Some(wal),
+        }
+    }
+
+    /// Adds an entry. Fails only when it can't be persisted, in which case
+    /// it isn't kept at all.
+    pub fn push(&self, letter: DeadLetter) -> Result<()> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        if let Some(wal) = &self.wal {
+            wal.add_dead_letter(&letter)?;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        // A requeued event that fails again replaces its old entry
+        entries.retain(|entry| entry.event.id != letter.event.id);
+        while self.wal.is_none() && entries.len() >= self.capacity {
+            if let Some(dropped) = entries.pop_front() {
+                eprintln!(
+                    "Dead-letter store full, dropping event {}",
+                    dropped.event.id
+                );
+            }
+        }
+        entries.push_back(letter);
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Removes and returns the entries for `ids`, or every entry when `ids`
+    /// is `None`.
+    pub fn take(&self, ids: Option<&[String]>) -> Vec<DeadLetter> {
+        let mut entries = self.entries.lock().unwrap();
+        let taken: Vec<DeadLetter> = match ids {
+            Some(ids) => {
+                let (taken, kept): (Vec<_>, VecDeque<_>) = entries
+                    .drain(..)
+                    .partition(|entry| ids.contains(&entry.event.id));
+                *entries = kept;
+                taken
+            }
+            None => entries.drain(..).collect(),
+        };
+
+        if let Some(wal) = &self.wal {
+            for letter in &taken {
+                if let Err(e) = wal.remove_dead_letter(&letter.event.id) {
+                    eprintln!(
+                        "Failed to remove dead letter {} from the WAL: {}",
+                        letter.event.id, e
+                    );
+                }
+            }
+        }
+        taken
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(id: &str) -> DeadLetter {
+        DeadLetter {
+            event: NostrEvent {
+                id: id.to_string(),
+                pubkey: "pubkey".to_string(),
+                created_at: 0,
+                kind: 1,
+                tags: vec![],
+                content: "hello".to_string(),
+                sig: String::new(),
+                summary: None,
+            },
+            error: "boom".to_string(),
+            attempts: 4,
+            failed_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest_and_take_by_id() {
+        let store = DeadLetterStore::new(2);
+        store.push(letter("a")).unwrap();
+        store.push(letter("b")).unwrap();
+        store.push(letter("c")).unwrap();
+
+        let ids: Vec<String> = store.list().into_iter().map(|l| l.event.id).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+
+        let taken = store.take(Some(&["c".to_string()]));
+        assert_eq!(taken.len(), 1);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.take(None).len(), 1);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_persisted_entries_survive_restart_and_capacity() {
+        let wal = Arc::new(EventWal::temporary());
+        let store = DeadLetterStore::persisted(1, wal.clone());
+        store.push(letter("a")).unwrap();
+        store.push(letter("b")).unwrap();
+        drop(store);
+
+        // Reopened as after a restart
+        let store = DeadLetterStore::persisted(1, wal.clone());
+        let ids: Vec<String> = store.list().into_iter().map(|l| l.event.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        store.take(Some(&["a".to_string()]));
+        let ids: Vec<String> = wal
+            .dead_letters()
+            .unwrap()
+            .into_iter()
+            .map(|l| l.event.id)
+            .collect();
+        assert_eq!(ids, vec!["b"]);
+    }
+}
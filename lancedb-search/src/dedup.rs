@@ -0,0 +1,132 @@
+use crate::config::DedupConfig;
+use crate::embeddings::cosine_similarity;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+struct RecentEvent {
+    id: String,
+    created_at: i64,
+    embedding: Vec<f32>,
+}
+
+/// Remembers the latest embeddings of each author to recognize reposts of
+/// nearly the same text. Only kept in memory, so duplicates of events
+/// indexed before a restart aren't recognized.
+pub struct DuplicateDetector {
+    config: DedupConfig,
+    recent: Mutex<HashMap<String, VecDeque<RecentEvent>>>,
+}
+
+impl DuplicateDetector {
+    pub fn new(config: DedupConfig) -> Self {
+        Self {
+            config,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &DedupConfig {
+        &self.config
+    }
+
+    /// Returns the id of a recent event by `pubkey` that this one nearly
+    /// duplicates. Otherwise the event is remembered for later checks.
+    /// An event seen again under the same id is never its own duplicate.
+    pub fn check(
+        &self,
+        pubkey: &str,
+        event_id: &str,
+        created_at: i64,
+        embedding: &[f32],
+    ) -> Option<String> {
+        let mut recent = self.recent.lock().unwrap();
+
+        if let Some(events) = recent.get(pubkey) {
+            let duplicate = events
+                .iter()
+                .filter(|event| {
+                    event.id != event_id
+                        && (event.created_at - created_at).abs() <= self.config.window_secs
+                })
+                .find(|event| {
+                    cosine_similarity(&event.embedding, embedding) >= self.config.threshold
+                });
+            if let Some(duplicate) = duplicate {
+                return Some(duplicate.id.clone());
+            }
+        }
+
+        if !recent.contains_key(pubkey) && recent.len() >= self.config.max_authors.max(1) {
+            forget_least_active(&mut recent);
+        }
+        let events = recent.entry(pubkey.to_string()).or_default();
+        if !events.iter().any(|event| event.id == event_id) {
+            events.push_back(RecentEvent {
+                id: event_id.to_string(),
+                created_at,
+                embedding: embedding.to_vec(),
+            });
+        }
+        while events.len() > self.config.max_per_author.max(1) {
+            events.pop_front();
+        }
+        None
+    }
+}
+
+/// Drops the author whose newest remembered event is the oldest.
+fn forget_least_active(recent: &mut HashMap<String, VecDeque<RecentEvent>>) {
+    let least_active = recent
+        .iter()
+        .min_by_key(|(_, events)| {
+            events
+                .iter()
+                .map(|event| event.created_at)
+                .max()
+                .unwrap_or(i64::MIN)
+        })
+        .map(|(pubkey, _)| pubkey.clone());
+    if let Some(pubkey) = least_active {
+        recent.remove(&pubkey);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DuplicateAction;
+
+    fn detector(max_authors: usize) -> DuplicateDetector {
+        DuplicateDetector::new(DedupConfig {
+            threshold: 0.95,
+            window_secs: 3600,
+            max_per_author: 2,
+            max_authors,
+            action: DuplicateAction::Skip,
+        })
+    }
+
+    #[test]
+    fn test_detects_near_duplicates_by_the_same_author() {
+        let detector = detector(10);
+        assert_eq!(detector.check("alice", "a", 0, &[1.0, 0.0]), None);
+        assert_eq!(
+            detector.check("alice", "b", 60, &[1.0, 0.01]),
+            Some("a".to_string())
+        );
+        // Retried events aren't their own duplicates
+        assert_eq!(detector.check("alice", "a", 0, &[1.0, 0.0]), None);
+        // Other authors, distant texts and old events don't count
+        assert_eq!(detector.check("bob", "c", 60, &[1.0, 0.0]), None);
+        assert_eq!(detector.check("alice", "d", 60, &[0.0, 1.0]), None);
+        assert_eq!(detector.check("alice", "e", 7200, &[0.0, 1.0]), None);
+    }
+
+    #[test]
+    fn test_forgets_least_active_authors() {
+        let detector = detector(1);
+        detector.check("alice", "a", 0, &[1.0, 0.0]);
+        detector.check("bob", "b", 10, &[1.0, 0.0]);
+        assert_eq!(detector.check("alice", "c", 20, &[1.0, 0.0]), None);
+    }
+}
@@ -1,14 +1,76 @@
 use crate::{
-    EventSearchRequest, EventSearchResponse,
-    embeddings::EmbeddingService,
-    lancedb_store::LanceDBStore,
-    nostr::{NostrEvent, NostrEventWithEmbedding},
+    EventSearchRequest, EventSearchResponse, ProfileMatch, RankingMode, ResultField, ResultHit,
+    SearchMode, SeedFusion, SimilarEventsRequest, StoredEvent, VectorSpace,
+    cache::ResultCache,
+    chunking,
+    clustering::{self, ClusterInput, TopicCluster},
+    config::{
+        ChunkStrategy, ChunkingConfig, Config, DuplicateAction, IndexConfig, RetentionConfig,
+        SearchConfig,
+    },
+    dedup::DuplicateDetector,
+    embeddings::{EmbeddingService, cosine_similarity},
+    follow_graph::FollowGraph,
+    image_embeddings::ImageEmbedder,
+    lancedb_store::{LanceDBStore, distance_to_relevance},
+    media_descriptions::MediaDescriber,
+    nostr::{self, NostrEvent, NostrEventWithEmbedding, truncate_to_bytes},
+    query_expansion::QueryExpander,
+    ranking,
+    summarizer::ContentSummarizer,
+    tombstones::Tombstones,
+    vector_store::{
+        self, ExportedRow, SearchFilters, SearchHit, TableVersion, VectorColumn, VectorStore,
+    },
 };
 use anyhow::Result;
+use seekstr_core::url_extractor::{extract_imeta_image_urls, media_only_url};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Default similarity to the `exclude` text above which results are dropped.
+const DEFAULT_EXCLUDE_THRESHOLD: f32 = 0.6;
+/// How many extra candidates to fetch when results are filtered or
+/// re-ranked after the vector search.
+const OVERFETCH_FACTOR: usize = 3;
+/// Rank offset used when fusing semantic and keyword results.
+const RRF_K: f32 = 60.0;
+/// How many deleted events are remembered to keep copies that were still
+/// in flight out of the store.
+const TOMBSTONE_CAPACITY: usize = 100_000;
 
 pub struct EmbeddingSearchService {
     embedding_service: EmbeddingService,
-    lancedb_store: LanceDBStore,
+    store: Box<dyn VectorStore>,
+    search_config: SearchConfig,
+    query_expander: Option<QueryExpander>,
+    result_cache: ResultCache<EventSearchResponse>,
+    /// When set, event content up to this many bytes is stored alongside
+    /// the embeddings and returned as search snippets.
+    content_max_bytes: Option<usize>,
+    /// When set, long content is split into chunks before embedding.
+    chunking: Option<ChunkingConfig>,
+    image_index: Option<ImageIndex>,
+    /// One row per pubkey holding its embedded kind-0 profile
+    profile_store: Option<Box<dyn VectorStore>>,
+    media_describer: Option<MediaDescriber>,
+    summarizer: Option<ContentSummarizer>,
+    /// Boosts authors the searcher follows when a request names a viewer
+    follow_graph: Option<FollowGraph>,
+    /// Skips or marks events nearly duplicating a recent one by the same
+    /// author
+    duplicate_detector: Option<DuplicateDetector>,
+    /// Events removed by deletion requests, dropped whenever rows are
+    /// written
+    tombstones: Tombstones,
+}
+
+/// CLIP embeddings of event images. They live in their own table because
+/// they come from a different model, with a different size, than the text
+/// embeddings; each row is stored under its event's id like a chunk.
+struct ImageIndex {
+    embedder: ImageEmbedder,
+    store: Box<dyn VectorStore>,
 }
 
 impl EmbeddingSearchService {
@@ -16,39 +78,299 @@ impl EmbeddingSearchService {
         embedding_service: EmbeddingService,
         db_path: &str,
         table_name: &str,
+        search_config: SearchConfig,
     ) -> Result<Self> {
-        let lancedb_store = LanceDBStore::new(db_path, table_name).await?;
+        Self::with_storage_options(
+            embedding_service,
+            db_path,
+            table_name,
+            search_config,
+            &HashMap::new(),
+        )
+        .await
+    }
 
-        Ok(Self {
+    /// Like `new`, for stores on object storage (`s3://`, `gs://`) that
+    /// need credentials or endpoint options.
+    pub async fn with_storage_options(
+        embedding_service: EmbeddingService,
+        db_path: &str,
+        table_name: &str,
+        search_config: SearchConfig,
+        storage_options: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let store = LanceDBStore::with_storage_options(
+            db_path,
+            table_name,
+            embedding_service.dimensions(),
+            embedding_service.model_id(),
+            storage_options,
+        )
+        .await?;
+        Ok(Self::with_store(
             embedding_service,
-            lancedb_store,
-        })
+            Box::new(store),
+            search_config,
+        ))
     }
 
-    pub async fn embed_and_store_event(&self, event: &NostrEvent) -> Result<()> {
-        let embedding = self
-            .embedding_service
-            .generate_embedding(&event.content)
-            .await?;
+    /// Searches and stores `embedding_service`'s vectors in an already
+    /// opened `store`, whichever backend it is.
+    pub fn with_store(
+        embedding_service: EmbeddingService,
+        mut store: Box<dyn VectorStore>,
+        search_config: SearchConfig,
+    ) -> Self {
+        store.set_model_id(embedding_service.model_id());
+        let result_cache = ResultCache::new(
+            search_config.cache_size,
+            Duration::from_secs(search_config.cache_ttl_secs),
+        );
 
-        let embedded_event = NostrEventWithEmbedding::new(
-            event.id.clone(),
-            event.pubkey.clone(),
-            event.created_at,
-            event.kind,
-            event.tags.clone(),
-            embedding,
+        Self {
+            embedding_service,
+            store,
+            search_config,
+            query_expander: None,
+            result_cache,
+            content_max_bytes: None,
+            chunking: None,
+            image_index: None,
+            profile_store: None,
+            media_describer: None,
+            summarizer: None,
+            follow_graph: None,
+            duplicate_detector: None,
+            tombstones: Tombstones::new(TOMBSTONE_CAPACITY),
+        }
+    }
+
+    /// Builds the service with every optional feature enabled in `config`,
+    /// as used by the server and the command-line tools.
+    pub async fn from_config(config: &Config) -> Result<Self> {
+        println!(
+            "Using {:?} embedding model {} (id {})",
+            config.embedding.provider, config.embedding.model, config.embedding.model_id
         );
+        let embedding_service = EmbeddingService::new(&config.embedding)?;
+
+        let dimensions = embedding_service.dimensions();
+        let mut service = Self::with_store(
+            embedding_service,
+            vector_store::open(
+                config,
+                &config.table_name,
+                dimensions,
+                &config.embedding.model_id,
+            )
+            .await?,
+            config.search.clone(),
+        )
+        .with_index_config(config.index.clone());
+
+        if let Some(query_expansion) = config.query_expansion.clone() {
+            println!(
+                "Query expansion enabled with model {}",
+                query_expansion.model
+            );
+            service = service.with_query_expander(QueryExpander::new(query_expansion));
+        }
+
+        if let Some(summarization) = config.summarization.clone() {
+            println!(
+                "Summarizing content over {} tokens with model {}",
+                config.embedding.max_tokens, summarization.model
+            );
+            service = service.with_summarizer(ContentSummarizer::new(summarization));
+        }
+
+        if config.store_content {
+            service = service.with_content_storage(config.content_max_bytes);
+        }
+
+        if let Some(media_descriptions) = &config.media_descriptions {
+            service = service
+                .with_media_describer(MediaDescriber::new(media_descriptions.api_key.clone()));
+        }
+
+        if config.chunking.max_chars > 0 {
+            service = service.with_chunking(config.chunking.clone());
+        }
+
+        if let Some(image_config) = &config.image_embeddings {
+            println!("Image embeddings enabled with model {}", image_config.model);
+            let embedder = ImageEmbedder::new(image_config, config.embedding.cache_dir.as_deref())?;
+            let image_store = vector_store::open(
+                config,
+                &image_config.table_name,
+                embedder.dimensions(),
+                embedder.model_id(),
+            )
+            .await?;
+            service = service.with_image_embeddings(embedder, image_store);
+        }
+
+        if let Some(profile_config) = &config.profiles {
+            println!(
+                "Profile index enabled in table {}",
+                profile_config.table_name
+            );
+            let profile_store = vector_store::open(
+                config,
+                &profile_config.table_name,
+                dimensions,
+                &config.embedding.model_id,
+            )
+            .await?;
+            service = service.with_profile_index(profile_store);
+        }
+
+        if let Some(follow_graph) = config.follow_graph.clone() {
+            println!(
+                "Follow-graph ranking enabled with {} relays",
+                follow_graph.relays.len()
+            );
+            service = service.with_follow_graph(FollowGraph::new(follow_graph));
+        }
+
+        if let Some(dedup) = config.dedup.clone() {
+            println!(
+                "Near-duplicate suppression enabled: {:?} above {}",
+                dedup.action, dedup.threshold
+            );
+            service = service.with_duplicate_detector(DuplicateDetector::new(dedup));
+        }
+
+        Ok(service)
+    }
+
+    pub fn with_query_expander(mut self, query_expander: QueryExpander) -> Self {
+        self.query_expander = Some(query_expander);
+        self
+    }
+
+    pub fn with_index_config(mut self, index_config: IndexConfig) -> Self {
+        self.store.set_index_config(index_config);
+        self
+    }
+
+    pub fn with_content_storage(mut self, max_bytes: usize) -> Self {
+        self.content_max_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn with_chunking(mut self, chunking: ChunkingConfig) -> Self {
+        self.chunking = Some(chunking);
+        self
+    }
+
+    pub fn with_media_describer(mut self, media_describer: MediaDescriber) -> Self {
+        self.media_describer = Some(media_describer);
+        self
+    }
 
-        println!("{:?}", event);
-        match self.lancedb_store.insert_event(&embedded_event).await {
+    pub fn with_summarizer(mut self, summarizer: ContentSummarizer) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Enables personalized ranking of requests with a `viewer_pubkey`.
+    pub fn with_follow_graph(mut self, follow_graph: FollowGraph) -> Self {
+        self.follow_graph = Some(follow_graph);
+        self
+    }
+
+    pub fn with_duplicate_detector(mut self, duplicate_detector: DuplicateDetector) -> Self {
+        self.duplicate_detector = Some(duplicate_detector);
+        self
+    }
+
+    /// Enables `vector=image` searches. `store` must have been opened with
+    /// the embedder's dimensions.
+    pub fn with_image_embeddings(
+        mut self,
+        embedder: ImageEmbedder,
+        mut store: Box<dyn VectorStore>,
+    ) -> Self {
+        store.set_model_id(embedder.model_id());
+        self.image_index = Some(ImageIndex { embedder, store });
+        self
+    }
+
+    /// Enables profile indexing and `search_profiles`. `store` must have
+    /// been opened with the text embedding dimensions.
+    pub fn with_profile_index(mut self, mut store: Box<dyn VectorStore>) -> Self {
+        store.set_model_id(self.embedding_service.model_id());
+        self.profile_store = Some(store);
+        self
+    }
+
+    pub async fn embed_and_store_event(&self, event: &NostrEvent) -> Result<()> {
+        let rows = self.embed_event(event).await?;
+        self.store_rows(&rows).await
+    }
+
+    /// Embeds an event into the rows `store_rows` writes, without storing
+    /// them, so callers can batch inserts across events. Deletion requests
+    /// are applied instead and produce no rows.
+    pub async fn embed_event(&self, event: &NostrEvent) -> Result<Vec<NostrEventWithEmbedding>> {
+        if event.kind == nostr::DELETION_KIND {
+            self.apply_deletion(event).await?;
+            return Ok(Vec::new());
+        }
+        if self.is_deleted(event) {
+            return Ok(Vec::new());
+        }
+
+        let rows = self.embed_rows(event).await?;
+
+        self.index_images(std::slice::from_ref(event)).await;
+        self.index_profiles(std::slice::from_ref(event)).await;
+
+        Ok(self.suppress_duplicate(event, rows))
+    }
+
+    /// Embeds an event held back by the spam filter into rows for its
+    /// quarantine table. Unlike `embed_event` it leaves the image, profile
+    /// and duplicate indexes alone, so spam stays out of search and can't
+    /// mark later events as duplicates.
+    pub async fn embed_quarantined(
+        &self,
+        event: &NostrEvent,
+    ) -> Result<Vec<NostrEventWithEmbedding>> {
+        self.embed_rows(event).await
+    }
+
+    async fn embed_rows(&self, event: &NostrEvent) -> Result<Vec<NostrEventWithEmbedding>> {
+        let text = self.text_to_embed(event).await;
+        let chunks = self.chunk(&text);
+        let embeddings = match chunks.as_slice() {
+            [content] => vec![self.embedding_service.generate_embedding(content).await?],
+            chunks => self.embedding_service.generate_embeddings(chunks).await?,
+        };
+        let summary_embedding = self.embed_summary(event).await?;
+
+        Ok(self.chunk_rows(
+            event,
+            &text,
+            &chunks,
+            embeddings,
+            summary_embedding.as_ref(),
+        ))
+    }
+
+    /// Upserts embedded rows, treating rows that already exist as stored.
+    /// Rows of events deleted in the meantime are dropped.
+    pub async fn store_rows(&self, rows: &[NostrEventWithEmbedding]) -> Result<()> {
+        let rows = self.tombstones.retain_live(rows);
+        let rows = supersede(&self.store, &rows).await?;
+        match self.store.insert_events(&rows).await {
             Ok(()) => Ok(()),
             Err(e) => {
                 let error_msg = e.to_string().to_lowercase();
                 if error_msg.contains("duplicate") || error_msg.contains("already exists") {
                     eprintln!(
-                        "Warning: Event {} already exists in database, skipping insertion.",
-                        event.id
+                        "Warning: Some events may already exist in database, insertion partially completed."
                     );
                     Ok(())
                 } else {
@@ -58,44 +380,481 @@ impl EmbeddingSearchService {
         }
     }
 
+    /// Embeds events in provider batches and stores them. Events whose
+    /// content cannot be embedded are skipped.
     pub async fn embed_and_store_events(&self, events: &[NostrEvent]) -> Result<()> {
-        let mut embedded_events = Vec::new();
+        let (deletions, events): (Vec<NostrEvent>, Vec<NostrEvent>) = events
+            .iter()
+            .cloned()
+            .partition(|event| event.kind == nostr::DELETION_KIND);
+        for deletion in &deletions {
+            self.apply_deletion(deletion).await?;
+        }
+        let events: Vec<NostrEvent> = events
+            .into_iter()
+            .filter(|event| !self.is_deleted(event))
+            .collect();
+        let events = events.as_slice();
 
+        let mut texts_to_embed = Vec::with_capacity(events.len());
         for event in events {
-            if let Ok(embedding) = self
-                .embedding_service
-                .generate_embedding(&event.content)
-                .await
-            {
-                let embedded_event = NostrEventWithEmbedding::new(
-                    event.id.clone(),
-                    event.pubkey.clone(),
-                    event.created_at,
-                    event.kind,
-                    event.tags.clone(),
-                    embedding,
-                );
-                embedded_events.push(embedded_event);
-            }
+            texts_to_embed.push(self.text_to_embed(event).await);
+        }
+        let chunked: Vec<Vec<String>> =
+            texts_to_embed.iter().map(|text| self.chunk(text)).collect();
+        let texts: Vec<String> = chunked.iter().flatten().cloned().collect();
+        let mut content_embeddings = self.embed_batch(&texts).await.into_iter();
+
+        let summarized: Vec<(usize, String)> = events
+            .iter()
+            .enumerate()
+            .filter_map(|(index, event)| {
+                let summary = event.summary.as_deref()?.trim();
+                (!summary.is_empty()).then(|| (index, summary.to_string()))
+            })
+            .collect();
+        let summaries: Vec<String> = summarized
+            .iter()
+            .map(|(_, summary)| summary.clone())
+            .collect();
+        let mut summary_embeddings: HashMap<usize, Vec<f32>> = summarized
+            .iter()
+            .map(|(index, _)| *index)
+            .zip(self.embed_batch(&summaries).await)
+            .filter_map(|(index, embedding)| Some((index, embedding?)))
+            .collect();
+
+        let mut embedded_events = Vec::new();
+        for (index, (event, chunks)) in events.iter().zip(&chunked).enumerate() {
+            // Every chunk must be consumed, even when an earlier one failed
+            let embeddings: Vec<Option<Vec<f32>>> =
+                content_embeddings.by_ref().take(chunks.len()).collect();
+            let Some(embeddings) = embeddings.into_iter().collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+
+            let summary_embedding = summary_embeddings.remove(&index);
+            let rows = self.chunk_rows(
+                event,
+                &texts_to_embed[index],
+                chunks,
+                embeddings,
+                summary_embedding.as_ref(),
+            );
+            embedded_events.extend(self.suppress_duplicate(event, rows));
         }
 
-        if !embedded_events.is_empty() {
-            match self.lancedb_store.insert_events(&embedded_events).await {
-                Ok(()) => Ok(()),
+        self.index_images(events).await;
+        self.index_profiles(events).await;
+
+        self.store_rows(&embedded_events).await
+    }
+
+    /// Embeds `texts` in provider batches. When a batch request fails its
+    /// texts are retried one by one so a single bad input doesn't drop the
+    /// whole batch; texts that still fail map to `None`.
+    async fn embed_batch(&self, texts: &[String]) -> Vec<Option<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+
+        for chunk in texts.chunks(self.embedding_service.batch_size()) {
+            match self.embedding_service.generate_embeddings(chunk).await {
+                Ok(chunk_embeddings) => embeddings.extend(chunk_embeddings.into_iter().map(Some)),
                 Err(e) => {
-                    let error_msg = e.to_string().to_lowercase();
-                    if error_msg.contains("duplicate") || error_msg.contains("already exists") {
-                        eprintln!(
-                            "Warning: Some events may already exist in database, insertion partially completed."
-                        );
-                        Ok(())
-                    } else {
-                        Err(e)
+                    eprintln!(
+                        "Warning: Batch embedding failed, embedding {} texts individually: {}",
+                        chunk.len(),
+                        e
+                    );
+                    for text in chunk {
+                        embeddings.push(self.embedding_service.generate_embedding(text).await.ok());
                     }
                 }
             }
+        }
+
+        embeddings
+    }
+
+    /// Downloads and embeds the `imeta` images of `events` when image
+    /// embeddings are enabled. Failures are logged and don't affect the
+    /// text embeddings of the event.
+    async fn index_images(&self, events: &[NostrEvent]) {
+        let Some(image_index) = &self.image_index else {
+            return;
+        };
+
+        let mut rows = Vec::new();
+        for event in events {
+            for (index, url) in extract_imeta_image_urls(event).into_iter().enumerate() {
+                match image_index.embedder.embed_image_url(&url).await {
+                    Ok(embedding) => rows.push(
+                        NostrEventWithEmbedding::new(
+                            event.id.clone(),
+                            event.pubkey.clone(),
+                            event.created_at,
+                            event.kind,
+                            event.tags.clone(),
+                            embedding,
+                        )
+                        .with_chunk(index)
+                        .with_content(url)
+                        .with_address(event.address()),
+                    ),
+                    Err(e) => eprintln!(
+                        "Warning: Failed to embed image {} of event {}: {}",
+                        url, event.id, e
+                    ),
+                }
+            }
+        }
+
+        let rows = self.tombstones.retain_live(&rows);
+        if rows.is_empty() {
+            return;
+        }
+        let stored = match supersede(&image_index.store, &rows).await {
+            Ok(rows) => image_index.store.insert_events(&rows).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = stored {
+            eprintln!("Warning: Failed to store image embeddings: {}", e);
+        }
+    }
+
+    /// Removes the events a NIP-09 deletion request refers to, from the
+    /// text and image tables, when they were published by the requester.
+    /// Returns the number of rows removed.
+    pub async fn apply_deletion(&self, deletion: &NostrEvent) -> Result<usize> {
+        let targets = deletion.deletion_targets();
+        if targets.is_empty() {
+            return Ok(0);
+        }
+        // Remembered first, so copies stored after the deletion are dropped
+        self.tombstones.record(&deletion.pubkey, &targets);
+
+        let mut removed = self
+            .store
+            .delete_events_by_author(&targets, &deletion.pubkey)
+            .await?;
+        if let Some(image_index) = &self.image_index {
+            removed += image_index
+                .store
+                .delete_events_by_author(&targets, &deletion.pubkey)
+                .await?;
+        }
+
+        if removed > 0 {
+            self.result_cache.clear();
+        }
+        println!(
+            "Deletion {} removed {} rows for {} events",
+            deletion.id,
+            removed,
+            targets.len()
+        );
+        Ok(removed)
+    }
+
+    /// Whether a deletion request by its author has already removed `event`.
+    pub fn is_deleted(&self, event: &NostrEvent) -> bool {
+        self.tombstones.is_deleted(&event.pubkey, &event.id)
+    }
+
+    /// Embeds the name and `about` text of kind-0 events into the profile
+    /// table, keyed by pubkey so the newest profile replaces older ones.
+    /// Failures are logged and don't affect the event itself.
+    async fn index_profiles(&self, events: &[NostrEvent]) {
+        let Some(profile_store) = &self.profile_store else {
+            return;
+        };
+
+        let profiles: Vec<(&NostrEvent, String)> = events
+            .iter()
+            .filter(|event| event.kind == 0)
+            .filter_map(|event| {
+                let metadata = nostr::profile_metadata(&event.content)?;
+                Some((event, nostr::profile_text(&metadata)?))
+            })
+            .collect();
+        if profiles.is_empty() {
+            return;
+        }
+
+        let texts: Vec<String> = profiles.iter().map(|(_, text)| text.clone()).collect();
+        let rows: Vec<NostrEventWithEmbedding> = profiles
+            .iter()
+            .zip(self.embed_batch(&texts).await)
+            .filter_map(|((event, _), embedding)| {
+                Some(
+                    NostrEventWithEmbedding::new(
+                        event.pubkey.clone(),
+                        event.pubkey.clone(),
+                        event.created_at,
+                        event.kind,
+                        event.tags.clone(),
+                        embedding?,
+                    )
+                    .with_content(event.content.clone())
+                    .with_address(event.address()),
+                )
+            })
+            .collect();
+
+        if rows.is_empty() {
+            return;
+        }
+        // An older profile arriving late must not overwrite a newer one
+        let stored = match supersede(profile_store, &rows).await {
+            Ok(rows) => profile_store.insert_events(&rows).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = stored {
+            eprintln!("Warning: Failed to store profile embeddings: {}", e);
+        }
+    }
+
+    /// Ranks indexed profiles by similarity of their name and `about` text
+    /// to `query`.
+    pub async fn search_profiles(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<ProfileMatch>> {
+        let profile_store = self.profile_store.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Profile search is disabled; set PROFILE_INDEX=true to enable it")
+        })?;
+
+        let limit = limit
+            .unwrap_or(self.search_config.default_limit)
+            .min(self.search_config.max_results);
+        let query_embedding = self.embedding_service.generate_embedding(query).await?;
+        let hits = profile_store
+            .search_similar_with_embeddings(
+                &query_embedding,
+                limit,
+                &SearchFilters::default(),
+                VectorColumn::Content,
+            )
+            .await?;
+
+        Ok(hits
+            .into_iter()
+            .map(|hit| {
+                let metadata = hit
+                    .content
+                    .as_deref()
+                    .and_then(nostr::profile_metadata)
+                    .unwrap_or_default();
+                ProfileMatch {
+                    pubkey: hit.pubkey,
+                    score: distance_to_relevance(hit.distance),
+                    name: metadata.name,
+                    display_name: metadata.display_name,
+                    about: metadata.about,
+                    nip05: metadata.nip05,
+                    picture: metadata.picture,
+                }
+            })
+            .collect())
+    }
+
+    /// Embeds `text` as a content-space query, e.g. for saved searches.
+    pub async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        self.embedding_service.generate_embedding(text).await
+    }
+
+    /// Embeds a query or exclude text in the space being searched.
+    async fn embed_query(&self, text: &str, vector_space: VectorSpace) -> Result<Vec<f32>> {
+        match vector_space {
+            VectorSpace::Image => self.image_index()?.embedder.embed_text(text).await,
+            _ => self.embedding_service.generate_embedding(text).await,
+        }
+    }
+
+    fn follow_graph(&self) -> Result<&FollowGraph> {
+        self.follow_graph.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Personalized ranking is not enabled; set FOLLOW_GRAPH=true")
+        })
+    }
+
+    fn image_index(&self) -> Result<&ImageIndex> {
+        self.image_index.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Image search is not enabled; set IMAGE_EMBEDDING_MODEL")
+        })
+    }
+
+    /// The text embedded and stored for an event. Without chunking, text
+    /// over the model's token limit is summarized when a summarizer is
+    /// configured; anything still too long is truncated by the embedding
+    /// service.
+    async fn text_to_embed(&self, event: &NostrEvent) -> String {
+        let text = self.source_text(event).await;
+
+        if self.chunking.is_none()
+            && let Some(summarizer) = &self.summarizer
+            && !self.embedding_service.fits_context(&text)
+        {
+            match summarizer.summarize(&text).await {
+                Ok(summary) => return summary,
+                Err(e) => eprintln!(
+                    "Warning: Failed to summarize event {}, truncating instead: {}",
+                    event.id, e
+                ),
+            }
+        }
+
+        text
+    }
+
+    /// The event content, or for content that is only a media link the
+    /// media's description: the event's scribe summary if it has one,
+    /// otherwise one generated by the media describer.
+    async fn source_text(&self, event: &NostrEvent) -> String {
+        let Some(url) = media_only_url(&event.content) else {
+            return event.content.clone();
+        };
+
+        if let Some(summary) = event.summary.as_deref().map(str::trim)
+            && !summary.is_empty()
+        {
+            return summary.to_string();
+        }
+
+        if let Some(media_describer) = &self.media_describer {
+            match media_describer.describe(&url).await {
+                Ok(description) if !description.trim().is_empty() => return description,
+                Ok(_) => {}
+                Err(e) => eprintln!(
+                    "Warning: Failed to describe media {} of event {}: {}",
+                    url, event.id, e
+                ),
+            }
+        }
+
+        event.content.clone()
+    }
+
+    /// Splits content into chunks when chunking is enabled and the content
+    /// is long enough; otherwise returns the content as the only chunk.
+    fn chunk(&self, content: &str) -> Vec<String> {
+        match &self.chunking {
+            Some(chunking) => {
+                chunking::chunk_text(content, chunking.max_chars, chunking.overlap_chars)
+            }
+            None => vec![content.to_string()],
+        }
+    }
+
+    /// Builds the rows stored for an event from the embeddings of its
+    /// chunks: one row per chunk with the per-chunk strategy, otherwise a
+    /// single row holding the pooled embedding.
+    fn chunk_rows(
+        &self,
+        event: &NostrEvent,
+        text: &str,
+        chunks: &[String],
+        mut embeddings: Vec<Vec<f32>>,
+        summary_embedding: Option<&Vec<f32>>,
+    ) -> Vec<NostrEventWithEmbedding> {
+        let per_chunk = embeddings.len() > 1
+            && self
+                .chunking
+                .as_ref()
+                .is_some_and(|chunking| chunking.strategy == ChunkStrategy::PerChunk);
+
+        if per_chunk {
+            chunks
+                .iter()
+                .zip(embeddings)
+                .enumerate()
+                .map(|(index, (chunk, embedding))| {
+                    self.event_row(event, embedding, summary_embedding, chunk)
+                        .with_chunk(index)
+                })
+                .collect()
         } else {
-            Ok(())
+            let embedding = if embeddings.len() == 1 {
+                embeddings.remove(0)
+            } else {
+                self.embedding_service.pool(&embeddings)
+            };
+            vec![self.event_row(event, embedding, summary_embedding, text)]
+        }
+    }
+
+    /// Drops or marks the rows of an event that nearly duplicates a recent
+    /// one by the same author. Replaceable and addressable events are
+    /// exempt, since their new versions are meant to replace old ones.
+    fn suppress_duplicate(
+        &self,
+        event: &NostrEvent,
+        mut rows: Vec<NostrEventWithEmbedding>,
+    ) -> Vec<NostrEventWithEmbedding> {
+        let Some(detector) = &self.duplicate_detector else {
+            return rows;
+        };
+        let Some(first) = rows.first() else {
+            return rows;
+        };
+        if first.address.is_some() {
+            return rows;
+        }
+        let Some(original) = detector.check(
+            &event.pubkey,
+            &event.id,
+            event.created_at,
+            &first.content_embedding,
+        ) else {
+            return rows;
+        };
+
+        match detector.config().action {
+            DuplicateAction::Skip => {
+                println!(
+                    "Skipping event {}: near-duplicate of {}",
+                    event.id, original
+                );
+                Vec::new()
+            }
+            DuplicateAction::Mark => {
+                for row in &mut rows {
+                    row.duplicate_of = Some(original.clone());
+                }
+                rows
+            }
+        }
+    }
+
+    fn event_row(
+        &self,
+        event: &NostrEvent,
+        embedding: Vec<f32>,
+        summary_embedding: Option<&Vec<f32>>,
+        content: &str,
+    ) -> NostrEventWithEmbedding {
+        let mut row = NostrEventWithEmbedding::new(
+            event.id.clone(),
+            event.pubkey.clone(),
+            event.created_at,
+            event.kind,
+            event.tags.clone(),
+            embedding,
+        );
+        if let Some(summary_embedding) = summary_embedding {
+            row = row.with_summary_embedding(summary_embedding.clone());
+        }
+        if let Some(max_bytes) = self.content_max_bytes {
+            row = row.with_content(truncate_to_bytes(content, max_bytes));
+        }
+        row.with_address(event.address())
+    }
+
+    async fn embed_summary(&self, event: &NostrEvent) -> Result<Option<Vec<f32>>> {
+        match event.summary.as_deref().map(str::trim) {
+            Some(summary) if !summary.is_empty() => Ok(Some(
+                self.embedding_service.generate_embedding(summary).await?,
+            )),
+            _ => Ok(None),
         }
     }
 
@@ -103,27 +862,204 @@ impl EmbeddingSearchService {
         &self,
         request: &EventSearchRequest,
     ) -> Result<EventSearchResponse> {
+        request.validate()?;
+
+        let cache_key = request.cache_key();
+        if let Some(cached) = self.result_cache.get(&cache_key) {
+            return Ok(cached);
+        }
+
+        let response = self.search_uncached(request).await?;
+        // Degraded answers would outlive the outage in the cache
+        if !response.degraded {
+            self.result_cache.insert(cache_key, response.clone());
+        }
+
+        Ok(response)
+    }
+
+    async fn search_uncached(&self, request: &EventSearchRequest) -> Result<EventSearchResponse> {
         let query = request.get_search_query().unwrap_or("");
-        let limit = request.limit.unwrap_or(50);
+        let limit = request
+            .limit
+            .unwrap_or(self.search_config.default_limit)
+            .min(self.search_config.max_results);
+        let min_score = request.min_score.unwrap_or(self.search_config.min_score);
 
-        let query_embedding = self.embedding_service.generate_embedding(query).await?;
+        let expanded_query = if request.expand_query.unwrap_or(false) {
+            self.expand_query(query).await
+        } else {
+            None
+        };
+        let query = expanded_query.as_deref().unwrap_or(query);
 
-        let author = request.author.as_deref();
-        let kind = request
-            .event_kinds
-            .as_ref()
-            .and_then(|kinds| kinds.first())
-            .map(|&k| k as i32);
+        let vector_space = request.vector.unwrap_or_default();
 
-        match self
-            .lancedb_store
-            .search_similar_with_filters(&query_embedding, limit, author, kind, None, None)
-            .await
+        let filters = SearchFilters {
+            authors: request.resolved_authors()?,
+            kind: request
+                .event_kinds
+                .as_ref()
+                .and_then(|kinds| kinds.first())
+                .map(|&k| k as i32),
+            min_created_at: request.since,
+            max_created_at: request.until,
+            tags: request.tags.clone().unwrap_or_default(),
+            geohash: request.geohash.clone(),
+            exclude_duplicates: self.duplicate_detector.is_some()
+                && !request.include_duplicates.unwrap_or(false),
+            version: request.version,
+            model_id: Some(self.embedding_service.model_id().to_string()),
+        };
+
+        // Image queries fail here when image search isn't enabled, which
+        // keyword matches wouldn't paper over
+        let query_embedding = match self.embed_query(query, vector_space).await {
+            Ok(query_embedding) => query_embedding,
+            Err(e) if vector_space != VectorSpace::Image => {
+                return self.keyword_fallback(query, limit, &filters, e).await;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let exclude_embedding = match request.exclude.as_deref().map(str::trim) {
+            Some(exclude) if !exclude.is_empty() => {
+                Some(self.embed_query(exclude, vector_space).await?)
+            }
+            _ => None,
+        };
+        let exclude_threshold = request
+            .exclude_threshold
+            .unwrap_or(DEFAULT_EXCLUDE_THRESHOLD);
+
+        let diversity = request.diversity.unwrap_or(0.0);
+
+        let ranking_mode = request.ranking.unwrap_or_default();
+
+        let search_mode = request.mode.unwrap_or_default();
+
+        // Fetched before searching so a disabled follow graph fails fast.
+        // Relay failures only cost the personalization.
+        let follow_network = match request.viewer_pubkey.as_deref() {
+            Some(viewer) => {
+                let follow_graph = self.follow_graph()?;
+                match follow_graph
+                    .network(&nostr::normalize_pubkey(viewer)?)
+                    .await
+                {
+                    Ok(network) if !network.is_empty() => Some((follow_graph, network)),
+                    Ok(_) => None,
+                    Err(e) => {
+                        eprintln!("Warning: Failed to fetch follow graph: {}", e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let fetch_limit = if exclude_embedding.is_some()
+            || diversity > 0.0
+            || ranking_mode != RankingMode::Relevance
+            || follow_network.is_some()
         {
-            Ok(event_ids) => Ok(EventSearchResponse {
-                total_found: event_ids.len(),
-                event_ids,
-            }),
+            limit.saturating_mul(OVERFETCH_FACTOR)
+        } else {
+            limit
+        };
+
+        let vector_hits = self
+            .search_vector_space(&query_embedding, fetch_limit, &filters, vector_space)
+            .await;
+        let (hits, keyword_matches) = match (search_mode, vector_hits) {
+            // Keyword hits carry text embeddings, which can't be scored
+            // against an image-space query
+            (SearchMode::Hybrid, Ok(vector_hits)) if vector_space != VectorSpace::Image => {
+                let (hits, keyword_matches) = self
+                    .fuse_with_full_text(query, vector_hits, fetch_limit, &filters)
+                    .await;
+                (Ok(hits), keyword_matches)
+            }
+            (_, hits) => (hits, HashSet::new()),
+        };
+
+        match hits {
+            Ok(hits) => {
+                let mut candidates: Vec<(SearchHit, f32)> = hits
+                    .into_iter()
+                    .map(|hit| {
+                        let score = score_hit(&hit, &query_embedding, vector_space);
+                        (hit, score)
+                    })
+                    .filter(|(hit, score)| {
+                        meets_min_score(hit, *score, min_score, &keyword_matches)
+                    })
+                    .filter(|(hit, _)| match &exclude_embedding {
+                        Some(exclude) => score_hit(hit, exclude, vector_space) <= exclude_threshold,
+                        None => true,
+                    })
+                    .collect();
+
+                if vector_space == VectorSpace::Fusion && search_mode != SearchMode::Hybrid {
+                    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+                }
+
+                if ranking_mode == RankingMode::Recency {
+                    let half_life_secs = request
+                        .half_life_hours
+                        .unwrap_or(self.search_config.recency_half_life_hours)
+                        * 3600.0;
+                    let now = unix_now();
+                    for (hit, score) in candidates.iter_mut() {
+                        *score = ranking::blend_recency(
+                            *score,
+                            hit.created_at,
+                            now,
+                            half_life_secs,
+                            self.search_config.recency_weight,
+                        );
+                    }
+                    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+                }
+
+                if let Some((follow_graph, network)) = &follow_network {
+                    for (hit, score) in candidates.iter_mut() {
+                        *score = network.boost(*score, &hit.pubkey, follow_graph.config());
+                    }
+                    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+                }
+
+                let selected: Vec<(SearchHit, f32)> = if diversity > 0.0 {
+                    let embeddings: Vec<&[f32]> = candidates
+                        .iter()
+                        .map(|(hit, _)| hit_vector(hit, vector_space))
+                        .collect();
+                    ranking::mmr_select(&query_embedding, &embeddings, limit, 1.0 - diversity)
+                        .into_iter()
+                        .map(|index| candidates[index].clone())
+                        .collect()
+                } else {
+                    candidates.into_iter().take(limit).collect()
+                };
+
+                let snippets: HashMap<String, String> = selected
+                    .iter()
+                    .filter_map(|(hit, _)| Some((hit.id.clone(), hit.content.clone()?)))
+                    .collect();
+                let scores: HashMap<String, f32> = selected
+                    .iter()
+                    .map(|(hit, score)| (hit.id.clone(), *score))
+                    .collect();
+                let event_ids: Vec<String> = selected.into_iter().map(|(hit, _)| hit.id).collect();
+
+                Ok(EventSearchResponse {
+                    total_found: event_ids.len(),
+                    event_ids,
+                    snippets,
+                    scores,
+                    degraded: false,
+                })
+            }
             Err(e) => {
                 let error_msg = e.to_string().to_lowercase();
                 if error_msg.contains("table") && error_msg.contains("not found") {
@@ -131,12 +1067,18 @@ impl EmbeddingSearchService {
                     Ok(EventSearchResponse {
                         total_found: 0,
                         event_ids: vec![],
+                        snippets: HashMap::new(),
+                        scores: HashMap::new(),
+                        degraded: false,
                     })
                 } else if error_msg.contains("no data") || error_msg.contains("empty") {
                     eprintln!("Warning: No data available for search, returning empty results.");
                     Ok(EventSearchResponse {
                         total_found: 0,
                         event_ids: vec![],
+                        snippets: HashMap::new(),
+                        scores: HashMap::new(),
+                        degraded: false,
                     })
                 } else {
                     Err(e)
@@ -145,58 +1087,618 @@ impl EmbeddingSearchService {
         }
     }
 
-    pub async fn create_index(&self) -> Result<()> {
-        match self.lancedb_store.create_index().await {
-            Ok(()) => Ok(()),
-            Err(e) => {
-                let error_msg = e.to_string().to_lowercase();
-                if error_msg.contains("not enough rows to train") || error_msg.contains("kmeans") {
+    /// Runs `request` and groups its results into at most `clusters`
+    /// topics by their stored vectors.
+    pub async fn cluster_search(
+        &self,
+        request: &EventSearchRequest,
+        clusters: usize,
+    ) -> Result<Vec<TopicCluster>> {
+        let response = self.semantic_search(request).await?;
+
+        let mut rows_by_event: HashMap<String, Vec<SearchHit>> = HashMap::new();
+        for hit in self.store.get_events(&response.event_ids).await? {
+            rows_by_event.entry(hit.id.clone()).or_default().push(hit);
+        }
+
+        let results: Vec<ClusterInput> = response
+            .event_ids
+            .iter()
+            .filter_map(|id| {
+                let rows = rows_by_event.get(id)?;
+                let embeddings: Vec<&[f32]> =
+                    rows.iter().map(|row| row.embedding.as_slice()).collect();
+                Some(ClusterInput {
+                    id: id.clone(),
+                    embedding: ranking::mean_vector(&embeddings)?,
+                    content: response
+                        .snippets
+                        .get(id)
+                        .cloned()
+                        .or_else(|| rows.iter().find_map(|row| row.content.clone())),
+                })
+            })
+            .collect();
+
+        Ok(clustering::cluster_results(&results, clusters))
+    }
+
+    /// "More like this" for several seeds at once. Seed events are looked
+    /// up in the store (unknown ids are skipped), seed texts are embedded,
+    /// and the vectors are either averaged into one query or searched
+    /// separately and fused. The seed events themselves are not returned.
+    pub async fn similar_events(
+        &self,
+        request: &SimilarEventsRequest,
+    ) -> Result<EventSearchResponse> {
+        let limit = request
+            .limit
+            .unwrap_or(self.search_config.default_limit)
+            .min(self.search_config.max_results);
+
+        let mut seeds: Vec<Vec<f32>> = Vec::new();
+        if !request.event_ids.is_empty() {
+            let mut rows_by_event: HashMap<String, Vec<Vec<f32>>> = HashMap::new();
+            for hit in self.store.get_events(&request.event_ids).await? {
+                rows_by_event.entry(hit.id).or_default().push(hit.embedding);
+            }
+            for event_id in &request.event_ids {
+                // Chunked events are represented by the mean of their chunks
+                let Some(rows) = rows_by_event.get(event_id) else {
                     eprintln!(
-                        "Warning: Not enough rows to create index. Need at least 256 rows for index creation."
+                        "Warning: Seed event {} is not indexed, ignoring it",
+                        event_id
                     );
-                    Ok(())
-                } else if error_msg.contains("index already exists")
-                    || error_msg.contains("already indexed")
-                {
-                    eprintln!("Warning: Index already exists for this table.");
-                    Ok(())
+                    continue;
+                };
+                let rows: Vec<&[f32]> = rows.iter().map(Vec::as_slice).collect();
+                seeds.extend(ranking::mean_vector(&rows));
+            }
+        }
+        for text in request.texts.iter().map(|text| text.trim()) {
+            if !text.is_empty() {
+                seeds.push(self.embed_query(text, VectorSpace::Content).await?);
+            }
+        }
+
+        let filters = SearchFilters {
+            model_id: Some(self.embedding_service.model_id().to_string()),
+            ..Default::default()
+        };
+        // Seeds are likely among their own nearest neighbors
+        let fetch_limit = limit + request.event_ids.len();
+
+        let hits = match request.fusion.unwrap_or_default() {
+            SeedFusion::Average => {
+                let seeds: Vec<&[f32]> = seeds.iter().map(Vec::as_slice).collect();
+                match ranking::mean_vector(&seeds) {
+                    Some(query) => {
+                        self.search_vector_space(
+                            &query,
+                            fetch_limit,
+                            &filters,
+                            VectorSpace::Content,
+                        )
+                        .await?
+                    }
+                    None => Vec::new(),
+                }
+            }
+            SeedFusion::Rrf => {
+                let mut rankings: Vec<Vec<String>> = Vec::with_capacity(seeds.len());
+                let mut hits_by_id: HashMap<String, SearchHit> = HashMap::new();
+                for seed in &seeds {
+                    let hits = self
+                        .search_vector_space(seed, fetch_limit, &filters, VectorSpace::Content)
+                        .await?;
+                    rankings.push(hits.iter().map(|hit| hit.id.clone()).collect());
+                    for hit in hits {
+                        hits_by_id.entry(hit.id.clone()).or_insert(hit);
+                    }
+                }
+                ranking::reciprocal_rank_fusion(&rankings, RRF_K)
+                    .into_iter()
+                    .filter_map(|(id, _)| hits_by_id.remove(&id))
+                    .collect()
+            }
+        };
+
+        let selected: Vec<SearchHit> = hits
+            .into_iter()
+            .filter(|hit| !request.event_ids.contains(&hit.id))
+            .take(limit)
+            .collect();
+        let snippets: HashMap<String, String> = selected
+            .iter()
+            .filter_map(|hit| Some((hit.id.clone(), hit.content.clone()?)))
+            .collect();
+        let event_ids: Vec<String> = selected.into_iter().map(|hit| hit.id).collect();
+
+        Ok(EventSearchResponse {
+            total_found: event_ids.len(),
+            event_ids,
+            snippets,
+            scores: HashMap::new(),
+            degraded: false,
+        })
+    }
+
+    /// Answers a search whose query couldn't be embedded with full-text
+    /// matches on stored content, flagged as degraded. Fails with the
+    /// embedding error when the query is empty or the full-text index is
+    /// unavailable, e.g. because content storage is disabled.
+    async fn keyword_fallback(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+        embedding_error: anyhow::Error,
+    ) -> Result<EventSearchResponse> {
+        if query.trim().is_empty() {
+            return Err(embedding_error);
+        }
+
+        let hits = match self.store.search_full_text(query, limit, filters).await {
+            Ok(hits) => dedupe_hits(hits),
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "{}; keyword search is unavailable too: {}",
+                    embedding_error,
+                    e
+                ));
+            }
+        };
+        eprintln!(
+            "Warning: Query embedding failed, answering with keyword matches only: {}",
+            embedding_error
+        );
+
+        let snippets: HashMap<String, String> = hits
+            .iter()
+            .filter_map(|hit| Some((hit.id.clone(), hit.content.clone()?)))
+            .collect();
+        let event_ids: Vec<String> = hits.into_iter().take(limit).map(|hit| hit.id).collect();
+
+        Ok(EventSearchResponse {
+            total_found: event_ids.len(),
+            event_ids,
+            snippets,
+            scores: HashMap::new(),
+            degraded: true,
+        })
+    }
+
+    /// Runs the vector search in the requested space. Fusion searches both
+    /// columns and merges the hits, keeping one entry per event; chunk hits
+    /// of the same event are merged the same way.
+    async fn search_vector_space(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filters: &SearchFilters,
+        vector_space: VectorSpace,
+    ) -> Result<Vec<SearchHit>> {
+        let (store, columns): (&dyn VectorStore, &[VectorColumn]) = match vector_space {
+            VectorSpace::Content => (&*self.store, &[VectorColumn::Content]),
+            VectorSpace::Summary => (&*self.store, &[VectorColumn::Summary]),
+            VectorSpace::Fusion => (
+                &*self.store,
+                &[VectorColumn::Content, VectorColumn::Summary],
+            ),
+            VectorSpace::Image => (&*self.image_index()?.store, &[VectorColumn::Content]),
+        };
+        // The image store checks queries against its own model instead
+        let image_filters;
+        let filters = if vector_space == VectorSpace::Image {
+            image_filters = SearchFilters {
+                model_id: None,
+                ..filters.clone()
+            };
+            &image_filters
+        } else {
+            filters
+        };
+
+        let mut hits: Vec<SearchHit> = Vec::new();
+        for &column in columns {
+            hits.extend(
+                store
+                    .search_similar_with_embeddings(query_embedding, limit, filters, column)
+                    .await?,
+            );
+        }
+
+        Ok(dedupe_hits(hits))
+    }
+
+    /// Runs a full-text search for `query` and merges it with the vector
+    /// hits by reciprocal rank fusion, also returning the ids the full-text
+    /// search found. Falls back to the vector hits when the full-text index
+    /// is unavailable.
+    async fn fuse_with_full_text(
+        &self,
+        query: &str,
+        vector_hits: Vec<SearchHit>,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> (Vec<SearchHit>, HashSet<String>) {
+        if query.trim().is_empty() {
+            return (vector_hits, HashSet::new());
+        }
+
+        let text_hits = match self.store.search_full_text(query, limit, filters).await {
+            Ok(text_hits) => text_hits,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Full-text search failed, using semantic results only: {}",
+                    e
+                );
+                return (vector_hits, HashSet::new());
+            }
+        };
+
+        let text_hits = dedupe_hits(text_hits);
+        let keyword_matches: HashSet<String> = text_hits.iter().map(|hit| hit.id.clone()).collect();
+
+        let rankings = [
+            vector_hits.iter().map(|hit| hit.id.clone()).collect(),
+            text_hits.iter().map(|hit| hit.id.clone()).collect(),
+        ];
+        let mut hits_by_id: HashMap<String, SearchHit> = vector_hits
+            .into_iter()
+            .chain(text_hits)
+            .map(|hit| (hit.id.clone(), hit))
+            .collect();
+
+        let hits = ranking::reciprocal_rank_fusion(&rankings, RRF_K)
+            .into_iter()
+            .filter_map(|(id, _)| hits_by_id.remove(&id))
+            .collect();
+        (hits, keyword_matches)
+    }
+
+    /// Expands the query with the configured chat model, falling back to the
+    /// original query when expansion is unavailable or fails.
+    async fn expand_query(&self, query: &str) -> Option<String> {
+        let Some(query_expander) = &self.query_expander else {
+            eprintln!("Warning: expand_query requested but no expansion model is configured.");
+            return None;
+        };
+
+        if query.trim().is_empty() {
+            return None;
+        }
+
+        match query_expander.expand(query).await {
+            Ok(expanded) => {
+                println!("Expanded query '{}' to '{}'", query, expanded);
+                Some(expanded)
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: Query expansion failed, using original query: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Removes duplicate rows for the same event from the store.
+    pub async fn dedupe(&self) -> Result<usize> {
+        let removed = self.store.dedupe().await?;
+        if removed > 0 {
+            self.result_cache.clear();
+        }
+        Ok(removed)
+    }
+
+    /// Compacts the table, prunes old versions and re-optimizes indexes.
+    pub async fn optimize(&self, prune_older_than: chrono::Duration) -> Result<()> {
+        self.store.optimize(prune_older_than).await
+    }
+
+    pub async fn list_versions(&self) -> Result<Vec<TableVersion>> {
+        self.store.list_versions().await
+    }
+
+    /// Restores the table to `version`; cached results from the newer data
+    /// are dropped.
+    pub async fn rollback(&self, version: u64) -> Result<()> {
+        self.store.rollback(version).await?;
+        self.result_cache.clear();
+        Ok(())
+    }
+
+    /// Applies the retention policy once and returns how many events were
+    /// deleted.
+    pub async fn prune(&self, retention: &RetentionConfig) -> Result<usize> {
+        let mut deleted = 0;
+
+        if let Some(max_age_days) = retention.max_age_days {
+            let cutoff = unix_now() - (max_age_days * 86_400) as i64;
+            deleted += self.store.delete_older_than(cutoff).await?;
+        }
+
+        if let Some(max_per_author) = retention.max_per_author {
+            deleted += self.store.prune_per_author(max_per_author).await?;
+        }
+
+        if deleted > 0 {
+            self.result_cache.clear();
+        }
+
+        Ok(deleted)
+    }
+
+    /// Registry id of the model used for new events and queries.
+    pub fn model_id(&self) -> &str {
+        self.embedding_service.model_id()
+    }
+
+    /// Size of the vectors the embedding model produces, which side tables
+    /// holding its embeddings must be opened with.
+    pub fn dimensions(&self) -> usize {
+        self.embedding_service.dimensions()
+    }
+
+    /// Number of stored events embedded with `model_id`.
+    pub async fn count_events_for_model(&self, model_id: &str) -> Result<usize> {
+        self.store.count_events_for_model(model_id).await
+    }
+
+    /// Verifies the embedding provider can embed text.
+    pub async fn check_embedding_provider(&self) -> Result<()> {
+        self.embedding_service
+            .generate_embedding("health check")
+            .await
+            .map(|_| ())
+    }
+
+    /// Time left before embedding resumes after the provider's circuit
+    /// breaker opened, or `None` while the provider is usable.
+    pub fn embedding_paused_for(&self) -> Option<std::time::Duration> {
+        self.embedding_service.paused_for()
+    }
+
+    /// Stored rows of `event_ids` (tags, and content when stored).
+    pub async fn stored_events(&self, event_ids: &[String]) -> Result<Vec<ExportedRow>> {
+        self.store.get_rows(event_ids).await
+    }
+
+    /// The hits of `response` with only the attributes in `fields`. Stored
+    /// events are only looked up when `event` is selected; results no
+    /// longer in the store are returned without it.
+    pub async fn project_hits(
+        &self,
+        response: &EventSearchResponse,
+        fields: &[ResultField],
+    ) -> Result<Vec<ResultHit>> {
+        let mut events: HashMap<String, StoredEvent> = HashMap::new();
+        if fields.contains(&ResultField::Event) {
+            let mut rows_by_event: HashMap<String, Vec<ExportedRow>> = HashMap::new();
+            for row in self.stored_events(&response.event_ids).await? {
+                rows_by_event
+                    .entry(row.parent_id.clone())
+                    .or_default()
+                    .push(row);
+            }
+            for (event_id, mut rows) in rows_by_event {
+                let chunked = rows.len() > 1;
+                let row = rows.swap_remove(0);
+                events.insert(
+                    event_id.clone(),
+                    StoredEvent {
+                        id: event_id,
+                        pubkey: row.pubkey,
+                        created_at: row.created_at,
+                        kind: row.kind,
+                        tags: row.tags,
+                        content: row.content.filter(|_| !chunked),
+                    },
+                );
+            }
+        }
+
+        Ok(response
+            .event_ids
+            .iter()
+            .map(|event_id| ResultHit {
+                id: fields.contains(&ResultField::Id).then(|| event_id.clone()),
+                score: if fields.contains(&ResultField::Score) {
+                    response.scores.get(event_id).copied()
                 } else {
-                    Err(e)
+                    None
+                },
+                event: events.remove(event_id),
+            })
+            .collect())
+    }
+
+    /// How many indexed events use each hashtag.
+    pub async fn hashtag_counts(&self) -> Result<HashMap<String, u64>> {
+        self.store.count_tag_values("t").await
+    }
+
+    /// Verifies the vector store table can be opened and read.
+    pub async fn check_store(&self) -> Result<()> {
+        self.store.count_events().await.map(|_| ())
+    }
+
+    pub async fn create_index(&self) -> Result<()> {
+        if self.content_max_bytes.is_some()
+            && let Err(e) = self.store.create_fts_index().await
+        {
+            eprintln!(
+                "Warning: Failed to create full-text index, hybrid search will use semantic results only: {}",
+                e
+            );
+        }
+
+        for column in [VectorColumn::Content, VectorColumn::Summary] {
+            match self.store.create_index(column).await {
+                Ok(()) => {}
+                Err(e) => {
+                    let error_msg = e.to_string().to_lowercase();
+                    if error_msg.contains("not enough rows to train")
+                        || error_msg.contains("kmeans")
+                    {
+                        eprintln!(
+                            "Warning: Not enough rows to create index. Need at least 256 rows for index creation."
+                        );
+                    } else if error_msg.contains("index already exists")
+                        || error_msg.contains("already indexed")
+                    {
+                        eprintln!("Warning: Index already exists for this table.");
+                    } else {
+                        return Err(e);
+                    }
                 }
             }
         }
+
+        Ok(())
+    }
+}
+
+/// Keeps the first hit for each event, dropping later hits from other
+/// vector columns or chunks of the same event.
+fn dedupe_hits(hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    let mut seen = HashSet::new();
+    hits.into_iter()
+        .filter(|hit| seen.insert(hit.id.clone()))
+        .collect()
+}
+
+/// Keeps only the newest version of each replaceable or addressable event
+/// among `rows` and in `store`: rows older than what is stored are dropped,
+/// and stored versions they replace are deleted.
+async fn supersede(
+    store: &dyn VectorStore,
+    rows: &[NostrEventWithEmbedding],
+) -> Result<Vec<NostrEventWithEmbedding>> {
+    let mut newest: HashMap<&str, (&str, i64)> = HashMap::new();
+    for row in rows {
+        let Some(address) = row.address.as_deref() else {
+            continue;
+        };
+        let candidate = (row.parent_id.as_str(), row.created_at);
+        match newest.get(address) {
+            Some(&(id, created_at))
+                if created_at > candidate.1 || (created_at == candidate.1 && id <= candidate.0) => {
+            }
+            _ => {
+                newest.insert(address, candidate);
+            }
+        }
+    }
+    if newest.is_empty() {
+        return Ok(rows.to_vec());
+    }
+
+    let mut current = HashMap::new();
+    for (address, (parent_id, created_at)) in newest {
+        if store
+            .replace_address(address, parent_id, created_at)
+            .await?
+        {
+            current.insert(address, (parent_id, created_at));
+        }
+    }
+
+    Ok(rows
+        .iter()
+        .filter(|row| match row.address.as_deref() {
+            Some(address) => {
+                current.get(address) == Some(&(row.parent_id.as_str(), row.created_at))
+            }
+            None => true,
+        })
+        .cloned()
+        .collect())
+}
+
+/// Whether a hit scoring `score` is relevant enough to return. Full-text
+/// matches are kept whatever their similarity: exact terms can be far from
+/// the query in embedding space, and finding those is what hybrid search
+/// is for.
+fn meets_min_score(
+    hit: &SearchHit,
+    score: f32,
+    min_score: f32,
+    keyword_matches: &HashSet<String>,
+) -> bool {
+    score >= min_score || keyword_matches.contains(&hit.id)
+}
+
+/// Similarity of a hit to `embedding` in the given vector space.
+fn score_hit(hit: &SearchHit, embedding: &[f32], vector_space: VectorSpace) -> f32 {
+    match vector_space {
+        VectorSpace::Content | VectorSpace::Image => cosine_similarity(&hit.embedding, embedding),
+        VectorSpace::Summary => cosine_similarity(&hit.summary_embedding, embedding),
+        VectorSpace::Fusion => {
+            (cosine_similarity(&hit.embedding, embedding)
+                + cosine_similarity(&hit.summary_embedding, embedding))
+                / 2.0
+        }
     }
 }
 
+/// The vector used to compare hits with each other for diversification.
+fn hit_vector(hit: &SearchHit, vector_space: VectorSpace) -> &[f32] {
+    match vector_space {
+        VectorSpace::Summary => &hit.summary_embedding,
+        VectorSpace::Content | VectorSpace::Fusion | VectorSpace::Image => &hit.embedding,
+    }
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::EmbeddingConfig;
 
     #[tokio::test]
     async fn test_embedding_search_service_creation() {
-        let embedding_service_result = EmbeddingService::new();
+        let embedding_service_result = EmbeddingService::new(&EmbeddingConfig::default());
         if embedding_service_result.is_err() {
             return;
         }
 
         let embedding_service = embedding_service_result.unwrap();
 
-        let service_result =
-            EmbeddingSearchService::new(embedding_service, "test_db", "events").await;
+        let service_result = EmbeddingSearchService::new(
+            embedding_service,
+            "test_db",
+            "events",
+            SearchConfig::default(),
+        )
+        .await;
 
         assert!(service_result.is_ok() || service_result.is_err());
     }
 
     #[tokio::test]
     async fn test_embed_event() {
-        let embedding_service_result = EmbeddingService::new();
+        let embedding_service_result = EmbeddingService::new(&EmbeddingConfig::default());
         if embedding_service_result.is_err() {
             return;
         }
 
         let embedding_service = embedding_service_result.unwrap();
-        let service_result =
-            EmbeddingSearchService::new(embedding_service, "test_db_2", "events").await;
+        let service_result = EmbeddingSearchService::new(
+            embedding_service,
+            "test_db_2",
+            "events",
+            SearchConfig::default(),
+        )
+        .await;
 
         if service_result.is_err() {
             return;
@@ -211,9 +1713,103 @@ mod tests {
             tags: vec![],
             content: "test content".to_string(),
             sig: "test_sig".to_string(),
+            summary: None,
         };
 
         let result = service.embed_and_store_event(&event).await;
         assert!(result.is_ok() || result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_target_stored_after_its_deletion_is_dropped() {
+        let Ok(embedding_service) = EmbeddingService::new(&EmbeddingConfig::default()) else {
+            return;
+        };
+        let dimensions = embedding_service.dimensions();
+        let db_path = std::env::temp_dir().join("seekstr_test_deleted_target");
+        let _ = std::fs::remove_dir_all(&db_path);
+        let Ok(service) = EmbeddingSearchService::new(
+            embedding_service,
+            db_path.to_str().unwrap(),
+            "events",
+            SearchConfig::default(),
+        )
+        .await
+        else {
+            return;
+        };
+
+        let deletion = NostrEvent {
+            id: "deletion".to_string(),
+            pubkey: "alice".to_string(),
+            created_at: 1234567890,
+            kind: nostr::DELETION_KIND,
+            tags: vec![vec!["e".to_string(), "a".to_string()]],
+            content: String::new(),
+            sig: String::new(),
+            summary: None,
+        };
+        service.apply_deletion(&deletion).await.unwrap();
+
+        // The target finished embedding after the deletion ran
+        let row = |id: &str| {
+            NostrEventWithEmbedding::new(
+                id.to_string(),
+                "alice".to_string(),
+                1234567800,
+                1,
+                vec![],
+                vec![0.1; dimensions],
+            )
+        };
+        service.store_rows(&[row("a"), row("b")]).await.unwrap();
+
+        let stored: Vec<String> = service
+            .store
+            .get_events(&["a".to_string(), "b".to_string()])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.id)
+            .collect();
+        assert_eq!(stored, vec!["b".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&db_path);
+    }
+
+    #[test]
+    fn test_keyword_only_hit_survives_default_min_score() {
+        let hit = |id: &str| SearchHit {
+            id: id.to_string(),
+            distance: 0.0,
+            pubkey: "pubkey".to_string(),
+            created_at: 0,
+            embedding: vec![-1.0, 0.0],
+            summary_embedding: vec![-1.0, 0.0],
+            content: Some("lightning".to_string()),
+        };
+        let query_embedding = [1.0, 0.0];
+        let min_score = SearchConfig::default().min_score;
+        let keyword_matches: HashSet<String> = ["keyword".to_string()].into();
+
+        // Both hits point away from the query in embedding space
+        let keyword_hit = hit("keyword");
+        let score = score_hit(&keyword_hit, &query_embedding, VectorSpace::Content);
+        assert!(score < min_score);
+        assert!(meets_min_score(
+            &keyword_hit,
+            score,
+            min_score,
+            &keyword_matches
+        ));
+
+        let vector_hit = hit("vector");
+        let score = score_hit(&vector_hit, &query_embedding, VectorSpace::Content);
+        assert!(!meets_min_score(
+            &vector_hit,
+            score,
+            min_score,
+            &keyword_matches
+        ));
+    }
 }
@@ -1,25 +1,357 @@
+use crate::config::{EmbeddingConfig, EmbeddingProvider, RetryConfig};
+use crate::retry::{self, CircuitBreaker};
+use crate::tokens::TokenCounter;
 use anyhow::Result;
 use rig::client::EmbeddingsClient;
 use rig::embeddings::EmbeddingModel;
 use rig::providers::openai;
 
 pub struct EmbeddingService {
-    model: openai::embedding::EmbeddingModel,
+    backend: EmbeddingBackend,
+    dimensions: usize,
+    batch_size: usize,
+    retry: RetryConfig,
+    breaker: CircuitBreaker,
+    model_id: String,
+    normalize: bool,
+    token_counter: Option<TokenCounter>,
+}
+
+enum EmbeddingBackend {
+    OpenAi(openai::embedding::EmbeddingModel),
+    /// fastembed models embed through `&mut self`
+    #[cfg(feature = "local-embeddings")]
+    Local(std::sync::Arc<std::sync::Mutex<fastembed::TextEmbedding>>),
 }
 
 impl EmbeddingService {
-    pub fn new() -> Result<Self> {
-        let openai_client = openai::ClientBuilder::new("otrta_BiT6hytS2bEoJuP6H4p9X9IHAnwm35Su")
-            .base_url("https://ecash.server.otrta.me")
+    pub fn new(config: &EmbeddingConfig) -> Result<Self> {
+        match config.provider {
+            EmbeddingProvider::OpenAi => Self::new_openai(config),
+            EmbeddingProvider::Local => Self::new_local(config),
+        }
+    }
+
+    /// Connects to an OpenAI-compatible embeddings endpoint. The key may be
+    /// omitted for local servers such as Ollama.
+    fn new_openai(config: &EmbeddingConfig) -> Result<Self> {
+        let openai_client = openai::ClientBuilder::new(config.api_key.as_deref().unwrap_or(""))
+            .base_url(&config.api_url)
             .build()?;
 
-        let model = openai_client.embedding_model("nomic-embed-text:latest");
+        let model = openai_client.embedding_model_with_ndims(&config.model, config.dimensions);
+
+        Ok(Self {
+            backend: EmbeddingBackend::OpenAi(model),
+            dimensions: config.dimensions,
+            batch_size: config.batch_size.max(1),
+            retry: config.retry.clone(),
+            breaker: breaker(&config.retry),
+            model_id: config.model_id.clone(),
+            normalize: config.normalize,
+            token_counter: token_counter(config)?,
+        })
+    }
+
+    /// Loads an ONNX model with fastembed, downloading it on first use.
+    /// Fails when the configured dimensions differ from the model's output
+    /// size, since stores are opened with the configured value.
+    #[cfg(feature = "local-embeddings")]
+    fn new_local(config: &EmbeddingConfig) -> Result<Self> {
+        use fastembed::{TextEmbedding, TextInitOptions};
+
+        let model_info = TextEmbedding::list_supported_models()
+            .into_iter()
+            .find(|info| info.model_code.eq_ignore_ascii_case(&config.model))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported local embedding model '{}', expected a fastembed model code such as Xenova/all-MiniLM-L6-v2 or nomic-ai/nomic-embed-text-v1.5",
+                    config.model
+                )
+            })?;
+
+        if model_info.dim != config.dimensions {
+            anyhow::bail!(
+                "{} produces {} dimensions but {} are configured, set EMBEDDING_DIMENSIONS={}",
+                model_info.model_code,
+                model_info.dim,
+                config.dimensions,
+                model_info.dim
+            );
+        }
+
+        let mut options =
+            TextInitOptions::new(model_info.model.clone()).with_show_download_progress(true);
+        if let Some(cache_dir) = &config.cache_dir {
+            options = options.with_cache_dir(cache_dir.into());
+        }
+        let model = TextEmbedding::try_new(options)?;
+
+        Ok(Self {
+            backend: EmbeddingBackend::Local(std::sync::Arc::new(std::sync::Mutex::new(model))),
+            dimensions: model_info.dim,
+            batch_size: config.batch_size.max(1),
+            retry: config.retry.clone(),
+            breaker: breaker(&config.retry),
+            model_id: config.model_id.clone(),
+            normalize: config.normalize,
+            token_counter: token_counter(config)?,
+        })
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    fn new_local(_config: &EmbeddingConfig) -> Result<Self> {
+        anyhow::bail!(
+            "Local embeddings require building lancedb-search with the `local-embeddings` feature"
+        )
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Whether `text` fits the model's context without truncation.
+    pub fn fits_context(&self, text: &str) -> bool {
+        self.token_counter
+            .as_ref()
+            .is_none_or(|counter| counter.fits(text))
+    }
+
+    /// Cuts `text` to the configured token limit so the provider never sees
+    /// input longer than the model's context.
+    fn fit_to_context<'a>(&self, text: &'a str) -> &'a str {
+        let Some(counter) = &self.token_counter else {
+            return text;
+        };
+
+        let truncated = counter.truncate(text);
+        if truncated.len() < text.len() {
+            eprintln!(
+                "Warning: Truncated text of {} tokens to {} tokens before embedding",
+                counter.count(text),
+                counter.max_tokens()
+            );
+        }
+        truncated
+    }
+
+    /// Registry id of the model, stored alongside every vector.
+    pub fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    /// Maximum number of texts sent to the provider in one request.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Time left before the provider is called again after repeated
+    /// failures, or `None` while it is considered healthy.
+    pub fn paused_for(&self) -> Option<std::time::Duration> {
+        self.breaker.open_for()
+    }
+
+    /// Runs a provider call, retrying transient failures with backoff and
+    /// failing fast while the circuit breaker is open.
+    async fn with_retry<T, F, Fut>(&self, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        if let Some(remaining) = self.breaker.open_for() {
+            anyhow::bail!(
+                "Embedding provider unavailable, retrying in {}s",
+                remaining.as_secs().max(1)
+            );
+        }
+
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Ok(value) => {
+                    self.breaker.record_success();
+                    return Ok(value);
+                }
+                Err(e) if attempt < self.retry.max_retries && retry::is_transient(&e) => {
+                    let delay = retry::backoff(&self.retry, attempt);
+                    eprintln!(
+                        "Embedding request failed ({}), retrying in {}ms",
+                        e,
+                        delay.as_millis()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    if retry::is_transient(&e) {
+                        self.breaker.record_failure();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Embeds several texts with one provider request per `batch_size`
+    /// texts. Embeddings are returned in input order.
+    pub async fn generate_embeddings(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+
+        for chunk in texts.chunks(self.batch_size) {
+            let chunk_embeddings = self.with_retry(|| self.embed_chunk(chunk)).await?;
 
-        Ok(Self { model })
+            if chunk_embeddings.len() != chunk.len() {
+                anyhow::bail!(
+                    "Embedding provider returned {} embeddings for {} texts",
+                    chunk_embeddings.len(),
+                    chunk.len()
+                );
+            }
+            if let Some(embedding) = chunk_embeddings
+                .iter()
+                .find(|embedding| embedding.len() != self.dimensions)
+            {
+                anyhow::bail!(
+                    "Embedding model returned {} dimensions, expected {}",
+                    embedding.len(),
+                    self.dimensions
+                );
+            }
+
+            embeddings.extend(chunk_embeddings.into_iter().map(|e| self.finish(e)));
+        }
+
+        Ok(embeddings)
     }
 
     pub async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
-        let embedding = self.model.embed_text(text).await?;
-        Ok(embedding.vec.into_iter().map(|x| x as f32).collect())
+        let embedding = self.with_retry(|| self.embed_one(text)).await?;
+
+        if embedding.len() != self.dimensions {
+            anyhow::bail!(
+                "Embedding model returned {} dimensions, expected {}",
+                embedding.len(),
+                self.dimensions
+            );
+        }
+        Ok(self.finish(embedding))
+    }
+
+    /// Mean-pools chunk embeddings into one vector for the whole text.
+    pub fn pool(&self, embeddings: &[Vec<f32>]) -> Vec<f32> {
+        self.finish(crate::chunking::mean_pool(embeddings))
+    }
+
+    fn finish(&self, mut embedding: Vec<f32>) -> Vec<f32> {
+        if self.normalize {
+            l2_normalize(&mut embedding);
+        }
+        embedding
+    }
+
+    fn fitted(&self, texts: &[String]) -> Vec<String> {
+        texts
+            .iter()
+            .map(|text| self.fit_to_context(text).to_string())
+            .collect()
+    }
+
+    async fn embed_chunk(&self, chunk: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(match &self.backend {
+            EmbeddingBackend::OpenAi(model) => model
+                .embed_texts(self.fitted(chunk))
+                .await?
+                .into_iter()
+                .map(|embedding| embedding.vec.into_iter().map(|x| x as f32).collect())
+                .collect(),
+            #[cfg(feature = "local-embeddings")]
+            EmbeddingBackend::Local(model) => {
+                let model = model.clone();
+                let chunk = self.fitted(chunk);
+                let batch_size = self.batch_size;
+                tokio::task::spawn_blocking(move || {
+                    model.lock().unwrap().embed(chunk, Some(batch_size))
+                })
+                .await??
+            }
+        })
+    }
+
+    async fn embed_one(&self, text: &str) -> Result<Vec<f32>> {
+        let text = self.fit_to_context(text);
+        Ok(match &self.backend {
+            EmbeddingBackend::OpenAi(model) => {
+                let embedding = model.embed_text(text).await?;
+                embedding.vec.into_iter().map(|x| x as f32).collect()
+            }
+            #[cfg(feature = "local-embeddings")]
+            EmbeddingBackend::Local(model) => {
+                let model = model.clone();
+                let text = text.to_string();
+                tokio::task::spawn_blocking(move || model.lock().unwrap().embed(vec![text], None))
+                    .await??
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Local model returned no embedding"))?
+            }
+        })
+    }
+}
+
+fn token_counter(config: &EmbeddingConfig) -> Result<Option<TokenCounter>> {
+    if config.max_tokens == 0 {
+        return Ok(None);
+    }
+    Ok(Some(TokenCounter::new(config.max_tokens)?))
+}
+
+fn breaker(config: &RetryConfig) -> CircuitBreaker {
+    CircuitBreaker::new(
+        config.breaker_threshold,
+        std::time::Duration::from_secs(config.breaker_cooldown_secs),
+    )
+}
+
+/// Scales `vector` to unit length in place; zero vectors are left as is.
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_l2_normalize() {
+        let mut vector = vec![3.0, 4.0];
+        l2_normalize(&mut vector);
+        assert!((vector[0] - 0.6).abs() < 1e-6);
+        assert!((vector[1] - 0.8).abs() < 1e-6);
+
+        let mut zero = vec![0.0, 0.0];
+        l2_normalize(&mut zero);
+        assert_eq!(zero, vec![0.0, 0.0]);
     }
 }
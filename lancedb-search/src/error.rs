@@ -0,0 +1,91 @@
+use crate::FieldError;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Machine-readable error category returned in every error body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The request could not be parsed at all
+    InvalidRequest,
+    /// The request parsed but one or more filters are invalid
+    InvalidFilters,
+    /// Missing or wrong admin token
+    Unauthorized,
+    /// The requested resource doesn't exist
+    NotFound,
+    /// The event queue is full; retry later
+    QueueFull,
+    /// The event queue is not accepting events
+    QueueUnavailable,
+    /// Embedding or vector store failure
+    BackendError,
+    /// The request didn't finish within its deadline
+    Timeout,
+    /// The server is a read-only replica
+    ReadOnly,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidRequest | ErrorCode::InvalidFilters => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::QueueFull => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::QueueUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::BackendError => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::ReadOnly => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+/// JSON error body returned by the HTTP API.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_errors: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            field_errors: Vec::new(),
+        }
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn invalid_filters(field_errors: Vec<FieldError>) -> Self {
+        Self {
+            code: ErrorCode::InvalidFilters,
+            message: "One or more search filters are invalid".to_string(),
+            field_errors,
+        }
+    }
+
+    pub fn backend(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::BackendError, message)
+    }
+
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Timeout, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.code.status(), Json(self)).into_response()
+    }
+}
@@ -0,0 +1,74 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use nostr_sdk::hashes::{Hash, sha256};
+
+/// Tags successful GET responses with an ETag derived from their body and
+/// answers `304 Not Modified` when the client already holds it, so
+/// repeated identical searches only cost the round trip. The tag is weak
+/// because the compression layer may re-encode the body.
+pub async fn etag(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK || response.headers().contains_key(header::ETAG) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Failed to read response body for ETag: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let tag = format!("W/\"{}\"", sha256::Hash::hash(&bytes));
+    parts.headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&tag).expect("hex digest is a valid header value"),
+    );
+
+    if if_none_match.is_some_and(|candidates| matches(&candidates, &tag)) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::CONTENT_TYPE);
+        return Response::from_parts(parts, Body::empty());
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Whether an `If-None-Match` header value names `tag`, compared weakly
+/// as RFC 9110 requires for this header.
+fn matches(if_none_match: &str, tag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let tag = opaque(tag);
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_compares_weakly() {
+        let tag = "W/\"abc\"";
+        assert!(matches("\"abc\"", tag));
+        assert!(matches("W/\"xyz\", W/\"abc\"", tag));
+        assert!(matches("*", tag));
+        assert!(!matches("\"xyz\"", tag));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::config::Config;
+use crate::embedding_service::EmbeddingSearchService;
+use crate::{EventSearchRequest, RankingMode, SearchMode};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Results scored per query when the evaluation file doesn't set `k`.
+const DEFAULT_K: usize = 10;
+
+/// An evaluation file: labeled queries and the two configurations to
+/// compare on them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationSet {
+    /// Results scored per query
+    #[serde(default)]
+    pub k: Option<usize>,
+    pub queries: Vec<LabeledQuery>,
+    /// Defaults to the server configuration as is
+    #[serde(default)]
+    pub baseline: Variant,
+    pub candidate: Variant,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledQuery {
+    pub query: String,
+    /// Ids of the events a good search returns
+    pub relevant: Vec<String>,
+    /// Graded relevance of some of `relevant` for NDCG; the others count
+    /// as grade 1
+    #[serde(default)]
+    pub grades: HashMap<String, u32>,
+}
+
+impl LabeledQuery {
+    fn grade(&self, event_id: &str) -> u32 {
+        match self.grades.get(event_id) {
+            Some(grade) => *grade,
+            None if self.relevant.iter().any(|id| id == event_id) => 1,
+            None => 0,
+        }
+    }
+}
+
+/// One side of the comparison: the server configuration with some
+/// settings overridden. Anything left unset keeps the configured value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Variant {
+    /// Shown in the report; defaults to `baseline` and `candidate`
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Registry id of the embedding model (see `EMBEDDING_MODELS_FILE`)
+    #[serde(default)]
+    pub model_id: Option<String>,
+    /// Table holding the model's embeddings; defaults to the migration
+    /// table for the migration's model and `LANCEDB_TABLE_NAME` otherwise
+    #[serde(default)]
+    pub table_name: Option<String>,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    #[serde(default)]
+    pub ranking: Option<RankingMode>,
+    #[serde(default)]
+    pub mode: Option<SearchMode>,
+    #[serde(default)]
+    pub diversity: Option<f32>,
+    #[serde(default)]
+    pub expand_query: Option<bool>,
+}
+
+impl Variant {
+    /// The server configuration this variant searches with.
+    pub fn config(&self, base: &Config) -> Result<Config> {
+        let mut config = base.clone();
+        if let Some(model_id) = &self.model_id {
+            config.embedding.select_model(model_id)?;
+            if let Some(migration) = &base.migration
+                && migration.model_id == *model_id
+            {
+                config.table_name = migration.table_name.clone();
+            }
+        }
+        if let Some(table_name) = &self.table_name {
+            config.table_name = table_name.clone();
+        }
+        config.migration = None;
+        Ok(config)
+    }
+
+    fn request(&self, query: &str, k: usize) -> EventSearchRequest {
+        EventSearchRequest {
+            search: Some(query.to_string()),
+            limit: Some(k),
+            min_score: self.min_score,
+            ranking: self.ranking,
+            mode: self.mode,
+            diversity: self.diversity,
+            expand_query: self.expand_query,
+            ..Default::default()
+        }
+    }
+}
+
+/// Retrieval quality of one ranking, or the mean over all queries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    /// Share of the top `k` results that are relevant
+    pub precision: f64,
+    /// Share of the relevant events found in the top `k`
+    pub recall: f64,
+    /// Normalized discounted cumulative gain at `k`
+    pub ndcg: f64,
+}
+
+impl Metrics {
+    fn diff(&self, baseline: &Metrics) -> Metrics {
+        Metrics {
+            precision: self.precision - baseline.precision,
+            recall: self.recall - baseline.recall,
+            ndcg: self.ndcg - baseline.ndcg,
+        }
+    }
+
+    fn mean(metrics: &[Metrics]) -> Metrics {
+        if metrics.is_empty() {
+            return Metrics::default();
+        }
+        let count = metrics.len() as f64;
+        Metrics {
+            precision: metrics.iter().map(|m| m.precision).sum::<f64>() / count,
+            recall: metrics.iter().map(|m| m.recall).sum::<f64>() / count,
+            ndcg: metrics.iter().map(|m| m.ndcg).sum::<f64>() / count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryComparison {
+    pub query: String,
+    pub baseline: Metrics,
+    pub candidate: Metrics,
+    pub baseline_ids: Vec<String>,
+    pub candidate_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub k: usize,
+    pub baseline_name: String,
+    pub candidate_name: String,
+    /// Means over all queries
+    pub baseline: Metrics,
+    pub candidate: Metrics,
+    /// `candidate - baseline`; positive is better
+    pub diff: Metrics,
+    pub queries: Vec<QueryComparison>,
+}
+
+impl EvaluationReport {
+    /// Queries whose NDCG dropped with the candidate, worst first.
+    pub fn regressions(&self) -> Vec<&QueryComparison> {
+        let mut regressions: Vec<&QueryComparison> = self
+            .queries
+            .iter()
+            .filter(|query| query.candidate.ndcg < query.baseline.ndcg)
+            .collect();
+        regressions.sort_by(|a, b| {
+            (a.candidate.ndcg - a.baseline.ndcg).total_cmp(&(b.candidate.ndcg - b.baseline.ndcg))
+        });
+        regressions
+    }
+}
+
+/// Runs every labeled query against both services and scores their
+/// rankings.
+pub async fn evaluate(
+    set: &EvaluationSet,
+    baseline: &EmbeddingSearchService,
+    candidate: &EmbeddingSearchService,
+) -> Result<EvaluationReport> {
+    let k = set.k.unwrap_or(DEFAULT_K).max(1);
+    let mut queries = Vec::with_capacity(set.queries.len());
+    for labeled in &set.queries {
+        let (baseline_results, candidate_results) = tokio::join!(
+            baseline.semantic_search(&set.baseline.request(&labeled.query, k)),
+            candidate.semantic_search(&set.candidate.request(&labeled.query, k)),
+        );
+        let baseline_ids = baseline_results?.event_ids;
+        let candidate_ids = candidate_results?.event_ids;
+        queries.push(QueryComparison {
+            query: labeled.query.clone(),
+            baseline: score(labeled, &baseline_ids, k),
+            candidate: score(labeled, &candidate_ids, k),
+            baseline_ids,
+            candidate_ids,
+        });
+    }
+
+    let baseline_mean = Metrics::mean(&queries.iter().map(|q| q.baseline).collect::<Vec<_>>());
+    let candidate_mean = Metrics::mean(&queries.iter().map(|q| q.candidate).collect::<Vec<_>>());
+    Ok(EvaluationReport {
+        k,
+        baseline_name: set
+            .baseline
+            .name
+            .clone()
+            .unwrap_or_else(|| "baseline".to_string()),
+        candidate_name: set
+            .candidate
+            .name
+            .clone()
+            .unwrap_or_else(|| "candidate".to_string()),
+        baseline: baseline_mean,
+        candidate: candidate_mean,
+        diff: candidate_mean.diff(&baseline_mean),
+        queries,
+    })
+}
+
+/// Scores the top `k` of `ranked` against the query's labels.
+fn score(labeled: &LabeledQuery, ranked: &[String], k: usize) -> Metrics {
+    let top = &ranked[..ranked.len().min(k)];
+    let found = top
+        .iter()
+        .filter(|id| labeled.grade(id.as_str()) > 0)
+        .count();
+
+    let gain = |grade: u32| 2f64.powi(grade as i32) - 1.0;
+    let discount = |rank: usize| (rank as f64 + 2.0).log2();
+    let dcg: f64 = top
+        .iter()
+        .enumerate()
+        .map(|(rank, id)| gain(labeled.grade(id)) / discount(rank))
+        .sum();
+    let mut ideal: Vec<u32> = labeled
+        .relevant
+        .iter()
+        .map(|id| labeled.grade(id))
+        .collect();
+    ideal.sort_unstable_by(|a, b| b.cmp(a));
+    let idcg: f64 = ideal
+        .iter()
+        .take(k)
+        .enumerate()
+        .map(|(rank, grade)| gain(*grade) / discount(rank))
+        .sum();
+
+    Metrics {
+        precision: found as f64 / k as f64,
+        recall: if labeled.relevant.is_empty() {
+            0.0
+        } else {
+            found as f64 / labeled.relevant.len() as f64
+        },
+        ndcg: if idcg > 0.0 { dcg / idcg } else { 0.0 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labeled(relevant: &[&str]) -> LabeledQuery {
+        LabeledQuery {
+            query: "nostr".to_string(),
+            relevant: relevant.iter().map(|id| id.to_string()).collect(),
+            grades: HashMap::new(),
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_score_perfect_ranking() {
+        let metrics = score(&labeled(&["a", "b"]), &ids(&["a", "b"]), 2);
+        assert_eq!(metrics.precision, 1.0);
+        assert_eq!(metrics.recall, 1.0);
+        assert!((metrics.ndcg - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_score_penalizes_low_ranks_and_misses() {
+        let query = labeled(&["a", "b"]);
+        let late = score(&query, &ids(&["x", "a", "y"]), 3);
+        assert!((late.precision - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(late.recall, 0.5);
+
+        let early = score(&query, &ids(&["a", "x", "y"]), 3);
+        assert!(early.ndcg > late.ndcg);
+        assert_eq!(score(&query, &ids(&["x"]), 3), Metrics::default());
+    }
+
+    #[test]
+    fn test_score_uses_grades() {
+        let mut query = labeled(&["a", "b"]);
+        query.grades.insert("b".to_string(), 3);
+
+        let best_first = score(&query, &ids(&["b", "a"]), 2);
+        let best_last = score(&query, &ids(&["a", "b"]), 2);
+        assert!((best_first.ndcg - 1.0).abs() < 1e-9);
+        assert!(best_last.ndcg < best_first.ndcg);
+    }
+}
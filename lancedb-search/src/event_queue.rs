@@ -1,59 +1,621 @@
-use crate::nostr::NostrEvent;
-use anyhow::Result;
+use crate::config::ProcessorConfig;
+use crate::dead_letter::{DeadLetter, DeadLetterStore};
+use crate::event_wal::EventWal;
+use crate::live::LiveFeed;
+use crate::metrics::{QueueMetrics, QueueSnapshot};
+use crate::nostr::{NostrEvent, NostrEventWithEmbedding};
+use crate::saved_search::SavedSearches;
+use crate::spam::SpamFilter;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, mpsc};
+
+/// Why an event could not be queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueError {
+    /// The queue is at capacity; the caller should retry later
+    Full,
+    /// The processor has stopped
+    Closed,
+    /// The event could not be written to the WAL
+    Wal,
+}
+
+impl std::fmt::Display for EnqueueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnqueueError::Full => write!(f, "Event queue is full, retry later"),
+            EnqueueError::Closed => write!(f, "Failed to enqueue event: channel closed"),
+            EnqueueError::Wal => write!(f, "Failed to persist event to the WAL"),
+        }
+    }
+}
+
+impl std::error::Error for EnqueueError {}
 
 #[derive(Debug, Clone)]
 pub struct EventQueue {
-    sender: mpsc::UnboundedSender<NostrEvent>,
+    sender: mpsc::Sender<NostrEvent>,
+    wal: Option<Arc<EventWal>>,
+    metrics: Arc<QueueMetrics>,
 }
 
 impl EventQueue {
-    pub fn new() -> (Self, mpsc::UnboundedReceiver<NostrEvent>) {
-        let (sender, receiver) = mpsc::unbounded_channel();
-        (Self { sender }, receiver)
+    /// Creates a queue holding at most `capacity` events waiting to be
+    /// embedded.
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<NostrEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let queue = Self {
+            sender,
+            wal: None,
+            metrics: Arc::new(QueueMetrics::new()),
+        };
+        (queue, receiver)
+    }
+
+    /// Records every accepted event in `wal` before queueing it.
+    pub fn with_wal(mut self, wal: Arc<EventWal>) -> Self {
+        self.wal = Some(wal);
+        self
+    }
+
+    /// Whether the processor is still receiving from the queue.
+    pub fn is_alive(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Number of events waiting to be processed.
+    pub fn pending(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
+    }
+
+    /// Counters shared with the `EventProcessor` reading this queue.
+    pub fn metrics(&self) -> Arc<QueueMetrics> {
+        self.metrics.clone()
     }
 
-    pub fn enqueue(&self, event: NostrEvent) -> Result<()> {
-        self.sender
-            .send(event)
-            .map_err(|_| anyhow::anyhow!("Failed to enqueue event: channel closed"))?;
+    pub fn snapshot(&self) -> QueueSnapshot {
+        self.metrics.snapshot(self.pending())
+    }
+
+    /// Queues an event without waiting, failing with `EnqueueError::Full`
+    /// when the queue is at capacity.
+    pub fn enqueue(&self, event: NostrEvent) -> Result<(), EnqueueError> {
+        let permit = self.sender.try_reserve().map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => {
+                self.metrics.record_rejected();
+                EnqueueError::Full
+            }
+            mpsc::error::TrySendError::Closed(_) => EnqueueError::Closed,
+        })?;
+        self.persist(&event)?;
+        self.metrics.record_enqueued(&event.id);
+        permit.send(event);
+        Ok(())
+    }
+
+    /// Queues an event, waiting for room when the queue is full.
+    pub async fn enqueue_wait(&self, event: NostrEvent) -> Result<(), EnqueueError> {
+        let permit = self
+            .sender
+            .reserve()
+            .await
+            .map_err(|_| EnqueueError::Closed)?;
+        self.persist(&event)?;
+        self.metrics.record_enqueued(&event.id);
+        permit.send(event);
+        Ok(())
+    }
+
+    fn persist(&self, event: &NostrEvent) -> Result<(), EnqueueError> {
+        if let Some(wal) = &self.wal {
+            wal.append(event).map_err(|e| {
+                eprintln!("Failed to write event {} to WAL: {}", event.id, e);
+                EnqueueError::Wal
+            })?;
+        }
         Ok(())
     }
 }
 
 pub struct EventProcessor {
+    receiver: mpsc::Receiver<NostrEvent>,
+    replay: Vec<NostrEvent>,
+    worker: Worker,
+}
+
+/// State shared by the concurrent tasks that process events.
+#[derive(Clone)]
+struct Worker {
     embedding_service: Arc<crate::embedding_service::EmbeddingSearchService>,
-    receiver: mpsc::UnboundedReceiver<NostrEvent>,
+    config: ProcessorConfig,
+    dead_letters: Arc<DeadLetterStore>,
+    wal: Option<Arc<EventWal>>,
+    metrics: Arc<QueueMetrics>,
+    saved_searches: Option<Arc<SavedSearches>>,
+    live_feed: Option<LiveFeed>,
+    /// Candidate model of a migration, written alongside the current one
+    migration_target: Option<Arc<crate::embedding_service::EmbeddingSearchService>>,
+    spam_filter: Option<Arc<SpamFilter>>,
 }
 
 impl EventProcessor {
     pub fn new(
         embedding_service: Arc<crate::embedding_service::EmbeddingSearchService>,
-        receiver: mpsc::UnboundedReceiver<NostrEvent>,
+        receiver: mpsc::Receiver<NostrEvent>,
+        config: ProcessorConfig,
     ) -> Self {
         Self {
-            embedding_service,
             receiver,
+            replay: Vec::new(),
+            worker: Worker {
+                embedding_service,
+                dead_letters: Arc::new(DeadLetterStore::new(config.dead_letter_capacity)),
+                config,
+                wal: None,
+                metrics: Arc::new(QueueMetrics::new()),
+                saved_searches: None,
+                live_feed: None,
+                migration_target: None,
+                spam_filter: None,
+            },
         }
     }
 
+    /// Events that failed every retry, shared with the admin API.
+    pub fn dead_letters(&self) -> Arc<DeadLetterStore> {
+        self.worker.dead_letters.clone()
+    }
+
+    /// Reports progress into the queue's counters.
+    pub fn with_metrics(mut self, metrics: Arc<QueueMetrics>) -> Self {
+        self.worker.metrics = metrics;
+        self
+    }
+
+    /// Matches every stored event against `saved_searches` and sends their
+    /// notifications.
+    pub fn with_saved_searches(mut self, saved_searches: Arc<SavedSearches>) -> Self {
+        self.worker.saved_searches = Some(saved_searches);
+        self
+    }
+
+    /// Publishes every stored event to live subscriptions.
+    pub fn with_live_feed(mut self, live_feed: LiveFeed) -> Self {
+        self.worker.live_feed = Some(live_feed);
+        self
+    }
+
+    /// Also embeds every event with a migration's candidate model and
+    /// stores it in the candidate's table. Failures there are logged and
+    /// never hold up or dead-letter the event.
+    pub fn with_migration_target(
+        mut self,
+        migration_target: Arc<crate::embedding_service::EmbeddingSearchService>,
+    ) -> Self {
+        self.worker.migration_target = Some(migration_target);
+        self
+    }
+
+    /// Scores every event with `spam_filter` before embedding and writes
+    /// likely spam to its quarantine table instead of the main index.
+    pub fn with_spam_filter(mut self, spam_filter: Arc<SpamFilter>) -> Self {
+        self.worker.spam_filter = Some(spam_filter);
+        self
+    }
+
+    /// Removes events from `wal` once processed and replays whatever it
+    /// still holds from a previous run before reading the queue. Call this
+    /// before the queue accepts events so none of them are replayed twice,
+    /// and before `dead_letters`, which are kept in `wal` from then on.
+    pub fn with_wal(mut self, wal: Arc<EventWal>) -> Self {
+        self.replay = wal.pending().unwrap_or_else(|e| {
+            eprintln!("Failed to read event WAL: {}", e);
+            Vec::new()
+        });
+        self.worker.dead_letters = Arc::new(DeadLetterStore::persisted(
+            self.worker.config.dead_letter_capacity,
+            wal.clone(),
+        ));
+        self.worker.wal = Some(wal);
+        self
+    }
+
+    /// Embeds events on up to `workers` concurrent tasks and stores them in
+    /// batches until every `EventQueue` handle has been dropped and the
+    /// remaining backlog has been stored.
     pub async fn start_processing(mut self) {
-        println!("Event processor started");
+        let workers = self.worker.config.workers.max(1);
+        println!("Event processor started with {} workers", workers);
+
+        let (batches, batch_receiver) = mpsc::channel(self.worker.config.batch_size.max(1) * 2);
+        let batcher = tokio::spawn(self.worker.clone().store_batches(batch_receiver));
+
+        let permits = Arc::new(Semaphore::new(workers));
+        let replay = std::mem::take(&mut self.replay);
+        if !replay.is_empty() {
+            println!("Replaying {} events from the WAL", replay.len());
+        }
+        for event in &replay {
+            self.worker.metrics.record_enqueued(&event.id);
+        }
 
+        for event in replay {
+            self.dispatch(event, &permits, &batches).await;
+        }
         while let Some(event) = self.receiver.recv().await {
-            println!("Processing event: {}", event.id);
+            self.dispatch(event, &permits, &batches).await;
+        }
+
+        // Wait for in-flight events, then let the batcher flush what is left
+        let _ = permits.acquire_many(workers as u32).await;
+        drop(batches);
+        if let Err(e) = batcher.await {
+            eprintln!("Event batcher failed: {}", e);
+        }
+        println!("Event processor stopped: queue drained");
+    }
 
-            match self.embedding_service.embed_and_store_event(&event).await {
-                Ok(()) => {
-                    println!("Successfully processed event: {}", event.id);
+    /// Embeds `event` on its own task once a worker slot is free.
+    async fn dispatch(
+        &self,
+        event: NostrEvent,
+        permits: &Arc<Semaphore>,
+        batches: &mpsc::Sender<EmbeddedEvent>,
+    ) {
+        let permit = permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("processor semaphore is never closed");
+        let worker = self.worker.clone();
+        let batches = batches.clone();
+        tokio::spawn(async move {
+            worker.process(event, batches).await;
+            drop(permit);
+        });
+    }
+}
+
+/// An event whose rows are ready to be written to the store.
+struct EmbeddedEvent {
+    event: NostrEvent,
+    rows: Vec<NostrEventWithEmbedding>,
+    /// Rows for the migration's candidate model, when one is configured
+    candidate_rows: Vec<NostrEventWithEmbedding>,
+}
+
+impl Worker {
+    async fn process(&self, event: NostrEvent, batches: mpsc::Sender<EmbeddedEvent>) {
+        println!("Processing event: {}", event.id);
+
+        if self.quarantine_spam(&event).await {
+            return;
+        }
+
+        let embedded = self
+            .with_retries(&event, || self.embedding_service.embed_event(&event))
+            .await;
+        match embedded {
+            Ok(rows) => {
+                let candidate_rows = self.embed_candidate(&event).await;
+                let embedded = EmbeddedEvent {
+                    event,
+                    rows,
+                    candidate_rows,
+                };
+                if batches.send(embedded).await.is_err() {
+                    eprintln!("Event batcher stopped, leaving event in the WAL");
                 }
-                Err(e) => {
-                    eprintln!("Failed to process event {}: {}", event.id, e);
+            }
+            Err((e, attempts)) => {
+                if self.dead_letter(&event, e, attempts) {
+                    self.forget(&event);
                 }
             }
         }
+    }
+
+    /// Collects embedded events until `batch_size` of them are waiting or
+    /// `batch_timeout_ms` has passed since the first, then stores them with
+    /// a single upsert.
+    async fn store_batches(self, mut receiver: mpsc::Receiver<EmbeddedEvent>) {
+        let batch_size = self.config.batch_size.max(1);
+        let timeout = Duration::from_millis(self.config.batch_timeout_ms);
+
+        while let Some(first) = receiver.recv().await {
+            let deadline = tokio::time::Instant::now() + timeout;
+            let mut batch = vec![first];
+            while batch.len() < batch_size {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(embedded)) => batch.push(embedded),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            self.store_batch(batch).await;
+        }
+    }
+
+    async fn store_batch(&self, batch: Vec<EmbeddedEvent>) {
+        // A deletion can run while its target waits here or on another
+        // worker; storing the target afterwards would bring it back
+        let (deleted, batch): (Vec<EmbeddedEvent>, Vec<EmbeddedEvent>) = batch
+            .into_iter()
+            .partition(|embedded| self.embedding_service.is_deleted(&embedded.event));
+        for embedded in &deleted {
+            println!("Skipping event {}: deleted while queued", embedded.event.id);
+            self.metrics.record_processed(&embedded.event.id);
+            self.forget(&embedded.event);
+        }
+        if batch.is_empty() {
+            return;
+        }
+
+        let rows: Vec<NostrEventWithEmbedding> = batch
+            .iter()
+            .flat_map(|embedded| embedded.rows.iter().cloned())
+            .collect();
+
+        match self.embedding_service.store_rows(&rows).await {
+            Ok(()) => {
+                println!("Stored batch of {} events", batch.len());
+                for embedded in &batch {
+                    self.metrics.record_processed(&embedded.event.id);
+                    self.forget(&embedded.event);
+                    self.on_stored(embedded);
+                }
+                self.store_candidate_rows(batch.iter()).await;
+            }
+            // Store the events one by one so a single bad row only
+            // dead-letters its own event
+            Err(e) => {
+                eprintln!(
+                    "Failed to store batch of {} events, storing individually: {}",
+                    batch.len(),
+                    e
+                );
+                let mut stored_events = Vec::with_capacity(batch.len());
+                for embedded in &batch {
+                    let stored = self
+                        .with_retries(&embedded.event, || {
+                            self.embedding_service.store_rows(&embedded.rows)
+                        })
+                        .await;
+                    match stored {
+                        Ok(()) => {
+                            println!("Successfully processed event: {}", embedded.event.id);
+                            self.metrics.record_processed(&embedded.event.id);
+                            self.on_stored(embedded);
+                            stored_events.push(embedded);
+                            self.forget(&embedded.event);
+                        }
+                        Err((e, attempts)) => {
+                            if self.dead_letter(&embedded.event, e, attempts) {
+                                self.forget(&embedded.event);
+                            }
+                        }
+                    }
+                }
+                self.store_candidate_rows(stored_events.into_iter()).await;
+            }
+        }
+    }
+
+    /// Runs `operation` with backoff between failed attempts, returning the
+    /// last error and the number of attempts once retries are exhausted.
+    async fn with_retries<T, F, Fut>(
+        &self,
+        event: &NostrEvent,
+        mut operation: F,
+    ) -> Result<T, (anyhow::Error, u32)>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let retry = self.config.retry();
+        let mut attempt = 0;
+        loop {
+            self.wait_for_embedding_provider().await;
+
+            match operation().await {
+                Ok(value) => return Ok(value),
+                // The provider is down; keep the event and retry it
+                // once the circuit breaker closes instead of dropping it
+                Err(e) if self.embedding_service.embedding_paused_for().is_some() => {
+                    eprintln!("Deferring event {}: {}", event.id, e);
+                }
+                Err(e) if attempt < retry.max_retries => {
+                    let delay = crate::retry::backoff(&retry, attempt);
+                    attempt += 1;
+                    self.metrics.record_retry();
+                    eprintln!(
+                        "Failed to process event {} (attempt {}), retrying in {}ms: {}",
+                        event.id,
+                        attempt,
+                        delay.as_millis(),
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err((e, attempt + 1)),
+            }
+        }
+    }
+
+    /// Stores `event` in the spam filter's quarantine table when it looks
+    /// like spam. Returns whether it was quarantined; deletions are never
+    /// checked.
+    async fn quarantine_spam(&self, event: &NostrEvent) -> bool {
+        let Some(spam_filter) = &self.spam_filter else {
+            return false;
+        };
+        if event.kind == crate::nostr::DELETION_KIND {
+            return false;
+        }
+        let Some(verdict) = spam_filter.check(event).await else {
+            return false;
+        };
+
+        println!(
+            "Quarantining event {} (spam score {:.2}: {})",
+            event.id,
+            verdict.score,
+            verdict.reasons.join(", ")
+        );
+        let stored = self
+            .with_retries(event, || async {
+                let rows = self.embedding_service.embed_quarantined(event).await?;
+                spam_filter.quarantine(&rows).await
+            })
+            .await;
+        let kept = match stored {
+            Ok(()) => {
+                self.metrics.record_quarantined(&event.id);
+                true
+            }
+            Err((e, attempts)) => self.dead_letter(event, e, attempts),
+        };
+        if kept {
+            self.forget(event);
+        }
+        true
+    }
+
+    /// Records an event that failed every retry. Returns whether it may be
+    /// dropped from the WAL: not when the dead letter couldn't be persisted,
+    /// so it is replayed on the next start instead of lost.
+    fn dead_letter(&self, event: &NostrEvent, error: anyhow::Error, attempts: u32) -> bool {
+        eprintln!(
+            "Failed to process event {} after {} attempts, dead-lettering: {}",
+            event.id, attempts, error
+        );
+        self.metrics.record_failed(&event.id);
+        let letter = DeadLetter {
+            event: event.clone(),
+            error: error.to_string(),
+            attempts,
+            failed_at: chrono::Utc::now().timestamp(),
+        };
+        match self.dead_letters.push(letter) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!(
+                    "Failed to persist dead letter {}, leaving it in the WAL: {}",
+                    event.id, e
+                );
+                false
+            }
+        }
+    }
+
+    /// Embeds `event` with the migration's candidate model; deletions are
+    /// applied to the candidate's table instead.
+    async fn embed_candidate(&self, event: &NostrEvent) -> Vec<NostrEventWithEmbedding> {
+        let Some(migration_target) = &self.migration_target else {
+            return Vec::new();
+        };
+        migration_target
+            .embed_event(event)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "Warning: Failed to embed event {} with migration model {}: {}",
+                    event.id,
+                    migration_target.model_id(),
+                    e
+                );
+                Vec::new()
+            })
+    }
+
+    /// Writes the candidate rows of events the current model stored, so
+    /// the candidate's table never holds events the current one lacks.
+    async fn store_candidate_rows<'a>(&self, stored: impl Iterator<Item = &'a EmbeddedEvent>) {
+        let Some(migration_target) = &self.migration_target else {
+            return;
+        };
+        let rows: Vec<NostrEventWithEmbedding> = stored
+            .flat_map(|embedded| embedded.candidate_rows.iter().cloned())
+            .collect();
+        if rows.is_empty() {
+            return;
+        }
+        if let Err(e) = migration_target.store_rows(&rows).await {
+            eprintln!(
+                "Warning: Failed to store {} rows for migration model {}: {}",
+                rows.len(),
+                migration_target.model_id(),
+                e
+            );
+        }
+    }
+
+    /// Hands a stored event to live subscriptions and saved searches. Saved
+    /// searches are notified on their own task, so slow webhooks and relays
+    /// don't hold up ingestion.
+    fn on_stored(&self, embedded: &EmbeddedEvent) {
+        if let Some(live_feed) = &self.live_feed {
+            live_feed.publish(&embedded.event, &embedded.rows);
+        }
+
+        if let Some(saved_searches) = &self.saved_searches
+            && !embedded.rows.is_empty()
+        {
+            let saved_searches = saved_searches.clone();
+            let event = embedded.event.clone();
+            let rows = embedded.rows.clone();
+            tokio::spawn(async move {
+                saved_searches.notify_matches(&event, &rows).await;
+            });
+        }
+    }
+
+    /// Drops a finished event from the WAL.
+    fn forget(&self, event: &NostrEvent) {
+        if let Some(wal) = &self.wal
+            && let Err(e) = wal.remove(&event.id)
+        {
+            eprintln!("Failed to remove event {} from WAL: {}", event.id, e);
+        }
+    }
+
+    async fn wait_for_embedding_provider(&self) {
+        while let Some(remaining) = self.embedding_service.embedding_paused_for() {
+            eprintln!(
+                "Embedding provider unavailable, pausing queue for {}s",
+                remaining.as_secs().max(1)
+            );
+            tokio::time::sleep(remaining).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str) -> NostrEvent {
+        NostrEvent {
+            id: id.to_string(),
+            pubkey: "pubkey".to_string(),
+            created_at: 0,
+            kind: 1,
+            tags: vec![],
+            content: "hello".to_string(),
+            sig: String::new(),
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn test_enqueue_reports_full_and_closed() {
+        let (queue, receiver) = EventQueue::new(1);
+
+        assert_eq!(queue.enqueue(event("a")), Ok(()));
+        assert_eq!(queue.pending(), 1);
+        assert_eq!(queue.enqueue(event("b")), Err(EnqueueError::Full));
 
-        println!("Event processor stopped");
+        drop(receiver);
+        assert_eq!(queue.enqueue(event("c")), Err(EnqueueError::Closed));
     }
 }
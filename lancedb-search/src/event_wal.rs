@@ -0,0 +1,139 @@
+use crate::dead_letter::DeadLetter;
+use crate::nostr::NostrEvent;
+use anyhow::Result;
+
+/// On-disk log of events accepted by `POST /events` that have not been
+/// stored yet. Entries are written before an event is queued and removed
+/// once it has been processed, so anything left over after a crash is
+/// replayed on the next start. Events that failed every retry move to a
+/// separate tree, where they stay until requeued.
+pub struct EventWal {
+    db: sled::Db,
+    dead_letters: sled::Tree,
+}
+
+impl EventWal {
+    pub fn open(path: &str) -> Result<Self> {
+        let db = sled::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open event WAL at {}: {}", path, e))?;
+        Self::from_db(db)
+    }
+
+    /// A WAL that is deleted when dropped.
+    #[cfg(test)]
+    pub(crate) fn temporary() -> Self {
+        Self::from_db(sled::Config::new().temporary(true).open().unwrap()).unwrap()
+    }
+
+    fn from_db(db: sled::Db) -> Result<Self> {
+        let dead_letters = db.open_tree("dead_letters")?;
+        Ok(Self { db, dead_letters })
+    }
+
+    /// Durably records `event` before it is handed to the processor.
+    pub fn append(&self, event: &NostrEvent) -> Result<()> {
+        self.db
+            .insert(event.id.as_bytes(), serde_json::to_vec(event)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Forgets an event once it has been stored or permanently rejected.
+    pub fn remove(&self, event_id: &str) -> Result<()> {
+        self.db.remove(event_id.as_bytes())?;
+        Ok(())
+    }
+
+    /// Events that were accepted but never finished, oldest first.
+    pub fn pending(&self) -> Result<Vec<NostrEvent>> {
+        let mut events = Vec::new();
+        for entry in self.db.iter() {
+            let (key, value) = entry?;
+            match serde_json::from_slice::<NostrEvent>(&value) {
+                Ok(event) => events.push(event),
+                Err(e) => {
+                    eprintln!(
+                        "Dropping unreadable WAL entry {}: {}",
+                        String::from_utf8_lossy(&key),
+                        e
+                    );
+                    self.db.remove(key)?;
+                }
+            }
+        }
+        events.sort_by_key(|event| event.created_at);
+        Ok(events)
+    }
+
+    /// Durably records a dead-lettered event. It is kept even after the
+    /// event itself is removed, until `remove_dead_letter`.
+    pub fn add_dead_letter(&self, letter: &DeadLetter) -> Result<()> {
+        self.dead_letters
+            .insert(letter.event.id.as_bytes(), serde_json::to_vec(letter)?)?;
+        self.dead_letters.flush()?;
+        Ok(())
+    }
+
+    pub fn remove_dead_letter(&self, event_id: &str) -> Result<()> {
+        self.dead_letters.remove(event_id.as_bytes())?;
+        Ok(())
+    }
+
+    /// Dead-lettered events, oldest failure first.
+    pub fn dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        let mut letters = Vec::new();
+        for entry in self.dead_letters.iter() {
+            let (key, value) = entry?;
+            match serde_json::from_slice::<DeadLetter>(&value) {
+                Ok(letter) => letters.push(letter),
+                Err(e) => eprintln!(
+                    "Skipping unreadable dead letter {}: {}",
+                    String::from_utf8_lossy(&key),
+                    e
+                ),
+            }
+        }
+        letters.sort_by_key(|letter| letter.failed_at);
+        Ok(letters)
+    }
+
+    pub fn len(&self) -> usize {
+        self.db.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.db.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, created_at: i64) -> NostrEvent {
+        NostrEvent {
+            id: id.to_string(),
+            pubkey: "pubkey".to_string(),
+            created_at,
+            kind: 1,
+            tags: vec![],
+            content: "hello".to_string(),
+            sig: String::new(),
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn test_pending_replays_unremoved_events_in_order() {
+        let wal = EventWal::temporary();
+
+        wal.append(&event("b", 20)).unwrap();
+        wal.append(&event("a", 10)).unwrap();
+        wal.append(&event("c", 30)).unwrap();
+        wal.remove("c").unwrap();
+
+        let ids: Vec<String> = wal.pending().unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(wal.len(), 2);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::config::FollowGraphConfig;
+use anyhow::Result;
+use nostr_sdk::{Client, Event, Filter, Kind, PublicKey};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Authors per kind-3 filter when fetching the contact lists of everyone a
+/// viewer follows; relays reject filters with very long author lists.
+const AUTHORS_PER_FILTER: usize = 250;
+
+/// Who a viewer follows directly, and who those accounts follow in turn.
+#[derive(Debug, Default)]
+pub struct FollowNetwork {
+    follows: HashSet<String>,
+    second_degree: HashSet<String>,
+}
+
+impl FollowNetwork {
+    /// `follows_of_follows` may repeat direct follows; those only count as
+    /// direct.
+    pub fn new(
+        follows: HashSet<String>,
+        follows_of_follows: impl IntoIterator<Item = String>,
+    ) -> Self {
+        let second_degree = follows_of_follows
+            .into_iter()
+            .filter(|pubkey| !follows.contains(pubkey))
+            .collect();
+        Self {
+            follows,
+            second_degree,
+        }
+    }
+
+    /// `score` raised by the configured boost when `pubkey` is in the
+    /// network. Boosts are relative to the score's magnitude, so weakly
+    /// related posts from friends don't overtake strong matches.
+    pub fn boost(&self, score: f32, pubkey: &str, config: &FollowGraphConfig) -> f32 {
+        let boost = if self.follows.contains(pubkey) {
+            config.follow_boost
+        } else if self.second_degree.contains(pubkey) {
+            config.second_degree_boost
+        } else {
+            0.0
+        };
+        score + score.abs() * boost
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.follows.is_empty()
+    }
+}
+
+/// Fetches viewers' follow networks from their kind-3 contact lists on
+/// public relays and keeps each for `cache_ttl_secs`.
+pub struct FollowGraph {
+    client: Client,
+    config: FollowGraphConfig,
+    connected: Mutex<bool>,
+    cache: Mutex<HashMap<String, (Instant, Arc<FollowNetwork>)>>,
+}
+
+impl FollowGraph {
+    pub fn new(config: FollowGraphConfig) -> Self {
+        Self {
+            client: Client::default(),
+            config,
+            connected: Mutex::new(false),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &FollowGraphConfig {
+        &self.config
+    }
+
+    /// The follow network of `viewer`, a hex public key.
+    pub async fn network(&self, viewer: &str) -> Result<Arc<FollowNetwork>> {
+        let ttl = Duration::from_secs(self.config.cache_ttl_secs);
+        if let Some((fetched_at, network)) = self.cache.lock().await.get(viewer)
+            && fetched_at.elapsed() < ttl
+        {
+            return Ok(network.clone());
+        }
+
+        // Fetched without holding the lock so other viewers aren't blocked
+        let network = Arc::new(self.fetch_network(viewer).await?);
+
+        let mut cache = self.cache.lock().await;
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ttl);
+        cache.insert(viewer.to_string(), (Instant::now(), network.clone()));
+        Ok(network)
+    }
+
+    async fn fetch_network(&self, viewer: &str) -> Result<FollowNetwork> {
+        self.connect().await?;
+
+        let viewer = PublicKey::from_hex(viewer)?;
+        let follows: HashSet<String> = self
+            .contact_lists(vec![viewer])
+            .await?
+            .remove(&viewer)
+            .map(|contact_list| followed_pubkeys(&contact_list))
+            .unwrap_or_default();
+
+        let followed_keys: Vec<PublicKey> = follows
+            .iter()
+            .filter_map(|pubkey| PublicKey::from_hex(pubkey).ok())
+            .collect();
+        let lookups = followed_keys
+            .chunks(AUTHORS_PER_FILTER)
+            .map(|authors| self.contact_lists(authors.to_vec()));
+        let mut follows_of_follows = Vec::new();
+        for found in futures::future::join_all(lookups).await {
+            match found {
+                Ok(contact_lists) => {
+                    follows_of_follows.extend(contact_lists.values().flat_map(followed_pubkeys))
+                }
+                // A partial second degree is still useful
+                Err(e) => eprintln!("Warning: Failed to fetch contact lists: {}", e),
+            }
+        }
+
+        let viewer = viewer.to_hex();
+        Ok(FollowNetwork::new(
+            follows,
+            follows_of_follows
+                .into_iter()
+                .filter(|pubkey| *pubkey != viewer),
+        ))
+    }
+
+    /// The newest contact list of each author that has one.
+    async fn contact_lists(&self, authors: Vec<PublicKey>) -> Result<HashMap<PublicKey, Event>> {
+        let filter = Filter::new().authors(authors).kind(Kind::ContactList);
+        let events = self
+            .client
+            .fetch_events_from(
+                self.config.relays.iter().map(String::as_str),
+                filter,
+                Duration::from_secs(self.config.timeout_secs),
+            )
+            .await?;
+
+        let mut newest: HashMap<PublicKey, Event> = HashMap::new();
+        for event in events {
+            match newest.get(&event.pubkey) {
+                Some(existing) if existing.created_at >= event.created_at => {}
+                _ => {
+                    newest.insert(event.pubkey, event);
+                }
+            }
+        }
+        Ok(newest)
+    }
+
+    async fn connect(&self) -> Result<()> {
+        let mut connected = self.connected.lock().await;
+        if !*connected {
+            for relay in &self.config.relays {
+                self.client.add_relay(relay.as_str()).await?;
+            }
+            self.client.connect().await;
+            *connected = true;
+        }
+        Ok(())
+    }
+}
+
+/// Hex public keys in a contact list's `p` tags.
+fn followed_pubkeys(contact_list: &Event) -> HashSet<String> {
+    contact_list
+        .tags
+        .public_keys()
+        .map(|pubkey| pubkey.to_hex())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> FollowGraphConfig {
+        FollowGraphConfig {
+            relays: Vec::new(),
+            timeout_secs: 5,
+            cache_ttl_secs: 60,
+            follow_boost: 0.2,
+            second_degree_boost: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_direct_follows_outrank_second_degree() {
+        let network = FollowNetwork::new(
+            HashSet::from(["alice".to_string()]),
+            ["alice".to_string(), "bob".to_string()],
+        );
+        let config = config();
+
+        let alice = network.boost(0.5, "alice", &config);
+        let bob = network.boost(0.5, "bob", &config);
+        let carol = network.boost(0.5, "carol", &config);
+        assert!((alice - 0.6).abs() < 1e-6);
+        assert!((bob - 0.525).abs() < 1e-6);
+        assert_eq!(carol, 0.5);
+    }
+
+    #[test]
+    fn test_boost_never_lowers_negative_scores() {
+        let network = FollowNetwork::new(HashSet::from(["alice".to_string()]), []);
+        assert!(network.boost(-0.5, "alice", &config()) > -0.5);
+    }
+}
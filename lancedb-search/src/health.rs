@@ -0,0 +1,80 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// How long a single dependency probe may take before it counts as failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DependencyHealth {
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub checks: BTreeMap<String, DependencyHealth>,
+}
+
+impl HealthReport {
+    /// A shallow report: the process is up and serving requests.
+    pub fn alive() -> Self {
+        Self {
+            status: HealthStatus::Ok,
+            checks: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, name: &str, health: DependencyHealth) {
+        if health.status == HealthStatus::Error {
+            self.status = HealthStatus::Error;
+        }
+        self.checks.insert(name.to_string(), health);
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Ok
+    }
+}
+
+/// Runs a dependency probe with a timeout and records its latency.
+pub async fn probe<F>(check: F) -> DependencyHealth
+where
+    F: Future<Output = Result<()>>,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(PROBE_TIMEOUT, check).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(Ok(())) => DependencyHealth {
+            status: HealthStatus::Ok,
+            latency_ms,
+            error: None,
+        },
+        Ok(Err(e)) => DependencyHealth {
+            status: HealthStatus::Error,
+            latency_ms,
+            error: Some(e.to_string()),
+        },
+        Err(_) => DependencyHealth {
+            status: HealthStatus::Error,
+            latency_ms,
+            error: Some(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+        },
+    }
+}
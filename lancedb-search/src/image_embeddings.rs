@@ -0,0 +1,145 @@
+use crate::config::ImageEmbeddingConfig;
+use anyhow::Result;
+
+/// CLIP-style image and text encoders that share one vector space, so a
+/// text query can be compared with image embeddings. Runs in-process with
+/// fastembed and needs the `local-embeddings` feature.
+pub struct ImageEmbedder {
+    #[cfg(feature = "local-embeddings")]
+    image_model: std::sync::Arc<std::sync::Mutex<fastembed::ImageEmbedding>>,
+    #[cfg(feature = "local-embeddings")]
+    text_model: std::sync::Arc<std::sync::Mutex<fastembed::TextEmbedding>>,
+    #[cfg(feature = "local-embeddings")]
+    http_client: reqwest::Client,
+    #[cfg(feature = "local-embeddings")]
+    max_bytes: usize,
+    model_id: String,
+    dimensions: usize,
+}
+
+impl ImageEmbedder {
+    #[cfg(feature = "local-embeddings")]
+    pub fn new(config: &ImageEmbeddingConfig, cache_dir: Option<&str>) -> Result<Self> {
+        use fastembed::{ImageEmbedding, ImageInitOptions, TextEmbedding, TextInitOptions};
+
+        let image_info = ImageEmbedding::list_supported_models()
+            .into_iter()
+            .find(|info| info.model_code.eq_ignore_ascii_case(&config.model))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported image embedding model '{}', expected a fastembed model code such as Qdrant/clip-ViT-B-32-vision",
+                    config.model
+                )
+            })?;
+        let text_info = TextEmbedding::list_supported_models()
+            .into_iter()
+            .find(|info| info.model_code.eq_ignore_ascii_case(&config.text_model))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported image query model '{}', expected a fastembed model code such as Qdrant/clip-ViT-B-32-text",
+                    config.text_model
+                )
+            })?;
+
+        if image_info.dim != text_info.dim {
+            anyhow::bail!(
+                "{} produces {} dimensions but {} produces {}; both must come from the same CLIP model",
+                image_info.model_code,
+                image_info.dim,
+                text_info.model_code,
+                text_info.dim
+            );
+        }
+
+        let mut image_options =
+            ImageInitOptions::new(image_info.model.clone()).with_show_download_progress(true);
+        let mut text_options =
+            TextInitOptions::new(text_info.model.clone()).with_show_download_progress(true);
+        if let Some(cache_dir) = cache_dir {
+            image_options = image_options.with_cache_dir(cache_dir.into());
+            text_options = text_options.with_cache_dir(cache_dir.into());
+        }
+
+        Ok(Self {
+            image_model: std::sync::Arc::new(std::sync::Mutex::new(ImageEmbedding::try_new(
+                image_options,
+            )?)),
+            text_model: std::sync::Arc::new(std::sync::Mutex::new(TextEmbedding::try_new(
+                text_options,
+            )?)),
+            http_client: reqwest::Client::new(),
+            max_bytes: config.max_bytes,
+            model_id: image_info.model_code.clone(),
+            dimensions: image_info.dim,
+        })
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    pub fn new(_config: &ImageEmbeddingConfig, _cache_dir: Option<&str>) -> Result<Self> {
+        anyhow::bail!(
+            "Image embeddings require building lancedb-search with the `local-embeddings` feature"
+        )
+    }
+
+    /// Model code stored with every image vector.
+    pub fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Downloads the image at `url` and embeds it.
+    #[cfg(feature = "local-embeddings")]
+    pub async fn embed_image_url(&self, url: &str) -> Result<Vec<f32>> {
+        let response = self.http_client.get(url).send().await?.error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length as usize > self.max_bytes)
+        {
+            anyhow::bail!("Image is larger than {} bytes", self.max_bytes);
+        }
+
+        let bytes = response.bytes().await?;
+        if bytes.len() > self.max_bytes {
+            anyhow::bail!("Image is larger than {} bytes", self.max_bytes);
+        }
+
+        let model = self.image_model.clone();
+        let mut embedding = tokio::task::spawn_blocking(move || {
+            model.lock().unwrap().embed_bytes(&[bytes.as_ref()], None)
+        })
+        .await??
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Image model returned no embedding"))?;
+        crate::embeddings::l2_normalize(&mut embedding);
+        Ok(embedding)
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    pub async fn embed_image_url(&self, _url: &str) -> Result<Vec<f32>> {
+        anyhow::bail!("Image embeddings require the `local-embeddings` feature")
+    }
+
+    /// Embeds a text query into the image vector space.
+    #[cfg(feature = "local-embeddings")]
+    pub async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        let model = self.text_model.clone();
+        let text = text.to_string();
+        let mut embedding =
+            tokio::task::spawn_blocking(move || model.lock().unwrap().embed(vec![text], None))
+                .await??
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Image query model returned no embedding"))?;
+        crate::embeddings::l2_normalize(&mut embedding);
+        Ok(embedding)
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    pub async fn embed_text(&self, _text: &str) -> Result<Vec<f32>> {
+        anyhow::bail!("Image embeddings require the `local-embeddings` feature")
+    }
+}
@@ -0,0 +1,103 @@
+use crate::embedding_service::EmbeddingSearchService;
+use crate::nostr::NostrEvent;
+use anyhow::Result;
+use std::io::BufRead;
+
+/// Running totals of an import.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportProgress {
+    /// Non-empty lines read
+    pub read: usize,
+    pub imported: usize,
+    /// Lines that weren't an event or failed verification
+    pub invalid: usize,
+    /// Events in batches that could not be embedded or stored
+    pub failed: usize,
+}
+
+/// Imports newline-delimited event JSON, as written by `strfry export`,
+/// embedding and storing `batch_size` events at a time. Invalid lines are
+/// reported and skipped rather than aborting the import.
+pub async fn import_events<R: BufRead>(
+    service: &EmbeddingSearchService,
+    reader: R,
+    batch_size: usize,
+    verify_signatures: bool,
+) -> Result<ImportProgress> {
+    let mut progress = ImportProgress::default();
+    let mut batch = Vec::with_capacity(batch_size);
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        progress.read += 1;
+
+        match parse_event(&line, verify_signatures) {
+            Ok(event) => batch.push(event),
+            Err(e) => {
+                eprintln!("Skipping line {}: {}", index + 1, e);
+                progress.invalid += 1;
+            }
+        }
+
+        if batch.len() >= batch_size {
+            store_batch(service, &mut batch, &mut progress).await;
+        }
+    }
+    store_batch(service, &mut batch, &mut progress).await;
+
+    Ok(progress)
+}
+
+async fn store_batch(
+    service: &EmbeddingSearchService,
+    batch: &mut Vec<NostrEvent>,
+    progress: &mut ImportProgress,
+) {
+    if batch.is_empty() {
+        return;
+    }
+
+    match service.embed_and_store_events(batch).await {
+        Ok(()) => progress.imported += batch.len(),
+        Err(e) => {
+            eprintln!("Failed to import {} events: {}", batch.len(), e);
+            progress.failed += batch.len();
+        }
+    }
+    println!(
+        "Imported {} of {} events ({} invalid, {} failed)",
+        progress.imported, progress.read, progress.invalid, progress.failed
+    );
+    batch.clear();
+}
+
+fn parse_event(line: &str, verify_signatures: bool) -> Result<NostrEvent> {
+    let event: NostrEvent = serde_json::from_str(line)?;
+    if verify_signatures {
+        event.verify()?;
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_checks_signatures() {
+        let keys = nostr_sdk::Keys::generate();
+        let event = nostr_sdk::EventBuilder::text_note("hello nostr")
+            .sign_with_keys(&keys)
+            .unwrap();
+        let line = serde_json::to_string(&NostrEvent::from(event)).unwrap();
+        assert!(parse_event(&line, true).is_ok());
+
+        let tampered = line.replace("hello nostr", "hello relay");
+        assert!(parse_event(&tampered, true).is_err());
+        assert!(parse_event(&tampered, false).is_ok());
+        assert!(parse_event("not json", false).is_err());
+    }
+}
@@ -1,30 +1,385 @@
+use crate::config::{Distance, IndexConfig, IndexType};
 use crate::nostr::NostrEventWithEmbedding;
+use crate::vector_store::{
+    DEFAULT_MODEL_ID, ExportedRow, SearchFilters, SearchHit, TableVersion, VectorColumn,
+    VectorStore,
+};
 use anyhow::Result;
+use arrow_array::builder::{ListBuilder, StringBuilder};
 use arrow_array::{
-    Array, FixedSizeListArray, Int64Array, RecordBatch, RecordBatchIterator, StringArray,
+    Array, FixedSizeListArray, Float32Array, Int64Array, ListArray, RecordBatch,
+    RecordBatchIterator, StringArray,
 };
 use arrow_schema::{DataType, Field, Schema};
+use async_trait::async_trait;
 use futures::TryStreamExt;
-use lancedb::query::{ExecutableQuery, QueryBase};
-use lancedb::{Connection, connect};
+use lancedb::DistanceType;
+use lancedb::index::Index;
+use lancedb::index::scalar::{FtsIndexBuilder, FullTextSearchQuery};
+use lancedb::index::vector::{
+    IvfFlatIndexBuilder, IvfHnswSqIndexBuilder, IvfPqIndexBuilder, OptimizeOptions,
+};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::table::{CompactionOptions, NewColumnTransform, OptimizeAction};
+use lancedb::{Connection, Table, connect};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+impl SearchFilters {
+    pub fn to_sql(&self) -> Option<String> {
+        let mut filter_clauses = Vec::new();
+
+        match self.authors.as_slice() {
+            [] => {}
+            [author] => {
+                filter_clauses.push(format!("pubkey = '{}'", escape_sql_string(author)));
+            }
+            authors => {
+                let authors = authors
+                    .iter()
+                    .map(|author| format!("'{}'", escape_sql_string(author)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                filter_clauses.push(format!("pubkey IN ({})", authors));
+            }
+        }
+
+        if let Some(model_id) = &self.model_id {
+            filter_clauses.push(format!("model_id = '{}'", escape_sql_string(model_id)));
+        }
+
+        if let Some(kind) = self.kind {
+            filter_clauses.push(format!("kind = {}", kind));
+        }
+
+        if let Some(min_created) = self.min_created_at {
+            filter_clauses.push(format!("created_at >= {}", min_created));
+        }
+
+        if let Some(max_created) = self.max_created_at {
+            filter_clauses.push(format!("created_at <= {}", max_created));
+        }
+
+        if let Some(geohash) = &self.geohash {
+            filter_clauses.push(format!(
+                "geohash LIKE '{}%'",
+                escape_sql_string(&geohash.to_lowercase())
+            ));
+        }
+
+        if self.exclude_duplicates {
+            filter_clauses.push("duplicate_of IS NULL".to_string());
+        }
+
+        let mut tag_names: Vec<&String> = self.tags.keys().collect();
+        tag_names.sort();
+        for name in tag_names {
+            let values = &self.tags[name];
+            if values.is_empty() {
+                continue;
+            }
+            let values = values
+                .iter()
+                .map(|value| format!("'{}'", escape_sql_string(&format!("{}:{}", name, value))))
+                .collect::<Vec<_>>()
+                .join(", ");
+            filter_clauses.push(format!("array_has_any(tag_values, [{}])", values));
+        }
+
+        if filter_clauses.is_empty() {
+            None
+        } else {
+            Some(filter_clauses.join(" AND "))
+        }
+    }
+}
+
+/// Maximum number of IDs in a single `id IN (...)` delete predicate.
+const DELETE_BATCH_SIZE: usize = 500;
+
+fn hits_from_batches(batches: &[RecordBatch]) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+
+    for batch in batches {
+        // Chunk rows are reported under the event they belong to
+        let ids = batch
+            .column_by_name("parent_id")
+            .or_else(|| batch.column_by_name("id"))
+            .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+        let pubkeys = batch
+            .column_by_name("pubkey")
+            .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+        let created_ats = batch
+            .column_by_name("created_at")
+            .and_then(|column| column.as_any().downcast_ref::<Int64Array>());
+        let embeddings = batch
+            .column_by_name(VectorColumn::Content.name())
+            .and_then(|column| column.as_any().downcast_ref::<FixedSizeListArray>());
+        let summary_embeddings = batch
+            .column_by_name(VectorColumn::Summary.name())
+            .and_then(|column| column.as_any().downcast_ref::<FixedSizeListArray>());
+        let distances = batch
+            .column_by_name("_distance")
+            .and_then(|column| column.as_any().downcast_ref::<Float32Array>());
+        let contents = batch
+            .column_by_name("content")
+            .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+
+        if let (Some(ids), Some(pubkeys), Some(created_ats), Some(embeddings)) =
+            (ids, pubkeys, created_ats, embeddings)
+        {
+            for i in 0..ids.len() {
+                let embedding = embedding_at(embeddings, i);
+                let summary_embedding = summary_embeddings
+                    .map(|summary_embeddings| embedding_at(summary_embeddings, i))
+                    .unwrap_or_else(|| embedding.clone());
+                hits.push(SearchHit {
+                    id: ids.value(i).to_string(),
+                    distance: distances.map_or(0.0, |distances| distances.value(i)),
+                    pubkey: pubkeys.value(i).to_string(),
+                    created_at: created_ats.value(i),
+                    embedding,
+                    summary_embedding,
+                    content: contents
+                        .filter(|contents| contents.is_valid(i))
+                        .map(|contents| contents.value(i).to_string()),
+                });
+            }
+        }
+    }
+
+    hits
+}
+
+/// Converts a batch of exported columns into rows. Tables that were never
+/// migrated lack `parent_id` and `model_id`; their rows are whole events
+/// embedded by `model_id`, matching what the migration would backfill.
+fn rows_from_batch(batch: &RecordBatch, model_id: &str) -> Vec<ExportedRow> {
+    let strings = |name: &str| {
+        batch
+            .column_by_name(name)
+            .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+    };
+    let integers = |name: &str| {
+        batch
+            .column_by_name(name)
+            .and_then(|column| column.as_any().downcast_ref::<Int64Array>())
+    };
+    let vectors = |column: VectorColumn| {
+        batch
+            .column_by_name(column.name())
+            .and_then(|column| column.as_any().downcast_ref::<FixedSizeListArray>())
+    };
+    let optional = |values: Option<&StringArray>, i: usize| {
+        values
+            .filter(|values| values.is_valid(i))
+            .map(|values| values.value(i).to_string())
+    };
+
+    let (Some(ids), Some(pubkeys), Some(kinds), Some(created_ats), Some(tags)) = (
+        strings("id"),
+        strings("pubkey"),
+        integers("kind"),
+        integers("created_at"),
+        strings("tags"),
+    ) else {
+        return Vec::new();
+    };
+    let parent_ids = strings("parent_id");
+    let model_ids = strings("model_id");
+    let addresses = strings("address");
+    let contents = strings("content");
+    let embeddings = vectors(VectorColumn::Content);
+    let summary_embeddings = vectors(VectorColumn::Summary);
+
+    (0..ids.len())
+        .map(|i| ExportedRow {
+            id: ids.value(i).to_string(),
+            parent_id: optional(parent_ids, i).unwrap_or_else(|| ids.value(i).to_string()),
+            pubkey: pubkeys.value(i).to_string(),
+            kind: kinds.value(i),
+            created_at: created_ats.value(i),
+            tags: serde_json::from_str(tags.value(i)).unwrap_or_default(),
+            content: optional(contents, i),
+            model_id: optional(model_ids, i).unwrap_or_else(|| model_id.to_string()),
+            address: optional(addresses, i),
+            content_embedding: embeddings.map(|embeddings| embedding_at(embeddings, i)),
+            summary_embedding: summary_embeddings
+                .map(|summary_embeddings| embedding_at(summary_embeddings, i)),
+        })
+        .collect()
+}
+
+fn embedding_at(embeddings: &FixedSizeListArray, index: usize) -> Vec<f32> {
+    embeddings
+        .value(index)
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .map(|values| values.values().to_vec())
+        .unwrap_or_default()
+}
+
+fn distance_type(distance: Distance) -> DistanceType {
+    match distance {
+        Distance::L2 => DistanceType::L2,
+        Distance::Cosine => DistanceType::Cosine,
+        Distance::Dot => DistanceType::Dot,
+    }
+}
+
+/// How rows written before a non-nullable column existed fill it in.
+/// `model_id` is the id of the model the store is opened for.
+fn backfill_expression(column: &str, model_id: &str) -> Option<String> {
+    match column {
+        // Tables predating model ids hold vectors of the model they are
+        // still opened with; any other id would hide every existing row
+        "model_id" => Some(format!("'{}'", escape_sql_string(model_id))),
+        // Rows written before chunking are whole events
+        "parent_id" => Some("id".to_string()),
+        // Events without a summary are searched by their content there
+        "summary_embedding" => Some("content_embedding".to_string()),
+        _ => None,
+    }
+}
+
+fn escape_sql_string(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+/// A scored vector search result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub id: String,
+    /// Vector distance reported by LanceDB (lower is closer)
+    pub distance: f32,
+    /// Distance mapped into `(0, 1]`, higher is more relevant
+    pub relevance: f32,
+}
+
+impl SearchResult {
+    pub fn new(id: String, distance: f32) -> Self {
+        Self {
+            id,
+            distance,
+            relevance: distance_to_relevance(distance),
+        }
+    }
+}
+
+pub fn distance_to_relevance(distance: f32) -> f32 {
+    1.0 / (1.0 + distance.max(0.0))
+}
+
 pub struct LanceDBStore {
     connection: Connection,
     table_name: String,
+    dimensions: usize,
+    index_config: IndexConfig,
+    model_id: String,
 }
 
 impl LanceDBStore {
-    pub async fn new(db_path: &str, table_name: &str) -> Result<Self> {
-        let connection = connect(db_path).execute().await?;
+    /// Opens (or creates) the table, whose vector columns hold
+    /// `dimensions`-sized embeddings written by `model_id`.
+    pub async fn new(
+        db_path: &str,
+        table_name: &str,
+        dimensions: usize,
+        model_id: &str,
+    ) -> Result<Self> {
+        Self::with_storage_options(db_path, table_name, dimensions, model_id, &HashMap::new()).await
+    }
 
-        let store = Self {
+    /// Like `new`, but `db_path` may also be an object store URI such as
+    /// `s3://bucket/path` or `gs://bucket/path`. `storage_options` carries
+    /// credentials and endpoint settings, e.g. `aws_access_key_id`,
+    /// `aws_region` or `google_service_account`.
+    pub async fn with_storage_options(
+        db_path: &str,
+        table_name: &str,
+        dimensions: usize,
+        model_id: &str,
+        storage_options: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut store = Self::read_only(db_path, table_name, dimensions, storage_options).await?;
+        store.model_id = model_id.to_string();
+        store.create_table_if_not_exists().await?;
+        Ok(store)
+    }
+
+    /// Connects without creating or migrating the table, for read replicas
+    /// that share it with a writer. Searches find nothing until the writer
+    /// has created it.
+    pub async fn read_only(
+        db_path: &str,
+        table_name: &str,
+        dimensions: usize,
+        storage_options: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let connection = connect(db_path)
+            .storage_options(
+                storage_options
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            )
+            .execute()
+            .await?;
+
+        Ok(Self {
             connection,
             table_name: table_name.to_string(),
+            dimensions,
+            index_config: IndexConfig::default(),
+            model_id: DEFAULT_MODEL_ID.to_string(),
+        })
+    }
+
+    /// Opens an existing table for reading, whatever embedding model
+    /// filled it. Used as the source of a reindex, so neither creates nor
+    /// migrates the table.
+    pub async fn open_existing(
+        db_path: &str,
+        table_name: &str,
+        storage_options: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let connection = connect(db_path)
+            .storage_options(
+                storage_options
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            )
+            .execute()
+            .await?;
+        let table = connection.open_table(table_name).execute().await?;
+        let schema = table.schema().await?;
+        let dimensions = match schema
+            .field_with_name(VectorColumn::Content.name())
+            .map(|field| field.data_type().clone())
+        {
+            Ok(DataType::FixedSizeList(_, size)) => size as usize,
+            _ => anyhow::bail!("Table '{}' has no embedding column", table_name),
         };
 
-        store.create_table_if_not_exists().await?;
-        Ok(store)
+        Ok(Self {
+            connection,
+            table_name: table_name.to_string(),
+            dimensions,
+            index_config: IndexConfig::default(),
+            model_id: DEFAULT_MODEL_ID.to_string(),
+        })
+    }
+
+    /// Opens the table, checked out at `version` when one is given.
+    async fn open_table_at(&self, version: Option<u64>) -> Result<Table> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+        if let Some(version) = version {
+            table.checkout(version).await?;
+        }
+        Ok(table)
     }
 
     async fn create_table_if_not_exists(&self) -> Result<()> {
@@ -39,6 +394,85 @@ impl LanceDBStore {
                 .create_table(&self.table_name, Box::new(batches))
                 .execute()
                 .await?;
+        } else {
+            self.check_dimensions().await?;
+            self.add_missing_columns().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds columns introduced after the table was created, so existing
+    /// tables keep accepting inserts. Columns with a backfill expression
+    /// are filled in for old rows; nullable ones stay null until the rows
+    /// are reindexed.
+    async fn add_missing_columns(&self) -> Result<()> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+        let existing = table.schema().await?;
+
+        let mut nulls = Vec::new();
+        let mut expressions = Vec::new();
+        for field in self.get_schema().fields() {
+            if existing.field_with_name(field.name()).is_ok() {
+                continue;
+            }
+            if let Some(expression) = backfill_expression(field.name(), &self.model_id) {
+                expressions.push((field.name().clone(), expression));
+            } else if field.is_nullable() {
+                nulls.push(field.as_ref().clone());
+            }
+        }
+
+        if !nulls.is_empty() {
+            eprintln!(
+                "Adding columns {:?} to table {}",
+                nulls.iter().map(|field| field.name()).collect::<Vec<_>>(),
+                self.table_name
+            );
+            table
+                .add_columns(
+                    NewColumnTransform::AllNulls(Arc::new(Schema::new(nulls))),
+                    None,
+                )
+                .await?;
+        }
+        if !expressions.is_empty() {
+            eprintln!(
+                "Adding columns {:?} to table {}",
+                expressions.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+                self.table_name
+            );
+            table
+                .add_columns(NewColumnTransform::SqlExpressions(expressions), None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Fails early when an existing table was created for a different
+    /// embedding model, rather than on the first insert.
+    async fn check_dimensions(&self) -> Result<()> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+        let schema = table.schema().await?;
+
+        if let Ok(field) = schema.field_with_name(VectorColumn::Content.name())
+            && let DataType::FixedSizeList(_, size) = field.data_type()
+            && *size as usize != self.dimensions
+        {
+            anyhow::bail!(
+                "Table '{}' stores {}-dimensional embeddings but the embedding model produces {}; use a new table or reindex",
+                self.table_name,
+                size,
+                self.dimensions
+            );
         }
 
         Ok(())
@@ -51,63 +485,42 @@ impl LanceDBStore {
             Field::new("created_at", DataType::Int64, false),
             Field::new("kind", DataType::Int64, false),
             Field::new("tags", DataType::Utf8, false),
+            Field::new(
+                "tag_values",
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                // Null for rows indexed before tag filtering until reindexed
+                true,
+            ),
             Field::new(
                 "content_embedding",
-                DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 768),
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    self.dimensions as i32,
+                ),
+                false,
+            ),
+            Field::new(
+                "summary_embedding",
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    self.dimensions as i32,
+                ),
                 false,
             ),
+            Field::new("content", DataType::Utf8, true),
+            Field::new("model_id", DataType::Utf8, false),
+            Field::new("parent_id", DataType::Utf8, false),
+            Field::new("address", DataType::Utf8, true),
+            Field::new("geohash", DataType::Utf8, true),
+            Field::new("duplicate_of", DataType::Utf8, true),
         ]))
     }
 
     pub async fn insert_event(&self, event: &NostrEventWithEmbedding) -> Result<()> {
-        let schema = self.get_schema();
-
-        let id_array = StringArray::from(vec![event.id.clone()]);
-        let pubkey_array = StringArray::from(vec![event.pubkey.clone()]);
-        let created_at_array = Int64Array::from(vec![event.created_at]);
-        let kind_array = Int64Array::from(vec![event.kind as i64]);
-        let tags_array = StringArray::from(vec![event.tags.clone()]);
-
-        let embedding_array =
-            FixedSizeListArray::from_iter_primitive::<arrow_array::types::Float32Type, _, _>(
-                std::iter::once(Some(
-                    event
-                        .content_embedding
-                        .iter()
-                        .map(|&x| Some(x))
-                        .collect::<Vec<_>>(),
-                )),
-                768,
-            );
-
-        let batch = RecordBatch::try_new(
-            schema,
-            vec![
-                Arc::new(id_array),
-                Arc::new(pubkey_array),
-                Arc::new(created_at_array),
-                Arc::new(kind_array),
-                Arc::new(tags_array),
-                Arc::new(embedding_array),
-            ],
-        )?;
-
-        let table = self
-            .connection
-            .open_table(&self.table_name)
-            .execute()
-            .await?;
-        let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), self.get_schema());
-        table.add(Box::new(batches)).execute().await?;
-
-        Ok(())
+        self.insert_events(std::slice::from_ref(event)).await
     }
 
-    pub async fn insert_events(&self, events: &[NostrEventWithEmbedding]) -> Result<()> {
-        if events.is_empty() {
-            return Ok(());
-        }
-
+    fn to_record_batch(&self, events: &[NostrEventWithEmbedding]) -> Result<RecordBatch> {
         let schema = self.get_schema();
 
         let ids: Vec<String> = events.iter().map(|e| e.id.clone()).collect();
@@ -115,23 +528,54 @@ impl LanceDBStore {
         let created_ats: Vec<i64> = events.iter().map(|e| e.created_at).collect();
         let kinds: Vec<i64> = events.iter().map(|e| e.kind as i64).collect();
         let tags: Vec<String> = events.iter().map(|e| e.tags.clone()).collect();
+        let contents: Vec<Option<String>> = events.iter().map(|e| e.content.clone()).collect();
+        let model_ids: Vec<&str> = events.iter().map(|_| self.model_id.as_str()).collect();
+        let parent_ids: Vec<String> = events.iter().map(|e| e.parent_id.clone()).collect();
+        let addresses: Vec<Option<String>> = events.iter().map(|e| e.address.clone()).collect();
+        let geohashes: Vec<Option<String>> = events.iter().map(|e| e.geohash.clone()).collect();
+        let duplicates_of: Vec<Option<String>> =
+            events.iter().map(|e| e.duplicate_of.clone()).collect();
+
+        let mut tag_values_builder = ListBuilder::new(StringBuilder::new());
+        for event in events {
+            for value in &event.tag_values {
+                tag_values_builder.values().append_value(value);
+            }
+            tag_values_builder.append(true);
+        }
 
         let embeddings: Vec<Vec<Option<f32>>> = events
             .iter()
             .map(|e| e.content_embedding.iter().map(|&x| Some(x)).collect())
             .collect();
+        let summary_embeddings: Vec<Vec<Option<f32>>> = events
+            .iter()
+            .map(|e| e.summary_embedding.iter().map(|&x| Some(x)).collect())
+            .collect();
 
         let id_array = StringArray::from(ids);
         let pubkey_array = StringArray::from(pubkeys);
         let created_at_array = Int64Array::from(created_ats);
         let kind_array = Int64Array::from(kinds);
         let tags_array = StringArray::from(tags);
+        let content_array = StringArray::from(contents);
+        let model_id_array = StringArray::from(model_ids);
+        let parent_id_array = StringArray::from(parent_ids);
+        let address_array = StringArray::from(addresses);
+        let geohash_array = StringArray::from(geohashes);
+        let duplicate_of_array = StringArray::from(duplicates_of);
+        let tag_values_array = tag_values_builder.finish();
 
         let embedding_array = FixedSizeListArray::from_iter_primitive::<
             arrow_array::types::Float32Type,
             _,
             _,
-        >(embeddings.into_iter().map(Some), 768);
+        >(embeddings.into_iter().map(Some), self.dimensions as i32);
+        let summary_embedding_array =
+            FixedSizeListArray::from_iter_primitive::<arrow_array::types::Float32Type, _, _>(
+                summary_embeddings.into_iter().map(Some),
+                self.dimensions as i32,
+            );
 
         let batch = RecordBatch::try_new(
             schema,
@@ -141,26 +585,196 @@ impl LanceDBStore {
                 Arc::new(created_at_array),
                 Arc::new(kind_array),
                 Arc::new(tags_array),
+                Arc::new(tag_values_array),
                 Arc::new(embedding_array),
+                Arc::new(summary_embedding_array),
+                Arc::new(content_array),
+                Arc::new(model_id_array),
+                Arc::new(parent_id_array),
+                Arc::new(address_array),
+                Arc::new(geohash_array),
+                Arc::new(duplicate_of_array),
             ],
         )?;
 
+        Ok(batch)
+    }
+
+    pub async fn search_similar(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_similar_with_filters(query_embedding, limit, &SearchFilters::default())
+            .await
+    }
+
+    pub async fn search_similar_with_filters(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        let hits = self
+            .search_similar_with_embeddings(query_embedding, limit, filters, VectorColumn::Content)
+            .await?;
+
+        Ok(hits
+            .into_iter()
+            .map(|hit| SearchResult::new(hit.id, hit.distance))
+            .collect())
+    }
+
+    /// Passes every stored row to `visit`, one record batch at a time so
+    /// the table never has to fit in memory. Content and vectors are only
+    /// read when asked for. Columns the table predates are left out of the
+    /// query, so unmigrated tables can be exported as they are. Returns the
+    /// number of rows visited.
+    pub async fn export_rows<F>(
+        &self,
+        include_content: bool,
+        include_vectors: bool,
+        mut visit: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(ExportedRow) -> Result<()>,
+    {
+        let table = self.open_table_at(None).await?;
+        let schema = table.schema().await?;
+
+        let mut columns = vec![
+            "id",
+            "parent_id",
+            "pubkey",
+            "kind",
+            "created_at",
+            "tags",
+            "model_id",
+            "address",
+        ];
+        if include_content {
+            columns.push("content");
+        }
+        if include_vectors {
+            columns.push(VectorColumn::Content.name());
+            columns.push(VectorColumn::Summary.name());
+        }
+        columns.retain(|column| schema.field_with_name(column).is_ok());
+
+        let mut batches = table
+            .query()
+            .select(Select::columns(&columns))
+            .execute()
+            .await?;
+
+        let mut count = 0;
+        while let Some(batch) = batches.try_next().await? {
+            for row in rows_from_batch(&batch, &self.model_id) {
+                visit(row)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn vector_index(&self) -> Index {
+        let config = &self.index_config;
+        let distance = distance_type(config.distance);
+
+        match config.index_type {
+            IndexType::Auto => Index::Auto,
+            IndexType::IvfFlat => {
+                let mut builder = IvfFlatIndexBuilder::default().distance_type(distance);
+                if let Some(num_partitions) = config.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                Index::IvfFlat(builder)
+            }
+            IndexType::IvfPq => {
+                let mut builder = IvfPqIndexBuilder::default().distance_type(distance);
+                if let Some(num_partitions) = config.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                if let Some(num_sub_vectors) = config.num_sub_vectors {
+                    builder = builder.num_sub_vectors(num_sub_vectors);
+                }
+                Index::IvfPq(builder)
+            }
+            IndexType::IvfHnswSq => {
+                let mut builder = IvfHnswSqIndexBuilder::default().distance_type(distance);
+                if let Some(num_partitions) = config.num_partitions {
+                    builder = builder.num_partitions(num_partitions);
+                }
+                Index::IvfHnswSq(builder)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl VectorStore for LanceDBStore {
+    async fn list_versions(&self) -> Result<Vec<TableVersion>> {
+        let table = self.open_table_at(None).await?;
+        let versions = table
+            .list_versions()
+            .await?
+            .into_iter()
+            .map(|version| TableVersion {
+                version: version.version,
+                timestamp: version.timestamp.timestamp(),
+            })
+            .collect();
+        Ok(versions)
+    }
+
+    async fn rollback(&self, version: u64) -> Result<()> {
+        let table = self.open_table_at(Some(version)).await?;
+        table.restore().await?;
+        Ok(())
+    }
+
+    fn set_index_config(&mut self, index_config: IndexConfig) {
+        self.index_config = index_config;
+    }
+
+    fn set_model_id(&mut self, model_id: &str) {
+        self.model_id = model_id.to_string();
+    }
+
+    async fn insert_events(&self, events: &[NostrEventWithEmbedding]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        // Merge-insert rejects source rows that match the same target row,
+        // so keep only the last copy of each event in the batch.
+        let mut unique: Vec<NostrEventWithEmbedding> = Vec::with_capacity(events.len());
+        for event in events {
+            match unique.iter_mut().find(|existing| existing.id == event.id) {
+                Some(existing) => *existing = event.clone(),
+                None => unique.push(event.clone()),
+            }
+        }
+
+        let batch = self.to_record_batch(&unique)?;
+
         let table = self
             .connection
             .open_table(&self.table_name)
             .execute()
             .await?;
         let batches = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), self.get_schema());
-        table.add(Box::new(batches)).execute().await?;
+
+        let mut merge_insert = table.merge_insert(&["id"]);
+        merge_insert
+            .when_matched_update_all(None)
+            .when_not_matched_insert_all();
+        merge_insert.execute(Box::new(batches)).await?;
 
         Ok(())
     }
 
-    pub async fn search_similar(
-        &self,
-        query_embedding: &[f32],
-        limit: usize,
-    ) -> Result<Vec<String>> {
+    async fn dedupe(&self) -> Result<usize> {
         let table = self
             .connection
             .open_table(&self.table_name)
@@ -169,101 +783,652 @@ impl LanceDBStore {
 
         let results = table
             .query()
-            .nearest_to(query_embedding)?
-            .limit(limit)
+            .select(Select::columns(&["id"]))
             .execute()
             .await?;
-
-        let mut event_ids = Vec::new();
         let batches = results.try_collect::<Vec<_>>().await?;
 
-        for batch in batches {
-            if let Some(id_column) = batch.column_by_name("id")
-                && let Some(string_array) = id_column.as_any().downcast_ref::<StringArray>()
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for batch in &batches {
+            if let Some(ids) = batch
+                .column_by_name("id")
+                .and_then(|column| column.as_any().downcast_ref::<StringArray>())
             {
-                for i in 0..string_array.len() {
-                    let id = string_array.value(i).to_string();
-                    event_ids.push(id);
+                for i in 0..ids.len() {
+                    *counts.entry(ids.value(i).to_string()).or_default() += 1;
                 }
             }
         }
 
-        Ok(event_ids)
+        let duplicated: Vec<String> = counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(id, _)| id)
+            .collect();
+
+        let mut removed = 0;
+        for chunk in duplicated.chunks(DELETE_BATCH_SIZE) {
+            let predicate = format!(
+                "id IN ({})",
+                chunk
+                    .iter()
+                    .map(|id| format!("'{}'", escape_sql_string(id)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+
+            let results = table.query().only_if(&predicate).execute().await?;
+            let batches = results.try_collect::<Vec<_>>().await?;
+
+            // Keep the first row seen for each id as a single-row batch
+            let mut seen = HashSet::new();
+            let mut kept: Vec<RecordBatch> = Vec::new();
+            let mut total = 0;
+            for batch in &batches {
+                let Some(ids) = batch
+                    .column_by_name("id")
+                    .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+                else {
+                    continue;
+                };
+                for i in 0..ids.len() {
+                    total += 1;
+                    if seen.insert(ids.value(i).to_string()) {
+                        kept.push(batch.slice(i, 1));
+                    }
+                }
+            }
+
+            let Some(schema) = kept.first().map(|batch| batch.schema()) else {
+                continue;
+            };
+
+            table.delete(&predicate).await?;
+            let kept_rows = kept.len();
+            let reader = RecordBatchIterator::new(kept.into_iter().map(Ok), schema);
+            table.add(Box::new(reader)).execute().await?;
+
+            removed += total - kept_rows;
+        }
+
+        Ok(removed)
     }
 
-    pub async fn search_similar_with_filters(
+    async fn search_similar_with_embeddings(
         &self,
         query_embedding: &[f32],
         limit: usize,
-        author: Option<&str>,
-        kind: Option<i32>,
-        min_created_at: Option<i64>,
-        max_created_at: Option<i64>,
-    ) -> Result<Vec<String>> {
-        let table = self
-            .connection
-            .open_table(&self.table_name)
-            .execute()
-            .await?;
+        filters: &SearchFilters,
+        column: VectorColumn,
+    ) -> Result<Vec<SearchHit>> {
+        let table = self.open_table_at(filters.version).await?;
 
         let mut vector_query = table
             .query()
             .nearest_to(query_embedding)?
-            .column("content_embedding")
+            .column(column.name())
+            .distance_type(distance_type(self.index_config.distance))
             .limit(limit);
 
-        let mut filter_clauses = Vec::new();
+        if let Some(filter_condition) = filters.scoped_to_model(&self.model_id)?.to_sql() {
+            vector_query = vector_query.only_if(&filter_condition);
+        }
+
+        let results = vector_query.execute().await?;
+        let batches = results.try_collect::<Vec<_>>().await?;
+
+        Ok(hits_from_batches(&batches))
+    }
 
-        if let Some(author) = author {
-            filter_clauses.push(format!("pubkey = '{}'", author));
+    async fn search_full_text(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        let table = self.open_table_at(filters.version).await?;
+
+        let mut text_query = table
+            .query()
+            .full_text_search(FullTextSearchQuery::new(query.to_string()))
+            .limit(limit);
+
+        if let Some(filter_condition) = filters.scoped_to_model(&self.model_id)?.to_sql() {
+            text_query = text_query.only_if(&filter_condition);
         }
 
-        if let Some(kind) = kind {
-            filter_clauses.push(format!("kind = {}", kind));
+        let results = text_query.execute().await?;
+        let batches = results.try_collect::<Vec<_>>().await?;
+
+        Ok(hits_from_batches(&batches))
+    }
+
+    async fn get_events(&self, event_ids: &[String]) -> Result<Vec<SearchHit>> {
+        let table = self.open_table_at(None).await?;
+
+        let mut hits = Vec::new();
+        for chunk in event_ids.chunks(DELETE_BATCH_SIZE) {
+            let predicate = format!(
+                "parent_id IN ({})",
+                chunk
+                    .iter()
+                    .map(|id| format!("'{}'", escape_sql_string(id)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let results = table.query().only_if(&predicate).execute().await?;
+            let batches = results.try_collect::<Vec<_>>().await?;
+            hits.extend(hits_from_batches(&batches));
         }
+        Ok(hits)
+    }
 
-        if let Some(min_created) = min_created_at {
-            filter_clauses.push(format!("created_at >= {}", min_created));
+    async fn get_rows(&self, event_ids: &[String]) -> Result<Vec<ExportedRow>> {
+        let table = self.open_table_at(None).await?;
+
+        let mut rows: HashMap<String, ExportedRow> = HashMap::new();
+        for chunk in event_ids.chunks(DELETE_BATCH_SIZE) {
+            let predicate = format!(
+                "parent_id IN ({})",
+                chunk
+                    .iter()
+                    .map(|id| format!("'{}'", escape_sql_string(id)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let results = table
+                .query()
+                .select(Select::columns(&[
+                    "id",
+                    "parent_id",
+                    "pubkey",
+                    "kind",
+                    "created_at",
+                    "tags",
+                    "model_id",
+                    "address",
+                    "content",
+                ]))
+                .only_if(&predicate)
+                .execute()
+                .await?;
+            for batch in results.try_collect::<Vec<_>>().await? {
+                for mut row in rows_from_batch(&batch) {
+                    row.id = row.parent_id.clone();
+                    rows.entry(row.parent_id.clone()).or_insert(row);
+                }
+            }
         }
+        Ok(rows.into_values().collect())
+    }
 
-        if let Some(max_created) = max_created_at {
-            filter_clauses.push(format!("created_at <= {}", max_created));
+    async fn count_tag_values(&self, name: &str) -> Result<HashMap<String, u64>> {
+        let table = self.open_table_at(None).await?;
+        let mut batches = table
+            .query()
+            .only_if("id = parent_id")
+            .select(Select::columns(&["tag_values"]))
+            .execute()
+            .await?;
+
+        let prefix = format!("{}:", name);
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        while let Some(batch) = batches.try_next().await? {
+            let Some(lists) = batch
+                .column_by_name("tag_values")
+                .and_then(|column| column.as_any().downcast_ref::<ListArray>())
+            else {
+                continue;
+            };
+            for i in 0..lists.len() {
+                if lists.is_null(i) {
+                    continue;
+                }
+                let values = lists.value(i);
+                let Some(values) = values.as_any().downcast_ref::<StringArray>() else {
+                    continue;
+                };
+                for j in 0..values.len() {
+                    if let Some(value) = values.value(j).strip_prefix(&prefix) {
+                        *counts.entry(value.to_lowercase()).or_default() += 1;
+                    }
+                }
+            }
         }
+        Ok(counts)
+    }
 
-        if !filter_clauses.is_empty() {
-            let filter_condition = filter_clauses.join(" AND ");
-            vector_query = vector_query.only_if(&filter_condition);
+    async fn count_events(&self) -> Result<usize> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+        Ok(table.count_rows(None).await?)
+    }
+
+    async fn count_events_for_model(&self, model_id: &str) -> Result<usize> {
+        let table = self.open_table_at(None).await?;
+        Ok(table
+            .count_rows(Some(format!(
+                "model_id = '{}'",
+                escape_sql_string(model_id)
+            )))
+            .await?)
+    }
+
+    async fn delete_older_than(&self, cutoff: i64) -> Result<usize> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+
+        let predicate = format!("created_at < {}", cutoff);
+        let matching = table.count_rows(Some(predicate.clone())).await?;
+        if matching > 0 {
+            table.delete(&predicate).await?;
         }
+        Ok(matching)
+    }
 
-        let results = vector_query.execute().await?;
+    async fn delete_events_by_author(&self, event_ids: &[String], pubkey: &str) -> Result<usize> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+
+        let mut removed = 0;
+        for chunk in event_ids.chunks(DELETE_BATCH_SIZE) {
+            let ids = chunk
+                .iter()
+                .map(|id| format!("'{}'", escape_sql_string(id)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let predicate = format!(
+                "pubkey = '{}' AND parent_id IN ({})",
+                escape_sql_string(pubkey),
+                ids
+            );
+            let matching = table.count_rows(Some(predicate.clone())).await?;
+            if matching > 0 {
+                table.delete(&predicate).await?;
+                removed += matching;
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn replace_address(
+        &self,
+        address: &str,
+        parent_id: &str,
+        created_at: i64,
+    ) -> Result<bool> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+
+        let address = escape_sql_string(address);
+        let parent_id = escape_sql_string(parent_id);
+        let newer = format!(
+            "address = '{address}' AND (created_at > {created_at} OR (created_at = {created_at} AND parent_id < '{parent_id}'))"
+        );
+        if table.count_rows(Some(newer)).await? > 0 {
+            return Ok(false);
+        }
+
+        let older = format!("address = '{address}' AND parent_id != '{parent_id}'");
+        if table.count_rows(Some(older.clone())).await? > 0 {
+            table.delete(&older).await?;
+        }
+        Ok(true)
+    }
+
+    async fn prune_per_author(&self, max_per_author: usize) -> Result<usize> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
 
-        let mut event_ids = Vec::new();
+        let results = table
+            .query()
+            .select(Select::columns(&["parent_id", "pubkey", "created_at"]))
+            .execute()
+            .await?;
         let batches = results.try_collect::<Vec<_>>().await?;
 
+        // Chunks of one event share its parent_id and count once
+        let mut by_author: HashMap<String, HashMap<String, i64>> = HashMap::new();
         for batch in batches {
-            if let Some(id_column) = batch.column_by_name("id")
-                && let Some(string_array) = id_column.as_any().downcast_ref::<StringArray>()
+            let parent_ids = batch
+                .column_by_name("parent_id")
+                .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+            let pubkeys = batch
+                .column_by_name("pubkey")
+                .and_then(|column| column.as_any().downcast_ref::<StringArray>());
+            let created_ats = batch
+                .column_by_name("created_at")
+                .and_then(|column| column.as_any().downcast_ref::<Int64Array>());
+
+            if let (Some(parent_ids), Some(pubkeys), Some(created_ats)) =
+                (parent_ids, pubkeys, created_ats)
             {
-                for i in 0..string_array.len() {
-                    let id = string_array.value(i).to_string();
-                    event_ids.push(id);
+                for i in 0..batch.num_rows() {
+                    by_author
+                        .entry(pubkeys.value(i).to_string())
+                        .or_default()
+                        .insert(parent_ids.value(i).to_string(), created_ats.value(i));
                 }
             }
         }
 
-        Ok(event_ids)
+        let mut expired_ids = Vec::new();
+        for events in by_author.into_values() {
+            if events.len() > max_per_author {
+                let mut events: Vec<(i64, String)> = events
+                    .into_iter()
+                    .map(|(parent_id, created_at)| (created_at, parent_id))
+                    .collect();
+                events.sort_by(|a, b| b.0.cmp(&a.0));
+                expired_ids.extend(events.drain(max_per_author..).map(|(_, id)| id));
+            }
+        }
+
+        for chunk in expired_ids.chunks(DELETE_BATCH_SIZE) {
+            let ids = chunk
+                .iter()
+                .map(|id| format!("'{}'", escape_sql_string(id)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            table.delete(&format!("parent_id IN ({})", ids)).await?;
+        }
+
+        Ok(expired_ids.len())
+    }
+
+    async fn create_index(&self, column: VectorColumn) -> Result<()> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+        table
+            .create_index(&[column.name()], self.vector_index())
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    async fn optimize(&self, prune_older_than: chrono::Duration) -> Result<()> {
+        let table = self
+            .connection
+            .open_table(&self.table_name)
+            .execute()
+            .await?;
+
+        table
+            .optimize(OptimizeAction::Compact {
+                options: CompactionOptions::default(),
+                remap_options: None,
+            })
+            .await?;
+        table
+            .optimize(OptimizeAction::Prune {
+                older_than: Some(prune_older_than),
+                delete_unverified: Some(false),
+                error_if_tagged_old_versions: Some(false),
+            })
+            .await?;
+        table
+            .optimize(OptimizeAction::Index(OptimizeOptions::default()))
+            .await?;
+
+        Ok(())
     }
 
-    pub async fn create_index(&self) -> Result<()> {
+    async fn create_fts_index(&self) -> Result<()> {
         let table = self
             .connection
             .open_table(&self.table_name)
             .execute()
             .await?;
         table
-            .create_index(&["content_embedding"], lancedb::index::Index::Auto)
+            .create_index(&["content"], Index::FTS(FtsIndexBuilder::default()))
+            .replace(true)
             .execute()
             .await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relevance_decreases_with_distance() {
+        let exact = SearchResult::new("a".to_string(), 0.0);
+        let near = SearchResult::new("b".to_string(), 0.5);
+        let far = SearchResult::new("c".to_string(), 4.0);
+
+        assert_eq!(exact.relevance, 1.0);
+        assert!(near.relevance > far.relevance);
+        assert!(far.relevance > 0.0);
+    }
+
+    #[test]
+    fn test_rows_from_batch_reads_selected_columns() {
+        let string_field = |name: &str, nullable: bool| Field::new(name, DataType::Utf8, nullable);
+        let schema = Arc::new(Schema::new(vec![
+            string_field("id", false),
+            string_field("parent_id", false),
+            string_field("pubkey", false),
+            Field::new("kind", DataType::Int64, false),
+            Field::new("created_at", DataType::Int64, false),
+            string_field("tags", false),
+            string_field("model_id", false),
+            string_field("address", true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["a#0"])),
+                Arc::new(StringArray::from(vec!["a"])),
+                Arc::new(StringArray::from(vec!["pubkey"])),
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(Int64Array::from(vec![42])),
+                Arc::new(StringArray::from(vec![r#"[["t","rust"]]"#])),
+                Arc::new(StringArray::from(vec!["default"])),
+                Arc::new(StringArray::from(vec![None::<String>])),
+            ],
+        )
+        .unwrap();
+
+        let rows = rows_from_batch(&batch, "default");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].parent_id, "a");
+        assert_eq!(
+            rows[0].tags,
+            vec![vec!["t".to_string(), "rust".to_string()]]
+        );
+        assert_eq!(rows[0].address, None);
+        assert_eq!(rows[0].content, None);
+        assert_eq!(rows[0].content_embedding, None);
+    }
+
+    #[test]
+    fn test_rows_from_batch_fills_in_unmigrated_columns() {
+        let string_field = |name: &str| Field::new(name, DataType::Utf8, false);
+        let schema = Arc::new(Schema::new(vec![
+            string_field("id"),
+            string_field("pubkey"),
+            Field::new("kind", DataType::Int64, false),
+            Field::new("created_at", DataType::Int64, false),
+            string_field("tags"),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(StringArray::from(vec!["a"])),
+                Arc::new(StringArray::from(vec!["pubkey"])),
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(Int64Array::from(vec![42])),
+                Arc::new(StringArray::from(vec!["[]"])),
+            ],
+        )
+        .unwrap();
+
+        let rows = rows_from_batch(&batch, "bge-small");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].parent_id, "a");
+        assert_eq!(rows[0].model_id, "bge-small");
+        assert_eq!(rows[0].address, None);
+    }
+
+    #[tokio::test]
+    async fn test_table_without_model_ids_is_searchable_after_migration() {
+        let db_path = std::env::temp_dir().join("seekstr_test_model_id_backfill");
+        let _ = std::fs::remove_dir_all(&db_path);
+        let db_path = db_path.to_str().unwrap().to_string();
+
+        // Schema of tables created before rows carried a model id
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("created_at", DataType::Int64, false),
+            Field::new("kind", DataType::Int64, false),
+            Field::new("tags", DataType::Utf8, false),
+            Field::new(
+                "content_embedding",
+                DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 2),
+                false,
+            ),
+        ]));
+        let embeddings = FixedSizeListArray::from_iter_primitive::<
+            arrow_array::types::Float32Type,
+            _,
+            _,
+        >(vec![Some(vec![Some(1.0), Some(0.0)])], 2);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a"])),
+                Arc::new(StringArray::from(vec!["pubkey"])),
+                Arc::new(Int64Array::from(vec![42])),
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["[]"])),
+                Arc::new(embeddings),
+            ],
+        )
+        .unwrap();
+        let connection = connect(&db_path).execute().await.unwrap();
+        connection
+            .create_table(
+                "events",
+                Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)),
+            )
+            .execute()
+            .await
+            .unwrap();
+
+        let store = LanceDBStore::new(&db_path, "events", 2, "nomic-embed-text")
+            .await
+            .unwrap();
+        let hits = store
+            .search_similar_with_embeddings(
+                &[1.0, 0.0],
+                10,
+                &SearchFilters::default(),
+                VectorColumn::Content,
+            )
+            .await
+            .unwrap();
+        let ids: Vec<String> = hits.into_iter().map(|hit| hit.id).collect();
+        assert_eq!(ids, vec!["a".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&db_path);
+    }
+
+    #[test]
+    fn test_empty_filters_produce_no_sql() {
+        assert_eq!(SearchFilters::default().to_sql(), None);
+    }
+
+    #[test]
+    fn test_multiple_authors_to_sql() {
+        let filters = SearchFilters {
+            authors: vec!["a".to_string(), "b".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(filters.to_sql().unwrap(), "pubkey IN ('a', 'b')");
+    }
+
+    #[test]
+    fn test_model_id_to_sql() {
+        let filters = SearchFilters {
+            model_id: Some("nomic-embed-text".to_string()),
+            kind: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            filters.to_sql().unwrap(),
+            "model_id = 'nomic-embed-text' AND kind = 1"
+        );
+    }
+
+    #[test]
+    fn test_geohash_prefix_to_sql() {
+        let filters = SearchFilters {
+            geohash: Some("U4PR".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(filters.to_sql().unwrap(), "geohash LIKE 'u4pr%'");
+    }
+
+    #[test]
+    fn test_exclude_duplicates_to_sql() {
+        let filters = SearchFilters {
+            exclude_duplicates: true,
+            kind: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            filters.to_sql().unwrap(),
+            "kind = 1 AND duplicate_of IS NULL"
+        );
+    }
+
+    #[test]
+    fn test_filters_to_sql() {
+        let mut tags = HashMap::new();
+        tags.insert(
+            "t".to_string(),
+            vec!["bitcoin".to_string(), "nostr".to_string()],
+        );
+        tags.insert("p".to_string(), vec!["abc".to_string()]);
+
+        let filters = SearchFilters {
+            authors: vec!["o'brien".to_string()],
+            kind: Some(1),
+            min_created_at: Some(10),
+            max_created_at: Some(20),
+            tags,
+            geohash: None,
+            exclude_duplicates: false,
+            version: Some(3),
+            model_id: None,
+        };
+
+        assert_eq!(
+            filters.to_sql().unwrap(),
+            "pubkey = 'o''brien' AND kind = 1 AND created_at >= 10 AND created_at <= 20 \
+             AND array_has_any(tag_values, ['p:abc']) \
+             AND array_has_any(tag_values, ['t:bitcoin', 't:nostr'])"
+        );
+    }
+}
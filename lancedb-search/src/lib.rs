@@ -1,54 +1,630 @@
+use anyhow::Result;
+use seekstr_core::de::{
+    StringOrList, deserialize_optional_from_str, deserialize_optional_string_list,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
+pub mod cache;
+pub mod chat;
+pub mod chunking;
+pub mod clustering;
 pub mod collect;
+pub mod collect_checkpoint;
+pub mod config;
+pub mod dead_letter;
+pub mod dedup;
 pub mod embedding_service;
 pub mod embeddings;
+pub mod error;
+pub mod etag;
+pub mod evaluation;
 pub mod event_queue;
+pub mod event_wal;
+pub mod follow_graph;
+pub mod health;
+pub mod image_embeddings;
+pub mod import;
 pub mod initialize;
 pub mod lancedb_store;
+pub mod list_publish;
+pub mod live;
+pub mod maintenance;
+pub mod media_descriptions;
+pub mod metrics;
+pub mod migration;
 pub mod nostr;
-pub mod url_extractor;
+#[cfg(feature = "pgvector")]
+pub mod pgvector_store;
+pub mod query_expansion;
+pub mod query_stats;
+pub mod ranking;
+pub mod reindex;
+pub mod relay_search;
+pub mod retention;
+pub mod retry;
+pub mod saved_search;
+pub mod spam;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+pub mod suggest;
+pub mod summarizer;
+pub mod thread;
+pub mod tokens;
+pub mod tombstones;
+pub mod vector_store;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct EventSearchRequest {
     pub language: Option<String>,
+    /// Author public key, hex or npub
     pub author: Option<String>,
-    #[serde(deserialize_with = "deserialize_optional_usize_from_string")]
+    /// Additional author public keys (hex or npub). In query strings these
+    /// are passed comma separated.
+    #[serde(default, deserialize_with = "deserialize_optional_string_list")]
+    pub authors: Option<Vec<String>>,
+    /// Maximum number of results to return, capped by the server's
+    /// configured `max_results`
+    #[serde(
+        default,
+        alias = "max_results",
+        deserialize_with = "deserialize_optional_from_str"
+    )]
     pub limit: Option<usize>,
     pub event_kinds: Option<Vec<u16>>,
     pub search: Option<String>,
+    /// Only return events created at or after this unix timestamp
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub since: Option<i64>,
+    /// Only return events created at or before this unix timestamp
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub until: Option<i64>,
+    /// Tag filters keyed by single-letter tag name (`t`, `p`, `e`, ...).
+    /// In query strings these are passed NIP-01 style, e.g. `#t=bitcoin,nostr`.
+    #[serde(default, deserialize_with = "deserialize_optional_tag_filters")]
+    #[param(ignore)]
+    pub tags: Option<HashMap<String, Vec<String>>>,
+    /// Only return events whose `g` tag lies inside this geohash cell,
+    /// e.g. `u4pr`; shorter prefixes cover larger areas
+    pub geohash: Option<String>,
+    /// Topics to filter out. The text is embedded and results too similar
+    /// to it are dropped.
+    pub exclude: Option<String>,
+    /// Similarity to the exclusion text above which a result is dropped
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub exclude_threshold: Option<f32>,
+    /// Minimum cosine similarity for a hit to be returned. Defaults to the
+    /// server's configured threshold.
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub min_score: Option<f32>,
+    /// Result diversification between 0.0 (pure relevance) and 1.0 (maximum
+    /// diversity), applied with maximal marginal relevance re-ranking
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub diversity: Option<f32>,
+    /// How to order results; defaults to pure relevance
+    pub ranking: Option<RankingMode>,
+    /// Overrides the server's recency half-life for `ranking=recency`
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub half_life_hours: Option<f64>,
+    /// Rewrite the query with the configured chat model before embedding
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub expand_query: Option<bool>,
+    /// Which vector space to search; defaults to the content embeddings
+    pub vector: Option<VectorSpace>,
+    /// `hybrid` combines keyword matches on stored content with the vector
+    /// search; defaults to pure semantic search
+    pub mode: Option<SearchMode>,
+    /// Search a pinned table version instead of the latest data
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub version: Option<u64>,
+    /// Public key (hex or npub) of the user searching. Results from authors
+    /// in their kind-3 contact list, and from authors those follow, rank
+    /// higher.
+    pub viewer_pubkey: Option<String>,
+    /// Also return the root and direct parent of results that are replies
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub expand_thread: Option<bool>,
+    /// Also return events marked as near-duplicates of earlier ones
+    #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+    pub include_duplicates: Option<bool>,
+    /// Attributes returned per hit in `hits`, comma separated: `id`,
+    /// `score` and/or `event`. Without it only `event_ids` are returned.
+    #[serde(default, deserialize_with = "deserialize_optional_result_fields")]
+    #[param(value_type = Option<String>)]
+    pub fields: Option<Vec<ResultField>>,
 }
 
-fn deserialize_optional_usize_from_string<'de, D>(
+/// Body of `POST /search/similar`: events and/or texts to find more of.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct SimilarEventsRequest {
+    /// Ids of stored events used as seeds
+    #[serde(default)]
+    pub event_ids: Vec<String>,
+    /// Free-text seeds, embedded like search queries
+    #[serde(default)]
+    pub texts: Vec<String>,
+    /// How the seeds are combined (default `average`)
+    #[serde(default)]
+    pub fusion: Option<SeedFusion>,
+    /// Maximum number of results, capped by the server's `max_results`
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedFusion {
+    /// Search once with the centroid of the seed vectors; finds what the
+    /// seeds have in common
+    #[default]
+    Average,
+    /// Search with each seed and merge the rankings by reciprocal rank
+    /// fusion; keeps the neighborhood of every seed
+    Rrf,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Vector similarity only
+    #[default]
+    Semantic,
+    /// Vector similarity fused with full-text matches on stored content
+    Hybrid,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorSpace {
+    /// Embeddings of the event content
+    #[default]
+    Content,
+    /// Embeddings of the scribe summary or media description
+    Summary,
+    /// Both spaces, scored by the mean similarity across them
+    Fusion,
+    /// CLIP embeddings of attached `imeta` images, matched against a CLIP
+    /// text encoding of the query. Requires image embeddings to be enabled.
+    Image,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RankingMode {
+    /// Order by cosine similarity to the query
+    #[default]
+    Relevance,
+    /// Blend similarity with an exponential decay on `created_at`
+    Recency,
+}
+
+/// Attribute of a search hit selectable with `fields`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultField {
+    Id,
+    /// Final ranking score, higher is more relevant
+    Score,
+    /// The stored event
+    Event,
+}
+
+fn deserialize_optional_result_fields<'de, D>(
     deserializer: D,
-) -> Result<Option<usize>, D::Error>
+) -> Result<Option<Vec<ResultField>>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    use serde::Deserialize;
+    let Some(names) = deserialize_optional_string_list(deserializer)? else {
+        return Ok(None);
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            serde_json::from_value(serde_json::Value::String(name.to_lowercase())).map_err(|_| {
+                serde::de::Error::custom(format!(
+                    "unknown field '{}', expected id, score or event",
+                    name
+                ))
+            })
+        })
+        .collect::<Result<Vec<ResultField>, D::Error>>()
+        .map(Some)
+}
 
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum StringOrUsize {
-        String(String),
-        Usize(usize),
-    }
+fn deserialize_optional_tag_filters<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, Vec<String>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let Some(raw) = Option::<HashMap<String, StringOrList>>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
 
-    match Option::<StringOrUsize>::deserialize(deserializer)? {
-        Some(StringOrUsize::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
-        Some(StringOrUsize::Usize(u)) => Ok(Some(u)),
-        None => Ok(None),
-    }
+    let tags = raw
+        .into_iter()
+        .map(|(name, values)| (name.trim_start_matches('#').to_string(), values.into_vec()))
+        .collect();
+
+    Ok(Some(tags))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct EventSearchResponse {
     pub event_ids: Vec<String>,
     pub total_found: usize,
+    /// Stored content of the returned events keyed by event ID, when the
+    /// server is configured to store content
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub snippets: HashMap<String, String>,
+    /// Final ranking score of the returned events keyed by event ID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub scores: HashMap<String, f32>,
+    /// The query couldn't be embedded, so the results are keyword matches
+    /// on stored content rather than semantic ones
+    #[serde(default)]
+    pub degraded: bool,
+}
+
+/// A search result with the attributes selected by `fields`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ResultHit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<StoredEvent>,
+}
+
+/// An event as kept in the index. Signatures aren't stored.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StoredEvent {
+    pub id: String,
+    pub pubkey: String,
+    pub created_at: i64,
+    pub kind: i64,
+    pub tags: Vec<Vec<String>>,
+    /// Absent when content storage is disabled or the event was split into
+    /// chunks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// A kind-0 profile matched by `/search/profiles`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProfileMatch {
+    pub pubkey: String,
+    /// Similarity to the query in `(0, 1]`, higher is more relevant
+    pub score: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub about: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nip05: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
 }
 
 impl EventSearchRequest {
+    /// Parses a request from flat query parameters, collecting `#<tag>`
+    /// keys into the `tags` filter map.
+    pub fn from_query(mut params: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if let Some(object) = params.as_object_mut() {
+            let tag_keys: Vec<String> = object
+                .keys()
+                .filter(|key| key.starts_with('#'))
+                .cloned()
+                .collect();
+
+            if !tag_keys.is_empty() {
+                let mut tags = serde_json::Map::new();
+                for key in tag_keys {
+                    if let Some(value) = object.remove(&key) {
+                        tags.insert(key, value);
+                    }
+                }
+                object.insert("tags".to_string(), serde_json::Value::Object(tags));
+            }
+        }
+
+        serde_json::from_value(params)
+    }
+
     pub fn get_search_query(&self) -> Option<&str> {
         self.search.as_deref()
     }
+
+    /// Collects `author` and `authors` into a deduplicated list of hex
+    /// public keys, accepting both hex and npub input.
+    pub fn resolved_authors(&self) -> Result<Vec<String>> {
+        let mut authors = Vec::new();
+        for author in self.author.iter().chain(self.authors.iter().flatten()) {
+            let author = nostr::normalize_pubkey(author)?;
+            if !authors.contains(&author) {
+                authors.push(author);
+            }
+        }
+        Ok(authors)
+    }
+
+    /// A stable key identifying requests that produce the same results:
+    /// whitespace and case are normalized in the query and tag filters are
+    /// sorted.
+    pub fn cache_key(&self) -> String {
+        let mut normalized = self.clone();
+        // Projection happens after the search, on the cached response
+        normalized.fields = None;
+        normalized.search = self.search.as_deref().map(|query| {
+            query
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        });
+
+        let tags: Option<std::collections::BTreeMap<String, Vec<String>>> =
+            normalized.tags.take().map(|tags| {
+                tags.into_iter()
+                    .map(|(name, mut values)| {
+                        values.sort();
+                        (name, values)
+                    })
+                    .collect()
+            });
+
+        format!(
+            "{}|{}",
+            serde_json::to_string(&normalized).unwrap_or_default(),
+            serde_json::to_string(&tags).unwrap_or_default()
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let errors = self.field_errors();
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        anyhow::bail!("{}", messages.join("; "))
+    }
+
+    /// Every problem with the request's filters, keyed by the offending field.
+    pub fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        for (field, author) in self.author.iter().map(|author| ("author", author)).chain(
+            self.authors
+                .iter()
+                .flatten()
+                .map(|author| ("authors", author)),
+        ) {
+            if let Err(e) = nostr::normalize_pubkey(author) {
+                errors.push(FieldError::new(field, e.to_string()));
+            }
+        }
+
+        if let Some(viewer) = &self.viewer_pubkey
+            && let Err(e) = nostr::normalize_pubkey(viewer)
+        {
+            errors.push(FieldError::new("viewer_pubkey", e.to_string()));
+        }
+
+        if let Some(geohash) = &self.geohash
+            && !nostr::is_geohash(&geohash.to_lowercase())
+        {
+            errors.push(FieldError::new(
+                "geohash",
+                "must be a geohash prefix (digits and letters other than a, i, l and o)",
+            ));
+        }
+
+        if let Some(since) = self.since
+            && since < 0
+        {
+            errors.push(FieldError::new(
+                "since",
+                "must be a non-negative unix timestamp",
+            ));
+        }
+
+        if let Some(until) = self.until
+            && until < 0
+        {
+            errors.push(FieldError::new(
+                "until",
+                "must be a non-negative unix timestamp",
+            ));
+        }
+
+        if let (Some(since), Some(until)) = (self.since, self.until)
+            && since > until
+        {
+            errors.push(FieldError::new(
+                "since",
+                format!("({}) must not be after until ({})", since, until),
+            ));
+        }
+
+        if let Some(threshold) = self.exclude_threshold
+            && !(-1.0..=1.0).contains(&threshold)
+        {
+            errors.push(FieldError::new(
+                "exclude_threshold",
+                "must be between -1.0 and 1.0",
+            ));
+        }
+
+        if let Some(min_score) = self.min_score
+            && !(-1.0..=1.0).contains(&min_score)
+        {
+            errors.push(FieldError::new("min_score", "must be between -1.0 and 1.0"));
+        }
+
+        if let Some(diversity) = self.diversity
+            && !(0.0..=1.0).contains(&diversity)
+        {
+            errors.push(FieldError::new("diversity", "must be between 0.0 and 1.0"));
+        }
+
+        if let Some(half_life) = self.half_life_hours
+            && half_life <= 0.0
+        {
+            errors.push(FieldError::new("half_life_hours", "must be positive"));
+        }
+
+        if self.limit == Some(0) {
+            errors.push(FieldError::new("limit", "must be greater than zero"));
+        }
+
+        if let Some(tags) = &self.tags {
+            let mut names: Vec<&String> = tags.keys().collect();
+            names.sort();
+            for name in names {
+                let field = format!("#{}", name);
+                if name.chars().count() != 1 {
+                    errors.push(FieldError::new(
+                        field,
+                        "is not a single-letter tag name; only single-letter tags are indexed",
+                    ));
+                } else if tags[name].is_empty() {
+                    errors.push(FieldError::new(field, "has no values"));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// A validation problem tied to a single request field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_query_collects_tag_filters() {
+        let params = serde_json::json!({
+            "search": "fees",
+            "limit": "10",
+            "since": "1700000000",
+            "#t": "bitcoin, lightning",
+        });
+
+        let request = EventSearchRequest::from_query(params).unwrap();
+        assert_eq!(request.limit, Some(10));
+        assert_eq!(request.since, Some(1700000000));
+
+        let tags = request.tags.unwrap();
+        assert_eq!(
+            tags.get("t"),
+            Some(&vec!["bitcoin".to_string(), "lightning".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_resolved_authors_accepts_npub_and_hex() {
+        let hex = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaef7b0f7";
+        let npub = "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64thhkrms570aga";
+
+        let request = EventSearchRequest {
+            author: Some(npub.to_string()),
+            authors: Some(vec![hex.to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(request.resolved_authors().unwrap(), vec![hex.to_string()]);
+
+        let invalid = EventSearchRequest {
+            author: Some("not-a-key".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_field_errors_name_each_invalid_filter() {
+        let request = EventSearchRequest {
+            author: Some("not-a-key".to_string()),
+            min_score: Some(2.0),
+            limit: Some(0),
+            ..Default::default()
+        };
+
+        let fields: Vec<String> = request
+            .field_errors()
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(fields, vec!["author", "min_score", "limit"]);
+    }
+
+    #[test]
+    fn test_cache_key_normalizes_query() {
+        let a = EventSearchRequest {
+            search: Some("  Bitcoin   Fees ".to_string()),
+            ..Default::default()
+        };
+        let b = EventSearchRequest {
+            search: Some("bitcoin fees".to_string()),
+            ..Default::default()
+        };
+        let c = EventSearchRequest {
+            search: Some("bitcoin fees".to_string()),
+            limit: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(a.cache_key(), b.cache_key());
+        assert_ne!(b.cache_key(), c.cache_key());
+    }
+
+    #[test]
+    fn test_from_query_parses_result_fields() {
+        let params = serde_json::json!({ "search": "fees", "fields": "id, Score" });
+        let request = EventSearchRequest::from_query(params).unwrap();
+        assert_eq!(
+            request.fields,
+            Some(vec![ResultField::Id, ResultField::Score])
+        );
+
+        let params = serde_json::json!({ "search": "fees", "fields": "id,sig" });
+        assert!(EventSearchRequest::from_query(params).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_inverted_time_range() {
+        let request = EventSearchRequest {
+            since: Some(200),
+            until: Some(100),
+            ..Default::default()
+        };
+        assert!(request.validate().is_err());
+    }
 }
@@ -0,0 +1,134 @@
+use crate::config::ListPublishConfig;
+use crate::nostr::NostrEvent;
+use anyhow::Result;
+use nostr_sdk::{Client, EventBuilder, EventId, Keys, Kind, Tag, TagKind};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// NIP-51 bookmark set: an addressable list of events identified by its
+/// `d` tag, so republishing under the same identifier replaces the list.
+const BOOKMARK_SET_KIND: u16 = 30003;
+
+/// Body of `POST /admin/lists/publish`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublishListRequest {
+    pub query: String,
+    /// Number of results to include; defaults to the search default
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// List title shown by clients; defaults to the query
+    #[serde(default)]
+    pub title: Option<String>,
+    /// `d` tag of the list; defaults to one derived from the query, so
+    /// publishing the same query again updates its list
+    #[serde(default)]
+    pub identifier: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublishListResponse {
+    /// The signed list event
+    pub event: NostrEvent,
+    /// Relays that accepted the event
+    pub relays: Vec<String>,
+}
+
+/// Signs search results as NIP-51 lists and publishes them.
+pub struct ListPublisher {
+    client: Client,
+    keys: Keys,
+}
+
+impl ListPublisher {
+    pub async fn new(config: &ListPublishConfig) -> Result<Self> {
+        let keys = Keys::parse(&config.nsec)?;
+        let client = Client::new(keys.clone());
+        for relay in &config.relays {
+            client.add_relay(relay.as_str()).await?;
+        }
+        client.connect().await;
+        Ok(Self { client, keys })
+    }
+
+    /// Publishes `event_ids`, in rank order, as the bookmark set
+    /// `identifier`. Fails when no relay accepts the event.
+    pub async fn publish(
+        &self,
+        identifier: &str,
+        title: &str,
+        event_ids: &[String],
+    ) -> Result<PublishListResponse> {
+        let event = EventBuilder::new(Kind::from(BOOKMARK_SET_KIND), "")
+            .tags(list_tags(identifier, title, event_ids)?)
+            .sign_with_keys(&self.keys)?;
+
+        let output = self.client.send_event(&event).await?;
+        if output.success.is_empty() {
+            anyhow::bail!(
+                "No relay accepted the list: {}",
+                output
+                    .failed
+                    .iter()
+                    .map(|(relay, error)| format!("{}: {}", relay, error))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            );
+        }
+
+        Ok(PublishListResponse {
+            event: NostrEvent::from(&event),
+            relays: output.success.iter().map(ToString::to_string).collect(),
+        })
+    }
+}
+
+fn list_tags(identifier: &str, title: &str, event_ids: &[String]) -> Result<Vec<Tag>> {
+    let mut tags = vec![
+        Tag::identifier(identifier),
+        Tag::custom(TagKind::Title, [title]),
+    ];
+    for id in event_ids {
+        tags.push(Tag::event(EventId::from_hex(id)?));
+    }
+    Ok(tags)
+}
+
+/// A `d` tag for the list of `query`'s results: its words lowercased and
+/// joined by dashes.
+pub fn list_identifier(query: &str) -> String {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!("search-{}", words.join("-"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_identifier_slugs_the_query() {
+        assert_eq!(
+            list_identifier("  Bitcoin, Lightning!"),
+            "search-bitcoin-lightning"
+        );
+    }
+
+    #[test]
+    fn test_list_tags_keep_rank_order() {
+        let ids = vec!["a".repeat(64), "b".repeat(64)];
+        let tags: Vec<Vec<String>> = list_tags("search-nostr", "nostr", &ids)
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.as_slice().to_vec())
+            .collect();
+
+        assert_eq!(tags[0], vec!["d", "search-nostr"]);
+        assert_eq!(tags[1], vec!["title", "nostr"]);
+        assert_eq!(tags[2], vec!["e".to_string(), ids[0].clone()]);
+        assert_eq!(tags[3], vec!["e".to_string(), ids[1].clone()]);
+        assert!(list_tags("d", "t", &["not-hex".to_string()]).is_err());
+    }
+}
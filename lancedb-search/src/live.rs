@@ -0,0 +1,229 @@
+use crate::embedding_service::EmbeddingSearchService;
+use crate::nostr::{NostrEvent, NostrEventWithEmbedding};
+use crate::saved_search::best_score;
+use axum::extract::ws::{Message, WebSocket};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use utoipa::ToSchema;
+
+/// An event as it was written to the index.
+pub struct IndexedEvent {
+    pub event: NostrEvent,
+    pub rows: Vec<NostrEventWithEmbedding>,
+}
+
+/// Fans newly indexed events out to live subscriptions. Subscribers that
+/// fall more than `capacity` events behind skip ahead.
+#[derive(Clone)]
+pub struct LiveFeed {
+    sender: broadcast::Sender<Arc<IndexedEvent>>,
+}
+
+impl LiveFeed {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publishes an event; a no-op while nobody is subscribed.
+    pub fn publish(&self, event: &NostrEvent, rows: &[NostrEventWithEmbedding]) {
+        if self.sender.receiver_count() == 0 || rows.is_empty() {
+            return;
+        }
+        let _ = self.sender.send(Arc::new(IndexedEvent {
+            event: event.clone(),
+            rows: rows.to_vec(),
+        }));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<IndexedEvent>> {
+        self.sender.subscribe()
+    }
+}
+
+/// Sent by the client to start or replace its subscription.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LiveQuery {
+    pub query: String,
+    /// Similarity a new event needs to be sent; defaults to the server's
+    /// `LIVE_MIN_SCORE`
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Only send events of these kinds
+    #[serde(default)]
+    pub kinds: Option<Vec<u16>>,
+}
+
+/// Messages sent to live subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveMessage {
+    /// The query is active; matches follow
+    Subscribed { query: String, min_score: f32 },
+    /// A newly indexed event matching the query
+    Match { score: f32, event: NostrEvent },
+    /// This many events were indexed while the connection was too slow to
+    /// keep up and were not checked
+    Lagged { skipped: u64 },
+    /// The last client message was rejected
+    Error { message: String },
+}
+
+struct Subscription {
+    embedding: Vec<f32>,
+    min_score: f32,
+    kinds: Option<Vec<u16>>,
+}
+
+impl Subscription {
+    fn score(&self, indexed: &IndexedEvent) -> Option<f32> {
+        if let Some(kinds) = &self.kinds
+            && !kinds.iter().any(|&kind| kind as i32 == indexed.event.kind)
+        {
+            return None;
+        }
+        best_score(&self.embedding, &indexed.rows).filter(|score| *score >= self.min_score)
+    }
+}
+
+/// Runs one WebSocket connection: waits for a [`LiveQuery`], then streams
+/// matching events until the client disconnects. A new query replaces the
+/// previous one.
+pub async fn serve_live_search(
+    mut socket: WebSocket,
+    service: Arc<EmbeddingSearchService>,
+    feed: LiveFeed,
+    default_min_score: f32,
+) {
+    let mut events = feed.subscribe();
+    let mut subscription: Option<Subscription> = None;
+
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    match subscribe(text.as_str(), &service, default_min_score).await {
+                        Ok((active, reply)) => {
+                            subscription = Some(active);
+                            Some(reply)
+                        }
+                        Err(message) => Some(LiveMessage::Error { message }),
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum
+                Some(Ok(_)) => None,
+            },
+            indexed = events.recv() => match indexed {
+                Ok(indexed) => subscription
+                    .as_ref()
+                    .and_then(|subscription| subscription.score(&indexed))
+                    .map(|score| LiveMessage::Match {
+                        score,
+                        event: indexed.event.clone(),
+                    }),
+                Err(RecvError::Lagged(skipped)) => Some(LiveMessage::Lagged { skipped }),
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        if let Some(reply) = reply {
+            let Ok(text) = serde_json::to_string(&reply) else {
+                continue;
+            };
+            if socket.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn subscribe(
+    text: &str,
+    service: &EmbeddingSearchService,
+    default_min_score: f32,
+) -> Result<(Subscription, LiveMessage), String> {
+    let query: LiveQuery =
+        serde_json::from_str(text).map_err(|e| format!("Invalid live query: {}", e))?;
+    let text = query.query.trim();
+    if text.is_empty() {
+        return Err("query must not be empty".to_string());
+    }
+    let min_score = query.min_score.unwrap_or(default_min_score);
+    if !(-1.0..=1.0).contains(&min_score) {
+        return Err("min_score must be between -1.0 and 1.0".to_string());
+    }
+
+    let embedding = service
+        .embed_text(text)
+        .await
+        .map_err(|e| format!("Failed to embed query: {}", e))?;
+    Ok((
+        Subscription {
+            embedding,
+            min_score,
+            kinds: query.kinds,
+        },
+        LiveMessage::Subscribed {
+            query: text.to_string(),
+            min_score,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed(kind: i32, embedding: Vec<f32>) -> IndexedEvent {
+        IndexedEvent {
+            event: NostrEvent {
+                id: "id".to_string(),
+                pubkey: "pubkey".to_string(),
+                created_at: 0,
+                kind,
+                tags: Vec::new(),
+                content: String::new(),
+                sig: String::new(),
+                summary: None,
+            },
+            rows: vec![NostrEventWithEmbedding::new(
+                "id".to_string(),
+                "pubkey".to_string(),
+                0,
+                kind,
+                Vec::new(),
+                embedding,
+            )],
+        }
+    }
+
+    #[test]
+    fn test_subscription_filters_by_score_and_kind() {
+        let subscription = Subscription {
+            embedding: vec![1.0, 0.0],
+            min_score: 0.8,
+            kinds: Some(vec![1]),
+        };
+
+        assert!(subscription.score(&indexed(1, vec![1.0, 0.1])).is_some());
+        assert!(subscription.score(&indexed(1, vec![0.0, 1.0])).is_none());
+        assert!(
+            subscription
+                .score(&indexed(30023, vec![1.0, 0.0]))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_publish_without_subscribers_is_a_no_op() {
+        let feed = LiveFeed::new(4);
+        let event = indexed(1, vec![1.0]);
+        feed.publish(&event.event, &event.rows);
+
+        let mut receiver = feed.subscribe();
+        feed.publish(&event.event, &event.rows);
+        assert_eq!(receiver.try_recv().unwrap().event.id, "id");
+    }
+}
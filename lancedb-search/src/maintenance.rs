@@ -0,0 +1,49 @@
+use crate::config::MaintenanceConfig;
+use crate::embedding_service::EmbeddingSearchService;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Periodically compacts the table, prunes old versions and re-optimizes
+/// the indexes so fragments from small inserts don't pile up.
+pub struct MaintenanceTask {
+    embedding_service: Arc<EmbeddingSearchService>,
+    config: MaintenanceConfig,
+}
+
+impl MaintenanceTask {
+    pub fn new(embedding_service: Arc<EmbeddingSearchService>, config: MaintenanceConfig) -> Self {
+        Self {
+            embedding_service,
+            config,
+        }
+    }
+
+    pub async fn start(self) {
+        println!(
+            "Maintenance task started (every {}s)",
+            self.config.interval_secs
+        );
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs));
+        // The first tick completes immediately; skip it so startup isn't
+        // slowed down by a compaction.
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let started = Instant::now();
+            match self
+                .embedding_service
+                .optimize(chrono::Duration::hours(self.config.prune_older_than_hours))
+                .await
+            {
+                Ok(()) => println!(
+                    "Table maintenance finished in {}ms",
+                    started.elapsed().as_millis()
+                ),
+                Err(e) => eprintln!("Table maintenance failed: {}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+/// Turns media URLs into searchable text with scribe: a transcript summary
+/// and any scene descriptions for audio and video, a description and any
+/// OCR text for images. Needs the
+/// `media-descriptions` feature.
+pub struct MediaDescriber {
+    #[cfg_attr(not(feature = "media-descriptions"), allow(dead_code))]
+    api_key: Option<String>,
+}
+
+impl MediaDescriber {
+    /// `api_key` is passed to scribe backends that need one (OpenAI).
+    pub fn new(api_key: Option<String>) -> Self {
+        Self { api_key }
+    }
+
+    #[cfg(feature = "media-descriptions")]
+    pub async fn describe(&self, url: &str) -> Result<String> {
+        use scribe::ProcessedContent;
+
+        let backend = scribe::create_backend_auto(url, self.api_key.clone(), None)?;
+        let result = scribe::process_single_url_direct(url, backend.as_ref()).await?;
+
+        let text = match result.content {
+            ProcessedContent::Transcript {
+                text,
+                summary,
+                scenes,
+                ..
+            } => [summary.unwrap_or(text)]
+                .into_iter()
+                .chain(scenes.into_iter().map(|scene| scene.description))
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            ProcessedContent::Description {
+                description,
+                tags,
+                ocr_text,
+            } => {
+                let tags = (!tags.is_empty()).then(|| tags.join(", "));
+                [Some(description), tags, ocr_text]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            }
+        };
+        Ok(text)
+    }
+
+    #[cfg(not(feature = "media-descriptions"))]
+    pub async fn describe(&self, _url: &str) -> Result<String> {
+        anyhow::bail!(
+            "Media descriptions require building lancedb-search with the `media-descriptions` feature"
+        )
+    }
+}
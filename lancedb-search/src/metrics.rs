@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Window over which `processing_rate` is averaged.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Ingest counters shared by `EventQueue` and `EventProcessor`.
+#[derive(Default)]
+pub struct QueueMetrics {
+    enqueued: AtomicU64,
+    rejected: AtomicU64,
+    processed: AtomicU64,
+    failed: AtomicU64,
+    quarantined: AtomicU64,
+    retries: AtomicU64,
+    /// When each unfinished event was accepted
+    in_flight: Mutex<HashMap<String, Instant>>,
+    /// Completion times within `RATE_WINDOW`
+    recent: Mutex<VecDeque<Instant>>,
+}
+
+/// Point-in-time view of the ingest pipeline.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueueSnapshot {
+    /// Events waiting in the queue
+    pub depth: usize,
+    /// Events accepted but not yet stored or dead-lettered
+    pub in_flight: usize,
+    /// Age of the oldest unfinished event
+    pub oldest_age_secs: f64,
+    /// Events finished per second over the last minute
+    pub processing_rate: f64,
+    pub enqueued_total: u64,
+    /// Events refused because the queue was full
+    pub rejected_total: u64,
+    pub processed_total: u64,
+    /// Events dead-lettered after exhausting their retries
+    pub failed_total: u64,
+    /// Events classified as spam and stored in the quarantine table
+    pub quarantined_total: u64,
+    pub retries_total: u64,
+}
+
+impl QueueMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_enqueued(&self, event_id: &str) {
+        self.enqueued.fetch_add(1, Ordering::Relaxed);
+        self.in_flight
+            .lock()
+            .unwrap()
+            .entry(event_id.to_string())
+            .or_insert_with(Instant::now);
+    }
+
+    pub fn record_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_processed(&self, event_id: &str) {
+        self.processed.fetch_add(1, Ordering::Relaxed);
+        self.finish(event_id);
+    }
+
+    pub fn record_failed(&self, event_id: &str) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        self.finish(event_id);
+    }
+
+    pub fn record_quarantined(&self, event_id: &str) {
+        self.quarantined.fetch_add(1, Ordering::Relaxed);
+        self.finish(event_id);
+    }
+
+    fn finish(&self, event_id: &str) {
+        self.in_flight.lock().unwrap().remove(event_id);
+
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap();
+        recent.push_back(now);
+        while recent
+            .front()
+            .is_some_and(|finished| now.duration_since(*finished) > RATE_WINDOW)
+        {
+            recent.pop_front();
+        }
+    }
+
+    pub fn snapshot(&self, depth: usize) -> QueueSnapshot {
+        let now = Instant::now();
+        let (in_flight, oldest_age_secs) = {
+            let in_flight = self.in_flight.lock().unwrap();
+            let oldest = in_flight
+                .values()
+                .map(|accepted| now.duration_since(*accepted))
+                .max()
+                .unwrap_or_default();
+            (in_flight.len(), oldest.as_secs_f64())
+        };
+        let recent = self
+            .recent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|finished| now.duration_since(**finished) <= RATE_WINDOW)
+            .count();
+
+        QueueSnapshot {
+            depth,
+            in_flight,
+            oldest_age_secs,
+            processing_rate: recent as f64 / RATE_WINDOW.as_secs_f64(),
+            enqueued_total: self.enqueued.load(Ordering::Relaxed),
+            rejected_total: self.rejected.load(Ordering::Relaxed),
+            processed_total: self.processed.load(Ordering::Relaxed),
+            failed_total: self.failed.load(Ordering::Relaxed),
+            quarantined_total: self.quarantined.load(Ordering::Relaxed),
+            retries_total: self.retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl QueueSnapshot {
+    /// Renders the snapshot in the Prometheus text exposition format.
+    pub fn to_prometheus(&self, dead_letters: usize) -> String {
+        let metrics: [(&str, &str, &str, f64); 11] = [
+            (
+                "event_queue_depth",
+                "gauge",
+                "Events waiting in the queue",
+                self.depth as f64,
+            ),
+            (
+                "event_queue_in_flight",
+                "gauge",
+                "Events accepted but not yet finished",
+                self.in_flight as f64,
+            ),
+            (
+                "event_queue_oldest_age_seconds",
+                "gauge",
+                "Age of the oldest unfinished event",
+                self.oldest_age_secs,
+            ),
+            (
+                "event_queue_processing_rate",
+                "gauge",
+                "Events finished per second over the last minute",
+                self.processing_rate,
+            ),
+            (
+                "event_queue_dead_letters",
+                "gauge",
+                "Events currently dead-lettered",
+                dead_letters as f64,
+            ),
+            (
+                "event_queue_enqueued_total",
+                "counter",
+                "Events accepted into the queue",
+                self.enqueued_total as f64,
+            ),
+            (
+                "event_queue_rejected_total",
+                "counter",
+                "Events refused because the queue was full",
+                self.rejected_total as f64,
+            ),
+            (
+                "event_queue_processed_total",
+                "counter",
+                "Events embedded and stored",
+                self.processed_total as f64,
+            ),
+            (
+                "event_queue_failed_total",
+                "counter",
+                "Events dead-lettered after exhausting retries",
+                self.failed_total as f64,
+            ),
+            (
+                "event_queue_quarantined_total",
+                "counter",
+                "Events classified as spam and quarantined",
+                self.quarantined_total as f64,
+            ),
+            (
+                "event_queue_retries_total",
+                "counter",
+                "Retried processing attempts",
+                self.retries_total as f64,
+            ),
+        ];
+
+        let mut output = String::new();
+        for (name, kind, help, value) in metrics {
+            output.push_str(&format!(
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"
+            ));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_tracks_in_flight_and_totals() {
+        let metrics = QueueMetrics::new();
+        metrics.record_enqueued("a");
+        metrics.record_enqueued("b");
+        metrics.record_rejected();
+        metrics.record_retry();
+        metrics.record_processed("a");
+
+        let snapshot = metrics.snapshot(1);
+        assert_eq!(snapshot.depth, 1);
+        assert_eq!(snapshot.in_flight, 1);
+        assert_eq!(snapshot.enqueued_total, 2);
+        assert_eq!(snapshot.rejected_total, 1);
+        assert_eq!(snapshot.processed_total, 1);
+        assert_eq!(snapshot.retries_total, 1);
+        assert!(snapshot.processing_rate > 0.0);
+
+        metrics.record_enqueued("c");
+        metrics.record_quarantined("c");
+        metrics.record_failed("b");
+        let snapshot = metrics.snapshot(0);
+        assert_eq!(snapshot.in_flight, 0);
+        assert_eq!(snapshot.failed_total, 1);
+        assert_eq!(snapshot.quarantined_total, 1);
+        assert_eq!(snapshot.oldest_age_secs, 0.0);
+
+        let text = snapshot.to_prometheus(1);
+        assert!(text.contains("event_queue_failed_total 1\n"));
+        assert!(text.contains("# TYPE event_queue_depth gauge\n"));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::EventSearchRequest;
+use crate::embedding_service::EmbeddingSearchService;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Body of `POST /admin/migration/compare`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CompareRequest {
+    pub query: String,
+    /// Results compared per model; defaults to the search default
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Where an event ranks for each model; ranks start at 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RankChange {
+    pub event_id: String,
+    /// Absent when the current model doesn't return the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_rank: Option<usize>,
+    /// Absent when the candidate model doesn't return the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_rank: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RankingComparison {
+    pub current_model: String,
+    pub candidate_model: String,
+    /// Rows written by each model so far, to judge how complete the
+    /// candidate's backfill is
+    pub current_rows: usize,
+    pub candidate_rows: usize,
+    /// Events in both result lists
+    pub overlap: usize,
+    /// `overlap` relative to the longer result list, 1.0 when both models
+    /// return the same events
+    pub overlap_ratio: f32,
+    /// Every returned event, in the current model's order followed by
+    /// events only the candidate returns
+    pub changes: Vec<RankChange>,
+}
+
+/// Runs `query` against the current and the candidate model and lines up
+/// their rankings.
+pub async fn compare_models(
+    current: &EmbeddingSearchService,
+    candidate: &EmbeddingSearchService,
+    query: &str,
+    limit: Option<usize>,
+) -> Result<RankingComparison> {
+    let request = EventSearchRequest {
+        limit,
+        search: Some(query.to_string()),
+        ..Default::default()
+    };
+    let (current_results, candidate_results, current_rows, candidate_rows) = tokio::join!(
+        current.semantic_search(&request),
+        candidate.semantic_search(&request),
+        current.count_events_for_model(current.model_id()),
+        candidate.count_events_for_model(candidate.model_id()),
+    );
+    let current_ids = current_results?.event_ids;
+    let candidate_ids = candidate_results?.event_ids;
+
+    let changes = rank_changes(&current_ids, &candidate_ids);
+    let overlap = changes
+        .iter()
+        .filter(|change| change.current_rank.is_some() && change.candidate_rank.is_some())
+        .count();
+    let longest = current_ids.len().max(candidate_ids.len());
+
+    Ok(RankingComparison {
+        current_model: current.model_id().to_string(),
+        candidate_model: candidate.model_id().to_string(),
+        current_rows: current_rows?,
+        candidate_rows: candidate_rows?,
+        overlap,
+        overlap_ratio: if longest == 0 {
+            1.0
+        } else {
+            overlap as f32 / longest as f32
+        },
+        changes,
+    })
+}
+
+fn rank_changes(current: &[String], candidate: &[String]) -> Vec<RankChange> {
+    let candidate_ranks: HashMap<&str, usize> = candidate
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (id.as_str(), index + 1))
+        .collect();
+
+    let mut changes: Vec<RankChange> = current
+        .iter()
+        .enumerate()
+        .map(|(index, id)| RankChange {
+            event_id: id.clone(),
+            current_rank: Some(index + 1),
+            candidate_rank: candidate_ranks.get(id.as_str()).copied(),
+        })
+        .collect();
+    changes.extend(
+        candidate
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| !current.contains(id))
+            .map(|(index, id)| RankChange {
+                event_id: id.clone(),
+                current_rank: None,
+                candidate_rank: Some(index + 1),
+            }),
+    );
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_changes_cover_both_lists() {
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        let changes = rank_changes(&ids(&["a", "b", "c"]), &ids(&["c", "a", "d"]));
+
+        let ranks: Vec<(&str, Option<usize>, Option<usize>)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.event_id.as_str(),
+                    change.current_rank,
+                    change.candidate_rank,
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranks,
+            vec![
+                ("a", Some(1), Some(2)),
+                ("b", Some(2), None),
+                ("c", Some(3), Some(1)),
+                ("d", None, Some(3)),
+            ]
+        );
+    }
+}
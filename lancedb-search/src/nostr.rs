@@ -1,15 +1,8 @@
+use anyhow::Result;
+use nostr_sdk::{JsonUtil, Metadata, PublicKey};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NostrEvent {
-    pub id: String,
-    pub pubkey: String,
-    pub created_at: i64,
-    pub kind: i32,
-    pub tags: Vec<Vec<String>>,
-    pub content: String,
-    pub sig: String,
-}
+pub use seekstr_core::nostr::{DELETION_KIND, NostrEvent};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NostrEventWithEmbedding {
@@ -18,7 +11,118 @@ pub struct NostrEventWithEmbedding {
     pub created_at: i64,
     pub kind: i32,
     pub tags: String,
+    pub tag_values: Vec<String>,
     pub content_embedding: Vec<f32>,
+    /// Embedding of the scribe summary; the content embedding when the
+    /// event has no summary.
+    pub summary_embedding: Vec<f32>,
+    /// Event content, only kept when content storage is enabled
+    pub content: Option<String>,
+    /// Event this row belongs to; differs from `id` for chunk rows
+    pub parent_id: String,
+    /// `kind:pubkey:d-tag` of replaceable and addressable events, whose
+    /// newest version replaces the stored one
+    pub address: Option<String>,
+    /// Most precise geohash of the event's `g` tags
+    pub geohash: Option<String>,
+    /// Earlier event by the same author this one nearly duplicates, when
+    /// duplicates are marked rather than skipped
+    pub duplicate_of: Option<String>,
+}
+
+/// Flattens single-letter tags into `name:value` strings so they can be
+/// filtered on in the store, e.g. `["t", "bitcoin"]` becomes `t:bitcoin`.
+pub fn indexed_tag_values(tags: &[Vec<String>]) -> Vec<String> {
+    tags.iter()
+        .filter(|tag| tag.len() >= 2 && tag[0].chars().count() == 1)
+        .map(|tag| format!("{}:{}", tag[0], tag[1]))
+        .collect()
+}
+
+/// Root and direct parent of a reply, read from its `e` tags: NIP-10
+/// `root`/`reply` markers when present, otherwise the deprecated
+/// positional form where the first tag is the root and the last the
+/// parent. Both are `None` for events that aren't replies.
+pub fn thread_references(tags: &[Vec<String>]) -> (Option<String>, Option<String>) {
+    let e_tags: Vec<&Vec<String>> = tags
+        .iter()
+        .filter(|tag| tag.len() >= 2 && tag[0] == "e")
+        .collect();
+    let marked = |marker: &str| {
+        e_tags
+            .iter()
+            .find(|tag| tag.get(3).map(String::as_str) == Some(marker))
+            .map(|tag| tag[1].clone())
+    };
+
+    let root = marked("root");
+    let reply = marked("reply");
+    if root.is_some() || reply.is_some() {
+        // A direct reply to the root only carries the root marker
+        let parent = reply.or_else(|| root.clone());
+        return (root.or_else(|| parent.clone()), parent);
+    }
+
+    let positional = |tag: Option<&&Vec<String>>| tag.map(|tag| tag[1].clone());
+    (positional(e_tags.first()), positional(e_tags.last()))
+}
+
+/// Characters of the geohash base32 alphabet.
+const GEOHASH_ALPHABET: &str = "0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Whether `value` is a (possibly partial) geohash.
+pub fn is_geohash(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| GEOHASH_ALPHABET.contains(c))
+}
+
+/// The longest valid geohash among `g` tags, lowercased. Events often carry
+/// the same location at several precisions; the most precise one matches
+/// every shorter prefix.
+pub fn event_geohash(tags: &[Vec<String>]) -> Option<String> {
+    tags.iter()
+        .filter(|tag| tag.len() >= 2 && tag[0] == "g")
+        .map(|tag| tag[1].trim().to_lowercase())
+        .filter(|value| is_geohash(value))
+        .max_by_key(|value| value.len())
+}
+
+/// Parses the metadata JSON of a kind-0 profile event.
+pub fn profile_metadata(content: &str) -> Option<Metadata> {
+    Metadata::from_json(content).ok()
+}
+
+/// Text embedded for a profile: its name followed by the `about` text.
+/// Profiles with neither are not indexed.
+pub fn profile_text(metadata: &Metadata) -> Option<String> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    let name = non_empty(&metadata.display_name).or_else(|| non_empty(&metadata.name));
+    let about = non_empty(&metadata.about);
+    match (name, about) {
+        (Some(name), Some(about)) => Some(format!("{}: {}", name, about)),
+        (Some(text), None) | (None, Some(text)) => Some(text),
+        (None, None) => None,
+    }
+}
+
+/// Cuts `content` to at most `max_bytes` bytes without splitting a
+/// character, appending an ellipsis when anything was removed.
+pub fn truncate_to_bytes(content: &str, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…", &content[..end])
 }
 
 impl NostrEventWithEmbedding {
@@ -31,15 +135,44 @@ impl NostrEventWithEmbedding {
         content_embedding: Vec<f32>,
     ) -> Self {
         Self {
+            parent_id: id.clone(),
             id,
             pubkey,
             created_at,
             kind,
+            tag_values: indexed_tag_values(&tags),
+            geohash: event_geohash(&tags),
             tags: serde_json::to_string(&tags).unwrap_or_default(),
+            summary_embedding: content_embedding.clone(),
             content_embedding,
+            content: None,
+            address: None,
+            duplicate_of: None,
         }
     }
 
+    pub fn with_content(mut self, content: String) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    pub fn with_address(mut self, address: Option<String>) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn with_summary_embedding(mut self, summary_embedding: Vec<f32>) -> Self {
+        self.summary_embedding = summary_embedding;
+        self
+    }
+
+    /// Turns the row into chunk `index` of its event, stored under
+    /// `<event id>:<index>`.
+    pub fn with_chunk(mut self, index: usize) -> Self {
+        self.id = format!("{}:{}", self.parent_id, index);
+        self
+    }
+
     pub fn get_tags(&self) -> Result<Vec<Vec<String>>, serde_json::Error> {
         serde_json::from_str(&self.tags)
     }
@@ -48,12 +181,99 @@ impl NostrEventWithEmbedding {
 impl NostrEventWithEmbedding {
     pub fn from_event_with_embedding(event: NostrEvent, embedding: Vec<f32>) -> Self {
         Self {
+            parent_id: event.id.clone(),
             id: event.id,
             pubkey: event.pubkey,
             created_at: event.created_at,
             kind: event.kind,
             tags: serde_json::to_string(&event.tags).unwrap_or_default(),
+            tag_values: indexed_tag_values(&event.tags),
+            geohash: event_geohash(&event.tags),
+            summary_embedding: embedding.clone(),
             content_embedding: embedding,
+            content: None,
+            address: event.address(),
+            duplicate_of: None,
         }
     }
 }
+
+/// Parses a public key given as hex, `npub1...` or `nostr:npub1...` and
+/// returns it as lowercase hex, the form stored in the index.
+pub fn normalize_pubkey(value: &str) -> Result<String> {
+    let value = value.trim();
+    let public_key = PublicKey::parse(value)
+        .map_err(|e| anyhow::anyhow!("Invalid public key '{}': {}", value, e))?;
+    Ok(public_key.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_references_prefer_markers() {
+        let tag = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        let marked = vec![
+            tag(&["e", "root-id", "", "root"]),
+            tag(&["e", "parent-id", "", "reply"]),
+            tag(&["e", "mention-id", "", "mention"]),
+        ];
+        assert_eq!(
+            thread_references(&marked),
+            (Some("root-id".to_string()), Some("parent-id".to_string()))
+        );
+
+        let direct_reply = vec![tag(&["e", "root-id", "", "root"])];
+        assert_eq!(
+            thread_references(&direct_reply),
+            (Some("root-id".to_string()), Some("root-id".to_string()))
+        );
+
+        let positional = vec![tag(&["e", "root-id"]), tag(&["e", "parent-id"])];
+        assert_eq!(
+            thread_references(&positional),
+            (Some("root-id".to_string()), Some("parent-id".to_string()))
+        );
+
+        assert_eq!(thread_references(&[tag(&["t", "nostr"])]), (None, None));
+    }
+
+    #[test]
+    fn test_event_geohash_takes_most_precise_g_tag() {
+        let tags = vec![
+            vec!["g".to_string(), "u4pr".to_string()],
+            vec!["g".to_string(), "U4PRUYD".to_string()],
+            vec!["g".to_string(), "not a geohash".to_string()],
+            vec!["t".to_string(), "u4pruydqqvj".to_string()],
+        ];
+        assert_eq!(event_geohash(&tags), Some("u4pruyd".to_string()));
+        assert_eq!(event_geohash(&[]), None);
+        assert!(!is_geohash("u4pa"));
+    }
+
+    #[test]
+    fn test_profile_text_prefers_display_name() {
+        let metadata = profile_metadata(
+            r#"{"name":"alice","display_name":"Alice","about":"Embedded Rust developer"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            profile_text(&metadata).as_deref(),
+            Some("Alice: Embedded Rust developer")
+        );
+
+        let metadata = profile_metadata(r#"{"about":"  "}"#).unwrap();
+        assert_eq!(profile_text(&metadata), None);
+        assert!(profile_metadata("not json").is_none());
+    }
+
+    #[test]
+    fn test_truncate_to_bytes_respects_char_boundaries() {
+        assert_eq!(truncate_to_bytes("short", 10), "short");
+        assert_eq!(truncate_to_bytes("hello world", 5), "hello…");
+        // "é" is two bytes; cutting at byte 2 would split it
+        assert_eq!(truncate_to_bytes("aéb", 2), "a…");
+    }
+}
@@ -0,0 +1,637 @@
+use crate::config::{Distance, IndexConfig, IndexType, PgvectorConfig};
+use crate::nostr::NostrEventWithEmbedding;
+use crate::vector_store::{
+    DEFAULT_MODEL_ID, ExportedRow, SearchFilters, SearchHit, TableVersion, VectorColumn,
+    VectorStore,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use pgvector::Vector;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::{Postgres, QueryBuilder, Row};
+use std::collections::{HashMap, HashSet};
+
+/// Columns of the events table, in insert order.
+const COLUMNS: [&str; 14] = [
+    "id",
+    "parent_id",
+    "pubkey",
+    "created_at",
+    "kind",
+    "tags",
+    "tag_values",
+    "content_embedding",
+    "summary_embedding",
+    "content",
+    "model_id",
+    "address",
+    "geohash",
+    "duplicate_of",
+];
+
+/// Columns read into a `SearchHit`, besides the distance.
+const HIT_COLUMNS: &str =
+    "parent_id, pubkey, created_at, content_embedding, summary_embedding, content";
+
+/// Rows per insert statement, keeping the bind parameters of one
+/// statement well below Postgres' limit of 65535.
+const INSERT_BATCH_SIZE: usize = 1_000;
+
+/// Number of IVF lists when `VECTOR_INDEX_PARTITIONS` is unset.
+const DEFAULT_IVF_LISTS: u32 = 100;
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// pgvector operator computing `distance` between two vectors.
+fn distance_operator(distance: Distance) -> &'static str {
+    match distance {
+        Distance::L2 => "<->",
+        Distance::Cosine => "<=>",
+        Distance::Dot => "<#>",
+    }
+}
+
+/// pgvector operator class indexing vectors for `distance`.
+fn operator_class(distance: Distance) -> &'static str {
+    match distance {
+        Distance::L2 => "vector_l2_ops",
+        Distance::Cosine => "vector_cosine_ops",
+        Distance::Dot => "vector_ip_ops",
+    }
+}
+
+/// Appends a `WHERE` clause for `filters`, with the same semantics as the
+/// LanceDB predicate built by `SearchFilters::to_sql`.
+fn push_filters(query: &mut QueryBuilder<'_, Postgres>, filters: &SearchFilters) -> Result<()> {
+    if filters.version.is_some() {
+        anyhow::bail!("Postgres tables keep no versions to search");
+    }
+
+    query.push(" WHERE TRUE");
+    if !filters.authors.is_empty() {
+        query
+            .push(" AND pubkey = ANY(")
+            .push_bind(filters.authors.clone())
+            .push(")");
+    }
+    if let Some(model_id) = &filters.model_id {
+        query.push(" AND model_id = ").push_bind(model_id.clone());
+    }
+    if let Some(kind) = filters.kind {
+        query.push(" AND kind = ").push_bind(kind as i64);
+    }
+    if let Some(min_created) = filters.min_created_at {
+        query.push(" AND created_at >= ").push_bind(min_created);
+    }
+    if let Some(max_created) = filters.max_created_at {
+        query.push(" AND created_at <= ").push_bind(max_created);
+    }
+    if let Some(geohash) = &filters.geohash {
+        query
+            .push(" AND starts_with(geohash, ")
+            .push_bind(geohash.to_lowercase())
+            .push(")");
+    }
+    if filters.exclude_duplicates {
+        query.push(" AND duplicate_of IS NULL");
+    }
+
+    let mut tag_names: Vec<&String> = filters.tags.keys().collect();
+    tag_names.sort();
+    for name in tag_names {
+        let values = &filters.tags[name];
+        if values.is_empty() {
+            continue;
+        }
+        let values: Vec<String> = values
+            .iter()
+            .map(|value| format!("{}:{}", name, value))
+            .collect();
+        query.push(" AND tag_values && ").push_bind(values);
+    }
+
+    Ok(())
+}
+
+fn hit_from_row(row: &PgRow) -> Result<SearchHit> {
+    Ok(SearchHit {
+        id: row.try_get("parent_id")?,
+        distance: row.try_get::<f64, _>("distance")? as f32,
+        pubkey: row.try_get("pubkey")?,
+        created_at: row.try_get("created_at")?,
+        embedding: row.try_get::<Vector, _>("content_embedding")?.to_vec(),
+        summary_embedding: row.try_get::<Vector, _>("summary_embedding")?.to_vec(),
+        content: row.try_get("content")?,
+    })
+}
+
+/// Event store in a Postgres table with pgvector columns, for deployments
+/// that already run Postgres. Rows have the same columns as the LanceDB
+/// table; tables keep no version history.
+pub struct PgvectorStore {
+    pool: PgPool,
+    table_name: String,
+    dimensions: usize,
+    index_config: IndexConfig,
+    model_id: String,
+}
+
+impl PgvectorStore {
+    /// Connects and creates the table, and the pgvector extension, when
+    /// they don't exist yet.
+    pub async fn connect(
+        config: &PgvectorConfig,
+        table_name: &str,
+        dimensions: usize,
+    ) -> Result<Self> {
+        let store = Self::read_only(config, table_name, dimensions).await?;
+        store.create_table_if_not_exists().await?;
+        Ok(store)
+    }
+
+    /// Connects without creating the table, for read replicas.
+    pub async fn read_only(
+        config: &PgvectorConfig,
+        table_name: &str,
+        dimensions: usize,
+    ) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.url)
+            .await?;
+
+        Ok(Self {
+            pool,
+            table_name: table_name.to_string(),
+            dimensions,
+            index_config: IndexConfig::default(),
+            model_id: DEFAULT_MODEL_ID.to_string(),
+        })
+    }
+
+    fn table(&self) -> String {
+        quote_identifier(&self.table_name)
+    }
+
+    async fn create_table_if_not_exists(&self) -> Result<()> {
+        sqlx::query("CREATE EXTENSION IF NOT EXISTS vector")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                id TEXT PRIMARY KEY,
+                parent_id TEXT NOT NULL,
+                pubkey TEXT NOT NULL,
+                created_at BIGINT NOT NULL,
+                kind BIGINT NOT NULL,
+                tags TEXT NOT NULL,
+                tag_values TEXT[] NOT NULL,
+                content_embedding vector({dimensions}) NOT NULL,
+                summary_embedding vector({dimensions}) NOT NULL,
+                content TEXT,
+                model_id TEXT NOT NULL,
+                address TEXT,
+                geohash TEXT,
+                duplicate_of TEXT
+            )",
+            table = self.table(),
+            dimensions = self.dimensions
+        ))
+        .execute(&self.pool)
+        .await?;
+        for column in ["parent_id", "address", "created_at"] {
+            sqlx::query(&format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                quote_identifier(&format!("{}_{}_idx", self.table_name, column)),
+                self.table(),
+                column
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+
+        self.check_dimensions().await
+    }
+
+    /// Fails early when an existing table was created for a different
+    /// embedding model, rather than on the first insert.
+    async fn check_dimensions(&self) -> Result<()> {
+        let stored: i32 = sqlx::query_scalar(
+            "SELECT atttypmod FROM pg_attribute
+             WHERE attrelid = $1::regclass AND attname = 'content_embedding'",
+        )
+        .bind(self.table())
+        .fetch_one(&self.pool)
+        .await?;
+
+        if stored as usize != self.dimensions {
+            anyhow::bail!(
+                "Table '{}' stores {}-dimensional embeddings but the embedding model produces {}; use a new table or reindex",
+                self.table_name,
+                stored,
+                self.dimensions
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl VectorStore for PgvectorStore {
+    async fn list_versions(&self) -> Result<Vec<TableVersion>> {
+        Ok(Vec::new())
+    }
+
+    async fn rollback(&self, _version: u64) -> Result<()> {
+        anyhow::bail!("Postgres tables keep no versions to roll back to")
+    }
+
+    fn set_index_config(&mut self, index_config: IndexConfig) {
+        self.index_config = index_config;
+    }
+
+    fn set_model_id(&mut self, model_id: &str) {
+        self.model_id = model_id.to_string();
+    }
+
+    async fn insert_events(&self, events: &[NostrEventWithEmbedding]) -> Result<()> {
+        // An upsert can't touch the same row twice in one statement, so
+        // keep only the last copy of each event in the batch.
+        let mut unique: Vec<&NostrEventWithEmbedding> = Vec::with_capacity(events.len());
+        for event in events {
+            match unique.iter_mut().find(|existing| existing.id == event.id) {
+                Some(existing) => *existing = event,
+                None => unique.push(event),
+            }
+        }
+
+        let updates = COLUMNS[1..]
+            .iter()
+            .map(|column| format!("{column} = EXCLUDED.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        for chunk in unique.chunks(INSERT_BATCH_SIZE) {
+            let mut query = QueryBuilder::<Postgres>::new(format!(
+                "INSERT INTO {} ({}) ",
+                self.table(),
+                COLUMNS.join(", ")
+            ));
+            query.push_values(chunk, |mut row, event| {
+                row.push_bind(event.id.clone())
+                    .push_bind(event.parent_id.clone())
+                    .push_bind(event.pubkey.clone())
+                    .push_bind(event.created_at)
+                    .push_bind(event.kind as i64)
+                    .push_bind(event.tags.clone())
+                    .push_bind(event.tag_values.clone())
+                    .push_bind(Vector::from(event.content_embedding.clone()))
+                    .push_bind(Vector::from(event.summary_embedding.clone()))
+                    .push_bind(event.content.clone())
+                    .push_bind(self.model_id.clone())
+                    .push_bind(event.address.clone())
+                    .push_bind(event.geohash.clone())
+                    .push_bind(event.duplicate_of.clone());
+            });
+            query.push(format!(" ON CONFLICT (id) DO UPDATE SET {}", updates));
+            query.build().execute(&self.pool).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn dedupe(&self) -> Result<usize> {
+        // `id` is the primary key, so rows are never duplicated
+        Ok(0)
+    }
+
+    async fn search_similar_with_embeddings(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filters: &SearchFilters,
+        column: VectorColumn,
+    ) -> Result<Vec<SearchHit>> {
+        let filters = filters.scoped_to_model(&self.model_id)?;
+        let distance = self.index_config.distance;
+        let embedding = Vector::from(query_embedding.to_vec());
+
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {}, ", HIT_COLUMNS));
+        // `<#>` is the negated inner product; report 1 - dot as LanceDB does
+        if distance == Distance::Dot {
+            query.push("1 + ");
+        }
+        query
+            .push(format!(
+                "({} {} ",
+                column.name(),
+                distance_operator(distance)
+            ))
+            .push_bind(embedding.clone())
+            .push(format!("::vector) AS distance FROM {}", self.table()));
+        push_filters(&mut query, &filters)?;
+        query
+            .push(format!(
+                " ORDER BY {} {} ",
+                column.name(),
+                distance_operator(distance)
+            ))
+            .push_bind(embedding)
+            .push("::vector LIMIT ")
+            .push_bind(limit as i64);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(hit_from_row).collect()
+    }
+
+    async fn search_full_text(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        let filters = filters.scoped_to_model(&self.model_id)?;
+
+        let mut text_query = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {}, 0::float8 AS distance FROM {}",
+            HIT_COLUMNS,
+            self.table()
+        ));
+        push_filters(&mut text_query, &filters)?;
+        text_query
+            .push(" AND to_tsvector('simple', coalesce(content, '')) @@ plainto_tsquery('simple', ")
+            .push_bind(query.to_string())
+            .push(") ORDER BY ts_rank(to_tsvector('simple', coalesce(content, '')), plainto_tsquery('simple', ")
+            .push_bind(query.to_string())
+            .push(")) DESC LIMIT ")
+            .push_bind(limit as i64);
+
+        let rows = text_query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(hit_from_row).collect()
+    }
+
+    async fn get_events(&self, event_ids: &[String]) -> Result<Vec<SearchHit>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, 0::float8 AS distance FROM {} WHERE parent_id = ANY($1)",
+            HIT_COLUMNS,
+            self.table()
+        ))
+        .bind(event_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(hit_from_row).collect()
+    }
+
+    async fn get_rows(&self, event_ids: &[String]) -> Result<Vec<ExportedRow>> {
+        let results = sqlx::query(&format!(
+            "SELECT parent_id, pubkey, kind, created_at, tags, model_id, address, content
+             FROM {} WHERE parent_id = ANY($1)",
+            self.table()
+        ))
+        .bind(event_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut rows: HashMap<String, ExportedRow> = HashMap::new();
+        for result in results {
+            let parent_id: String = result.try_get("parent_id")?;
+            if rows.contains_key(&parent_id) {
+                continue;
+            }
+            let tags: String = result.try_get("tags")?;
+            let row = ExportedRow {
+                id: parent_id.clone(),
+                parent_id: parent_id.clone(),
+                pubkey: result.try_get("pubkey")?,
+                kind: result.try_get("kind")?,
+                created_at: result.try_get("created_at")?,
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
+                content: result.try_get("content")?,
+                model_id: result.try_get("model_id")?,
+                address: result.try_get("address")?,
+                content_embedding: None,
+                summary_embedding: None,
+            };
+            rows.insert(parent_id, row);
+        }
+        Ok(rows.into_values().collect())
+    }
+
+    async fn count_tag_values(&self, name: &str) -> Result<HashMap<String, u64>> {
+        let prefix = format!("{}:", name);
+        let rows = sqlx::query(&format!(
+            "SELECT lower(substr(tag_value, $2)) AS tag_value, count(*) AS count
+             FROM {}, unnest(tag_values) AS tag_value
+             WHERE id = parent_id AND starts_with(tag_value, $1)
+             GROUP BY 1",
+            self.table()
+        ))
+        .bind(&prefix)
+        .bind(prefix.chars().count() as i32 + 1)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("tag_value")?,
+                    row.try_get::<i64, _>("count")? as u64,
+                ))
+            })
+            .collect()
+    }
+
+    async fn count_events(&self) -> Result<usize> {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {}", self.table()))
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn count_events_for_model(&self, model_id: &str) -> Result<usize> {
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT count(*) FROM {} WHERE model_id = $1",
+            self.table()
+        ))
+        .bind(model_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as usize)
+    }
+
+    async fn delete_older_than(&self, cutoff: i64) -> Result<usize> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE created_at < $1",
+            self.table()
+        ))
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn delete_events_by_author(&self, event_ids: &[String], pubkey: &str) -> Result<usize> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE pubkey = $1 AND parent_id = ANY($2)",
+            self.table()
+        ))
+        .bind(pubkey)
+        .bind(event_ids)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn replace_address(
+        &self,
+        address: &str,
+        parent_id: &str,
+        created_at: i64,
+    ) -> Result<bool> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Ids are compared bytewise, as NIP-01 orders them
+        let newer: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (
+                SELECT 1 FROM {} WHERE address = $1
+                AND (created_at > $2 OR (created_at = $2 AND parent_id COLLATE \"C\" < $3))
+            )",
+            self.table()
+        ))
+        .bind(address)
+        .bind(created_at)
+        .bind(parent_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+        if newer {
+            return Ok(false);
+        }
+
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE address = $1 AND parent_id != $2",
+            self.table()
+        ))
+        .bind(address)
+        .bind(parent_id)
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    async fn prune_per_author(&self, max_per_author: usize) -> Result<usize> {
+        // Chunks of one event share its parent_id and count once
+        let deleted: Vec<String> = sqlx::query_scalar(&format!(
+            "DELETE FROM {table} WHERE parent_id IN (
+                SELECT parent_id FROM (
+                    SELECT parent_id, row_number() OVER (PARTITION BY pubkey ORDER BY created_at DESC) AS rank
+                    FROM (SELECT DISTINCT parent_id, pubkey, created_at FROM {table}) events
+                ) ranked
+                WHERE rank > $1
+            )
+            RETURNING parent_id",
+            table = self.table()
+        ))
+        .bind(max_per_author as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(deleted.iter().collect::<HashSet<_>>().len())
+    }
+
+    async fn create_index(&self, column: VectorColumn) -> Result<()> {
+        let config = &self.index_config;
+        let (method, options) = match config.index_type {
+            IndexType::Auto | IndexType::IvfHnswSq => ("hnsw", String::new()),
+            IndexType::IvfFlat => (
+                "ivfflat",
+                format!(
+                    " WITH (lists = {})",
+                    config.num_partitions.unwrap_or(DEFAULT_IVF_LISTS)
+                ),
+            ),
+            IndexType::IvfPq => {
+                anyhow::bail!("pgvector has no ivf_pq index; use auto, ivf_flat or ivf_hnsw_sq")
+            }
+        };
+
+        let index = quote_identifier(&format!("{}_{}_idx", self.table_name, column.name()));
+        sqlx::query(&format!("DROP INDEX IF EXISTS {}", index))
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX {} ON {} USING {} ({} {}){}",
+            index,
+            self.table(),
+            method,
+            column.name(),
+            operator_class(config.distance),
+            options
+        ))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn optimize(&self, _prune_older_than: chrono::Duration) -> Result<()> {
+        // Indexes are maintained on write and there is no history to
+        // prune; reclaim deleted rows and refresh planner statistics
+        sqlx::query(&format!("VACUUM (ANALYZE) {}", self.table()))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_fts_index(&self) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {} ON {} USING GIN (to_tsvector('simple', coalesce(content, '')))",
+            quote_identifier(&format!("{}_content_fts_idx", self.table_name)),
+            self.table()
+        ))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_bind_every_value() {
+        let filters = SearchFilters {
+            authors: vec!["alice".to_string(), "bob".to_string()],
+            kind: Some(1),
+            geohash: Some("U4PR".to_string()),
+            exclude_duplicates: true,
+            tags: HashMap::from([(
+                "t".to_string(),
+                vec!["nostr".to_string(), "bitcoin".to_string()],
+            )]),
+            ..SearchFilters::default()
+        };
+
+        let mut query = QueryBuilder::<Postgres>::new("SELECT id FROM events");
+        push_filters(&mut query, &filters).unwrap();
+        assert_eq!(
+            query.sql(),
+            "SELECT id FROM events WHERE TRUE AND pubkey = ANY($1) AND kind = $2 \
+             AND starts_with(geohash, $3) AND duplicate_of IS NULL AND tag_values && $4"
+        );
+
+        let versioned = SearchFilters {
+            version: Some(3),
+            ..SearchFilters::default()
+        };
+        let mut query = QueryBuilder::<Postgres>::new("SELECT id FROM events");
+        assert!(push_filters(&mut query, &versioned).is_err());
+    }
+
+    #[test]
+    fn test_quote_identifier_escapes_quotes() {
+        assert_eq!(quote_identifier("nostr_events"), "\"nostr_events\"");
+        assert_eq!(quote_identifier("odd\"name"), "\"odd\"\"name\"");
+    }
+}
@@ -0,0 +1,30 @@
+use crate::chat::ChatClient;
+use crate::config::QueryExpansionConfig;
+use anyhow::Result;
+
+/// Rewrites terse search queries into richer text with a chat model before
+/// they are embedded, e.g. "btc fees" into a sentence about Bitcoin
+/// transaction fees, mempool congestion and fee estimation.
+pub struct QueryExpander {
+    chat: ChatClient,
+}
+
+impl QueryExpander {
+    pub fn new(config: QueryExpansionConfig) -> Self {
+        Self {
+            chat: ChatClient::new(config.api_url, config.api_key, config.model),
+        }
+    }
+
+    pub async fn expand(&self, query: &str) -> Result<String> {
+        let prompt = format!(
+            "Expand the following search query for a semantic search engine over Nostr notes. Spell out abbreviations, add closely related terms and phrase it as one or two descriptive sentences. Reply with the expanded query only.\n\nQuery: {}",
+            query
+        );
+
+        self.chat
+            .complete(&prompt, 150, 0.3)
+            .await
+            .map_err(|e| anyhow::anyhow!("Query expansion failed: {}", e))
+    }
+}
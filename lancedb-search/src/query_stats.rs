@@ -0,0 +1,179 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Queries longer than this are cut before they are recorded.
+const MAX_QUERY_CHARS: usize = 256;
+
+/// Search analytics kept on disk, for tuning thresholds and models. Only
+/// the normalized query text is recorded, never who searched or with which
+/// filters.
+pub struct QueryStats {
+    db: sled::Db,
+}
+
+/// Totals stored per normalized query.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueryRecord {
+    count: u64,
+    zero_results: u64,
+    total_latency_ms: u64,
+    total_hits: u64,
+    last_seen: i64,
+}
+
+/// Aggregated numbers for one query, as returned by `/stats/queries`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct QueryStat {
+    pub query: String,
+    pub count: u64,
+    /// Searches for this query that returned nothing
+    pub zero_results: u64,
+    pub avg_latency_ms: f64,
+    pub avg_hits: f64,
+    /// Unix timestamp of the latest search
+    pub last_seen: i64,
+}
+
+impl QueryStats {
+    pub fn open(path: &str) -> Result<Self> {
+        let db = sled::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open query stats at {}: {}", path, e))?;
+        Ok(Self { db })
+    }
+
+    pub fn record(&self, query: &str, latency_ms: u64, hits: usize) -> Result<()> {
+        let query = normalize_query(query);
+        if query.is_empty() {
+            return Ok(());
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        self.db.update_and_fetch(query.as_bytes(), |existing| {
+            let mut record: QueryRecord = existing
+                .and_then(|bytes| serde_json::from_slice(bytes).ok())
+                .unwrap_or_default();
+            record.count += 1;
+            record.zero_results += u64::from(hits == 0);
+            record.total_latency_ms += latency_ms;
+            record.total_hits += hits as u64;
+            record.last_seen = now;
+            serde_json::to_vec(&record).ok()
+        })?;
+        Ok(())
+    }
+
+    /// The `limit` most frequent queries.
+    pub fn top_queries(&self, limit: usize) -> Result<Vec<QueryStat>> {
+        let mut stats = self.all()?;
+        stats.sort_by(|a, b| b.count.cmp(&a.count).then(b.last_seen.cmp(&a.last_seen)));
+        stats.truncate(limit);
+        Ok(stats)
+    }
+
+    /// The `limit` queries that most often found nothing.
+    pub fn zero_result_queries(&self, limit: usize) -> Result<Vec<QueryStat>> {
+        let mut stats: Vec<QueryStat> = self
+            .all()?
+            .into_iter()
+            .filter(|stat| stat.zero_results > 0)
+            .collect();
+        stats.sort_by(|a, b| {
+            b.zero_results
+                .cmp(&a.zero_results)
+                .then(b.last_seen.cmp(&a.last_seen))
+        });
+        stats.truncate(limit);
+        Ok(stats)
+    }
+
+    /// Recorded queries starting with `prefix` and their counts, most
+    /// frequent first. Queries that never found anything are left out.
+    pub fn complete(&self, prefix: &str, limit: usize) -> Result<Vec<(String, u64)>> {
+        let prefix = normalize_query(prefix);
+        if prefix.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut completions = Vec::new();
+        for entry in self.db.scan_prefix(prefix.as_bytes()) {
+            let (key, value) = entry?;
+            let Ok(record) = serde_json::from_slice::<QueryRecord>(&value) else {
+                continue;
+            };
+            if record.zero_results < record.count {
+                completions.push((String::from_utf8_lossy(&key).into_owned(), record.count));
+            }
+        }
+        completions.sort_by(|a, b| b.1.cmp(&a.1));
+        completions.truncate(limit);
+        Ok(completions)
+    }
+
+    fn all(&self) -> Result<Vec<QueryStat>> {
+        let mut stats = Vec::new();
+        for entry in self.db.iter() {
+            let (key, value) = entry?;
+            let Ok(record) = serde_json::from_slice::<QueryRecord>(&value) else {
+                continue;
+            };
+            let count = record.count.max(1) as f64;
+            stats.push(QueryStat {
+                query: String::from_utf8_lossy(&key).into_owned(),
+                count: record.count,
+                zero_results: record.zero_results,
+                avg_latency_ms: record.total_latency_ms as f64 / count,
+                avg_hits: record.total_hits as f64 / count,
+                last_seen: record.last_seen,
+            });
+        }
+        Ok(stats)
+    }
+}
+
+/// Lowercases and collapses whitespace so trivially different spellings of
+/// a query are counted together.
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_are_grouped_by_normalized_query() {
+        let stats = QueryStats {
+            db: sled::Config::new().temporary(true).open().unwrap(),
+        };
+
+        stats.record("Nostr  Relays", 10, 5).unwrap();
+        stats.record("nostr relays", 30, 0).unwrap();
+        stats.record("bitcoin", 20, 3).unwrap();
+        stats.record("   ", 20, 3).unwrap();
+
+        let top = stats.top_queries(10).unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].query, "nostr relays");
+        assert_eq!(top[0].count, 2);
+        assert_eq!(top[0].avg_latency_ms, 20.0);
+        assert_eq!(top[0].avg_hits, 2.5);
+
+        let zero = stats.zero_result_queries(10).unwrap();
+        assert_eq!(zero.len(), 1);
+        assert_eq!(zero[0].zero_results, 1);
+
+        stats.record("nostr clients", 10, 0).unwrap();
+        assert_eq!(
+            stats.complete("NOSTR", 10).unwrap(),
+            vec![("nostr relays".to_string(), 2)]
+        );
+    }
+}
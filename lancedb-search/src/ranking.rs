@@ -0,0 +1,162 @@
+use crate::embeddings::{cosine_similarity, l2_normalize};
+
+/// Selects up to `limit` candidates by maximal marginal relevance.
+///
+/// `lambda` trades relevance to the query (1.0) against dissimilarity to the
+/// already selected results (0.0). Returns indices into `embeddings` in
+/// selection order.
+pub fn mmr_select(query: &[f32], embeddings: &[&[f32]], limit: usize, lambda: f32) -> Vec<usize> {
+    let relevance: Vec<f32> = embeddings
+        .iter()
+        .map(|embedding| cosine_similarity(query, embedding))
+        .collect();
+
+    let mut selected: Vec<usize> = Vec::with_capacity(limit.min(embeddings.len()));
+    let mut remaining: Vec<usize> = (0..embeddings.len()).collect();
+
+    while selected.len() < limit && !remaining.is_empty() {
+        let mut best_position = 0;
+        let mut best_score = f32::NEG_INFINITY;
+
+        for (position, &candidate) in remaining.iter().enumerate() {
+            let redundancy = selected
+                .iter()
+                .map(|&chosen| cosine_similarity(embeddings[candidate], embeddings[chosen]))
+                .fold(0.0_f32, f32::max);
+            let score = lambda * relevance[candidate] - (1.0 - lambda) * redundancy;
+
+            if score > best_score {
+                best_score = score;
+                best_position = position;
+            }
+        }
+
+        selected.push(remaining.remove(best_position));
+    }
+
+    selected
+}
+
+/// Merges several ranked ID lists with reciprocal rank fusion: each list
+/// contributes `1 / (k + rank)` for every ID it contains. Returns IDs with
+/// their fused score, best first.
+pub fn reciprocal_rank_fusion(rankings: &[Vec<String>], k: f32) -> Vec<(String, f32)> {
+    let mut scores: Vec<(String, f32)> = Vec::new();
+
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            let contribution = 1.0 / (k + rank as f32 + 1.0);
+            match scores.iter_mut().find(|(existing, _)| existing == id) {
+                Some((_, score)) => *score += contribution,
+                None => scores.push((id.clone(), contribution)),
+            }
+        }
+    }
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores
+}
+
+/// Centroid of `vectors` after normalizing each to unit length, so every
+/// vector pulls equally regardless of its magnitude. `None` when empty.
+pub fn mean_vector(vectors: &[&[f32]]) -> Option<Vec<f32>> {
+    let dimensions = vectors.first()?.len();
+    let mut mean = vec![0.0; dimensions];
+    for vector in vectors {
+        let mut normalized = vector.to_vec();
+        l2_normalize(&mut normalized);
+        for (sum, value) in mean.iter_mut().zip(normalized) {
+            *sum += value;
+        }
+    }
+    for value in mean.iter_mut() {
+        *value /= vectors.len() as f32;
+    }
+    Some(mean)
+}
+
+/// Exponential recency weight in `[0, 1]`: 1.0 for brand-new events, 0.5
+/// after one half-life, 0.25 after two, and so on.
+pub fn recency_decay(created_at: i64, now: i64, half_life_secs: f64) -> f32 {
+    if half_life_secs <= 0.0 {
+        return 1.0;
+    }
+    let age = (now - created_at).max(0) as f64;
+    0.5_f64.powf(age / half_life_secs) as f32
+}
+
+/// Blends cosine relevance with recency. `recency_weight` of 0.0 keeps pure
+/// relevance, 1.0 ranks purely by age.
+pub fn blend_recency(
+    relevance: f32,
+    created_at: i64,
+    now: i64,
+    half_life_secs: f64,
+    recency_weight: f32,
+) -> f32 {
+    let decay = recency_decay(created_at, now, half_life_secs);
+    (1.0 - recency_weight) * relevance + recency_weight * decay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recency_decay_halves_each_half_life() {
+        let now = 1_000_000;
+        assert_eq!(recency_decay(now, now, 3600.0), 1.0);
+        assert!((recency_decay(now - 3600, now, 3600.0) - 0.5).abs() < 1e-6);
+        assert!((recency_decay(now - 7200, now, 3600.0) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_blend_recency_prefers_fresh_notes() {
+        let now = 1_000_000;
+        let old = blend_recency(0.8, now - 86_400 * 7, now, 86_400.0, 0.5);
+        let fresh = blend_recency(0.7, now - 60, now, 86_400.0, 0.5);
+        assert!(fresh > old);
+    }
+
+    #[test]
+    fn test_rrf_rewards_ids_ranked_by_both_lists() {
+        let semantic = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let keyword = vec!["c".to_string(), "d".to_string()];
+
+        let fused = reciprocal_rank_fusion(&[semantic, keyword], 60.0);
+        let ids: Vec<&str> = fused.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "a", "b", "d"]);
+    }
+
+    #[test]
+    fn test_mean_vector_weights_vectors_equally() {
+        let short = [1.0, 0.0];
+        let long = [0.0, 10.0];
+
+        let mean = mean_vector(&[&short, &long]).unwrap();
+        assert!((mean[0] - mean[1]).abs() < 1e-6);
+        assert_eq!(mean_vector(&[]), None);
+    }
+
+    #[test]
+    fn test_mmr_without_diversity_keeps_relevance_order() {
+        let query = [1.0, 0.0];
+        let a = [1.0, 0.0];
+        let b = [0.9, 0.1];
+        let c = [0.0, 1.0];
+
+        let order = mmr_select(&query, &[&c, &a, &b], 3, 1.0);
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_mmr_skips_near_duplicates() {
+        let query = [1.0, 0.2];
+        let original = [1.0, 0.0];
+        let repost = [1.0, 0.0];
+        let other = [0.6, 0.8];
+
+        let order = mmr_select(&query, &[&original, &repost, &other], 2, 0.5);
+        assert_eq!(order, vec![0, 2]);
+    }
+}
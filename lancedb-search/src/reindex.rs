@@ -0,0 +1,247 @@
+use crate::embedding_service::EmbeddingSearchService;
+use crate::lancedb_store::LanceDBStore;
+use crate::nostr::NostrEvent;
+use crate::relay_search::RelaySearcher;
+use crate::vector_store::{ExportedRow, VectorStore};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How a reindex reads and paces its work.
+#[derive(Debug, Clone)]
+pub struct ReindexOptions {
+    pub batch_size: usize,
+    /// Upper bound on events embedded per second, to stay within the
+    /// embedding provider's rate limits
+    pub max_events_per_sec: Option<f64>,
+}
+
+/// Running totals, reported after every batch.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReindexProgress {
+    /// Events found in the source table
+    pub events: usize,
+    /// Events stored in the target table and found there afterwards
+    pub reindexed: usize,
+    /// Events whose content came from relays rather than the source table
+    pub refetched: usize,
+    /// Events without complete stored content that no relay returned
+    pub unavailable: usize,
+    /// Events that could not be embedded or stored, or were missing from
+    /// the target table after storing
+    pub failed: usize,
+}
+
+/// Re-embeds the events of one table into another, e.g. after switching
+/// the embedding model. The target service's model, chunking and content
+/// settings apply, so the target table is filled as if the events had
+/// just arrived.
+pub struct Reindexer {
+    source: LanceDBStore,
+    target: Arc<EmbeddingSearchService>,
+    /// Fetches events whose stored content is incomplete; without it they
+    /// are counted as unavailable
+    relay_searcher: Option<RelaySearcher>,
+    options: ReindexOptions,
+}
+
+impl Reindexer {
+    pub fn new(
+        source: LanceDBStore,
+        target: Arc<EmbeddingSearchService>,
+        relay_searcher: Option<RelaySearcher>,
+        options: ReindexOptions,
+    ) -> Self {
+        Self {
+            source,
+            target,
+            relay_searcher,
+            options,
+        }
+    }
+
+    pub async fn run(&self) -> Result<ReindexProgress> {
+        // Rows per event: chunked events have one per chunk, and none of
+        // them holds the whole content
+        let mut row_counts: HashMap<String, usize> = HashMap::new();
+        self.source
+            .export_rows(false, false, |row| {
+                *row_counts.entry(row.parent_id).or_default() += 1;
+                Ok(())
+            })
+            .await?;
+        let mut event_ids: Vec<String> = row_counts.keys().cloned().collect();
+        event_ids.sort();
+
+        let total_batches = event_ids.len().div_ceil(self.options.batch_size.max(1));
+        let started = Instant::now();
+        let mut progress = ReindexProgress {
+            events: event_ids.len(),
+            ..ReindexProgress::default()
+        };
+
+        for (batch_number, batch) in event_ids.chunks(self.options.batch_size.max(1)).enumerate() {
+            let mut events = Vec::with_capacity(batch.len());
+            let mut incomplete = Vec::new();
+            for row in self.source.get_rows(batch).await? {
+                let row_count = row_counts.get(&row.parent_id).copied().unwrap_or(1);
+                let id = row.parent_id.clone();
+                match stored_event(row, row_count) {
+                    Some(event) => events.push(event),
+                    None => incomplete.push(id),
+                }
+            }
+
+            if !incomplete.is_empty() {
+                let fetched = self.refetch(&incomplete).await;
+                progress.refetched += fetched.len();
+                progress.unavailable += incomplete.len() - fetched.len();
+                events.extend(fetched);
+            }
+
+            let ids: Vec<String> = events.iter().map(|event| event.id.clone()).collect();
+            match self.target.embed_and_store_events(&events).await {
+                Ok(()) => {
+                    // Events whose embedding failed are dropped silently
+                    // by the store step, so count what actually arrived
+                    let stored: HashSet<String> = self
+                        .target
+                        .stored_events(&ids)
+                        .await?
+                        .into_iter()
+                        .map(|row| row.parent_id)
+                        .collect();
+                    progress.reindexed += stored.len();
+                    progress.failed += ids.len() - stored.len();
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Failed to reindex batch {}/{}: {}",
+                        batch_number + 1,
+                        total_batches,
+                        e
+                    );
+                    progress.failed += ids.len();
+                }
+            }
+
+            println!(
+                "Batch {}/{}: {} reindexed, {} refetched, {} unavailable, {} failed, {:.1} events/s",
+                batch_number + 1,
+                total_batches,
+                progress.reindexed,
+                progress.refetched,
+                progress.unavailable,
+                progress.failed,
+                progress.reindexed as f64 / started.elapsed().as_secs_f64().max(1.0)
+            );
+
+            let processed = progress.reindexed + progress.failed;
+            if let Some(rate) = self.options.max_events_per_sec
+                && let Some(delay) = throttle_delay(processed, rate, started.elapsed())
+            {
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        Ok(progress)
+    }
+
+    /// Fetches `event_ids` from relays; relay failures are logged and
+    /// leave the events out.
+    async fn refetch(&self, event_ids: &[String]) -> Vec<NostrEvent> {
+        let Some(relay_searcher) = &self.relay_searcher else {
+            return Vec::new();
+        };
+        match relay_searcher.fetch_events(event_ids).await {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Failed to fetch {} events from relays: {}",
+                    event_ids.len(),
+                    e
+                );
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Rebuilds an event from its stored row when the row holds its whole
+/// content: content storage was enabled, the content wasn't truncated, and
+/// the event wasn't split into chunk rows. The signature isn't stored, so
+/// the rebuilt event has none.
+fn stored_event(row: ExportedRow, row_count: usize) -> Option<NostrEvent> {
+    let content = row.content?;
+    if row_count != 1 || content.ends_with('…') {
+        return None;
+    }
+    Some(NostrEvent {
+        id: row.parent_id,
+        pubkey: row.pubkey,
+        created_at: row.created_at,
+        kind: row.kind as i32,
+        tags: row.tags,
+        content,
+        sig: String::new(),
+        summary: None,
+    })
+}
+
+/// How long to wait so that `processed` events after `elapsed` stay at or
+/// below `max_events_per_sec`.
+fn throttle_delay(
+    processed: usize,
+    max_events_per_sec: f64,
+    elapsed: Duration,
+) -> Option<Duration> {
+    if max_events_per_sec <= 0.0 {
+        return None;
+    }
+    let due = Duration::from_secs_f64(processed as f64 / max_events_per_sec);
+    due.checked_sub(elapsed).filter(|delay| !delay.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(content: Option<&str>) -> ExportedRow {
+        ExportedRow {
+            id: "id".to_string(),
+            parent_id: "id".to_string(),
+            pubkey: "pubkey".to_string(),
+            kind: 1,
+            created_at: 1_700_000_000,
+            tags: vec![vec!["t".to_string(), "nostr".to_string()]],
+            content: content.map(str::to_string),
+            model_id: "old-model".to_string(),
+            address: None,
+            content_embedding: None,
+            summary_embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_stored_event_needs_complete_content() {
+        let event = stored_event(row(Some("hello")), 1).unwrap();
+        assert_eq!(event.id, "id");
+        assert_eq!(event.content, "hello");
+        assert_eq!(event.tags, vec![vec!["t".to_string(), "nostr".to_string()]]);
+
+        assert!(stored_event(row(None), 1).is_none());
+        assert!(stored_event(row(Some("hel…")), 1).is_none());
+        assert!(stored_event(row(Some("first chunk")), 3).is_none());
+    }
+
+    #[test]
+    fn test_throttle_delay_caps_the_rate() {
+        assert_eq!(
+            throttle_delay(100, 50.0, Duration::from_secs(1)),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(throttle_delay(100, 50.0, Duration::from_secs(3)), None);
+        assert_eq!(throttle_delay(100, 0.0, Duration::ZERO), None);
+    }
+}
@@ -0,0 +1,447 @@
+use crate::config::RelaySearchConfig;
+use crate::nostr::NostrEvent;
+use anyhow::Result;
+use nostr_sdk::{Client, EventId, Filter, Kind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+/// Relays queried when none are configured. relay.nostr.band and
+/// nostr.wine implement NIP-50 search.
+pub const DEFAULT_RELAYS: &[&str] = &[
+    "wss://relay.nostr.band",
+    "wss://nostr.wine",
+    "wss://relay.damus.io",
+    "wss://nos.lol",
+];
+
+/// Kinds searched when the caller doesn't ask for specific ones.
+pub const DEFAULT_KINDS: &[u16] = &[1, 30023];
+
+/// How many recent events to fetch per wanted result from relays without
+/// NIP-50, since most of them won't match the query.
+const CLIENT_SIDE_OVERFETCH: usize = 10;
+
+/// Full-text search against public relays, for queries the local index
+/// can't answer yet. Relays whose NIP-11 document lists NIP-50 get a
+/// `search` filter; the rest are asked for recent events that are matched
+/// against the query here.
+pub struct RelaySearcher {
+    client: Client,
+    http_client: reqwest::Client,
+    config: RwLock<RelaySearchConfig>,
+    /// Relays added to the client so far
+    connected: Mutex<HashSet<String>>,
+    /// Whether each relay supports NIP-50, read once from NIP-11. Failed
+    /// lookups aren't cached, so they're tried again on the next search
+    nip50_support: Mutex<HashMap<String, bool>>,
+}
+
+impl RelaySearcher {
+    pub fn new(config: RelaySearchConfig) -> Self {
+        Self {
+            client: Client::default(),
+            http_client: reqwest::Client::new(),
+            config: RwLock::new(config),
+            connected: Mutex::new(HashSet::new()),
+            nip50_support: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_default_relays() -> Self {
+        Self::new(RelaySearchConfig::default())
+    }
+
+    pub fn config(&self) -> RelaySearchConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Switches to a new relay list, timeout and default kinds. Relays no
+    /// longer listed are disconnected, new ones are connected on the next
+    /// search, and cached NIP-11 answers are dropped so they are read again.
+    pub async fn reload(&self, config: RelaySearchConfig) -> Result<()> {
+        *self.config.write().unwrap() = config.clone();
+
+        let mut connected = self.connected.lock().await;
+        let removed: Vec<String> = connected
+            .iter()
+            .filter(|relay| !config.relays.contains(relay))
+            .cloned()
+            .collect();
+        for relay in removed {
+            self.client.remove_relay(relay.as_str()).await?;
+            connected.remove(&relay);
+        }
+
+        self.nip50_support.lock().await.clear();
+        Ok(())
+    }
+
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<NostrEvent>> {
+        let default_kinds = self.config.read().unwrap().default_kinds.clone();
+        self.search_relay_events_with_kinds(query, &default_kinds, limit)
+            .await
+    }
+
+    /// Asks all relays at once for events of `kinds` matching `query` and
+    /// returns up to `limit` distinct events, newest first. Relays that
+    /// fail or miss the shared deadline are skipped.
+    pub async fn search_relay_events_with_kinds(
+        &self,
+        query: &str,
+        kinds: &[u16],
+        limit: usize,
+    ) -> Result<Vec<NostrEvent>> {
+        let config = self.config();
+        let timeout = config.timeout();
+        // Every relay gets the same deadline, NIP-11 lookups included;
+        // slow relays only lose their own results
+        let deadline = tokio::time::Instant::now() + timeout;
+        self.connect(&config.relays).await?;
+
+        let nip50_support = self.nip50_support(&config.relays, deadline).await;
+        let relays: Vec<(&String, bool)> = config
+            .relays
+            .iter()
+            .map(|relay| (relay, nip50_support.get(relay).copied().unwrap_or(false)))
+            .collect();
+
+        let kinds: Vec<Kind> = kinds.iter().map(|&kind| Kind::from(kind)).collect();
+        let search_filter = Filter::new()
+            .kinds(kinds.clone())
+            .search(query)
+            .limit(limit);
+        let scan_filter = Filter::new()
+            .kinds(kinds)
+            .limit(limit.saturating_mul(CLIENT_SIDE_OVERFETCH));
+
+        let searches = relays.into_iter().map(|(relay, supports_search)| {
+            let filter = if supports_search {
+                search_filter.clone()
+            } else {
+                scan_filter.clone()
+            };
+            async move {
+                let fetch = self
+                    .client
+                    .fetch_events_from([relay.as_str()], filter, timeout);
+                match tokio::time::timeout_at(deadline, fetch).await {
+                    Ok(Ok(found)) => found
+                        .into_iter()
+                        .filter(|event| supports_search || matches_query(&event.content, query))
+                        .map(|event| NostrEvent::from(&event))
+                        .collect(),
+                    Ok(Err(e)) => {
+                        eprintln!("Warning: Relay search on {} failed: {}", relay, e);
+                        Vec::new()
+                    }
+                    Err(_) => {
+                        eprintln!("Warning: Relay search on {} timed out", relay);
+                        Vec::new()
+                    }
+                }
+            }
+        });
+        let found: Vec<Vec<NostrEvent>> = futures::future::join_all(searches).await;
+
+        Ok(dedup_newest_first(found.into_iter().flatten(), limit))
+    }
+
+    /// Looks up events by id on all configured relays.
+    pub async fn fetch_events(&self, event_ids: &[String]) -> Result<Vec<NostrEvent>> {
+        let ids: Vec<EventId> = event_ids
+            .iter()
+            .filter_map(|id| EventId::from_hex(id).ok())
+            .collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let config = self.config();
+        self.connect(&config.relays).await?;
+        let filter = Filter::new().ids(ids.clone()).limit(ids.len());
+        let found = self
+            .client
+            .fetch_events_from(
+                config.relays.iter().map(String::as_str),
+                filter,
+                config.timeout(),
+            )
+            .await?;
+
+        Ok(dedup_newest_first(
+            found.into_iter().map(|event| NostrEvent::from(&event)),
+            ids.len(),
+        ))
+    }
+
+    /// Reads the NIP-11 document of each relay not seen before, giving up
+    /// at `deadline`. Relays whose document can't be fetched are treated as
+    /// not supporting NIP-50 for this search only.
+    async fn nip50_support(
+        &self,
+        relays: &[String],
+        deadline: tokio::time::Instant,
+    ) -> HashMap<String, bool> {
+        // The lock isn't held during lookups, so one slow relay doesn't
+        // hold up concurrent searches
+        let mut support = self.nip50_support.lock().await.clone();
+
+        let lookups = relays
+            .iter()
+            .filter(|relay| !support.contains_key(*relay))
+            .map(|relay| async move {
+                let lookup = tokio::time::timeout_at(deadline, self.supported_nips(relay));
+                let supports_search = match lookup.await {
+                    Ok(Ok(nips)) => Some(nips.contains(&50)),
+                    Ok(Err(e)) => {
+                        eprintln!(
+                            "Warning: Failed to read NIP-11 document of {}: {}",
+                            relay, e
+                        );
+                        None
+                    }
+                    Err(_) => {
+                        eprintln!("Warning: Reading NIP-11 document of {} timed out", relay);
+                        None
+                    }
+                };
+                (relay.clone(), supports_search)
+            });
+        let found: Vec<(String, Option<bool>)> = futures::future::join_all(lookups).await;
+
+        let mut cache = self.nip50_support.lock().await;
+        for (relay, supports_search) in found {
+            if let Some(supports_search) = supports_search {
+                cache.insert(relay.clone(), supports_search);
+            }
+            support.insert(relay, supports_search.unwrap_or(false));
+        }
+
+        support
+    }
+
+    async fn supported_nips(&self, relay: &str) -> Result<Vec<u16>> {
+        #[derive(Deserialize)]
+        struct RelayInformation {
+            #[serde(default)]
+            supported_nips: Vec<u16>,
+        }
+
+        let information: RelayInformation = self
+            .http_client
+            .get(information_url(relay))
+            .header("Accept", "application/nostr+json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(information.supported_nips)
+    }
+
+    /// Adds and connects relays the client doesn't know yet.
+    async fn connect(&self, relays: &[String]) -> Result<()> {
+        let mut connected = self.connected.lock().await;
+        let mut added = false;
+        for relay in relays {
+            if !connected.contains(relay) {
+                self.client.add_relay(relay.as_str()).await?;
+                connected.insert(relay.clone());
+                added = true;
+            }
+        }
+        if added {
+            self.client.connect().await;
+        }
+        Ok(())
+    }
+}
+
+/// Drops repeated event ids and keeps the `limit` newest events.
+fn dedup_newest_first(
+    events: impl IntoIterator<Item = NostrEvent>,
+    limit: usize,
+) -> Vec<NostrEvent> {
+    let mut seen = HashSet::new();
+    let mut events: Vec<NostrEvent> = events
+        .into_iter()
+        .filter(|event| seen.insert(event.id.clone()))
+        .collect();
+    events.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    events.truncate(limit);
+    events
+}
+
+/// HTTP URL serving a relay's NIP-11 information document.
+fn information_url(relay: &str) -> String {
+    if let Some(rest) = relay.strip_prefix("wss://") {
+        format!("https://{}", rest)
+    } else if let Some(rest) = relay.strip_prefix("ws://") {
+        format!("http://{}", rest)
+    } else {
+        relay.to_string()
+    }
+}
+
+/// Client-side stand-in for NIP-50: every query term must appear in the
+/// content, ignoring case.
+pub fn matches_query(content: &str, query: &str) -> bool {
+    let content = content.to_lowercase();
+    query
+        .split_whitespace()
+        .all(|term| content.contains(&term.to_lowercase()))
+}
+
+/// Where a combined search result came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultSource {
+    /// The local vector index
+    Local,
+    /// A relay search
+    Relay,
+    /// Found by both
+    Both,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CombinedHit {
+    pub id: String,
+    pub source: ResultSource,
+    /// The event as returned by a relay; local hits only carry their id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<NostrEvent>,
+    /// Stored content of local hits, when content storage is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+/// Merges local results (in rank order) with relay results, local hits
+/// first, dropping duplicates and labelling each hit with its source.
+pub fn merge_results(
+    local_ids: Vec<String>,
+    mut snippets: HashMap<String, String>,
+    relay_events: Vec<NostrEvent>,
+    limit: usize,
+) -> Vec<CombinedHit> {
+    let mut relay_events: Vec<Option<NostrEvent>> = relay_events.into_iter().map(Some).collect();
+    let mut take_relay_event = |id: &str| {
+        relay_events
+            .iter_mut()
+            .find(|event| event.as_ref().is_some_and(|event| event.id == id))
+            .and_then(Option::take)
+    };
+
+    let mut hits: Vec<CombinedHit> = Vec::new();
+    for id in local_ids {
+        if hits.iter().any(|hit| hit.id == id) {
+            continue;
+        }
+        let event = take_relay_event(&id);
+        hits.push(CombinedHit {
+            source: if event.is_some() {
+                ResultSource::Both
+            } else {
+                ResultSource::Local
+            },
+            snippet: snippets.remove(&id),
+            event,
+            id,
+        });
+    }
+
+    for event in relay_events.into_iter().flatten() {
+        if hits.iter().any(|hit| hit.id == event.id) {
+            continue;
+        }
+        hits.push(CombinedHit {
+            id: event.id.clone(),
+            source: ResultSource::Relay,
+            event: Some(event),
+            snippet: None,
+        });
+    }
+
+    hits.truncate(limit);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str) -> NostrEvent {
+        NostrEvent {
+            id: id.to_string(),
+            pubkey: "pubkey".to_string(),
+            created_at: 0,
+            kind: 1,
+            tags: vec![],
+            content: "hello".to_string(),
+            sig: String::new(),
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn test_dedup_newest_first() {
+        let mut old = event("a");
+        old.created_at = 1;
+        let mut new = event("b");
+        new.created_at = 2;
+
+        let events = dedup_newest_first(vec![old.clone(), new, old], 10);
+        let ids: Vec<&str> = events.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert_eq!(dedup_newest_first(events, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_information_url() {
+        assert_eq!(information_url("wss://nos.lol"), "https://nos.lol");
+        assert_eq!(
+            information_url("ws://localhost:8080"),
+            "http://localhost:8080"
+        );
+    }
+
+    #[test]
+    fn test_matches_query_requires_every_term() {
+        assert!(matches_query(
+            "Embedded Rust on the RP2040",
+            "rust embedded"
+        ));
+        assert!(!matches_query("Embedded C on the RP2040", "rust embedded"));
+    }
+
+    #[test]
+    fn test_merge_labels_sources_and_dedups() {
+        let hits = merge_results(
+            vec!["a".to_string(), "b".to_string()],
+            HashMap::new(),
+            vec![event("b"), event("c"), event("c")],
+            10,
+        );
+
+        let labelled: Vec<(&str, ResultSource)> = hits
+            .iter()
+            .map(|hit| (hit.id.as_str(), hit.source))
+            .collect();
+        assert_eq!(
+            labelled,
+            vec![
+                ("a", ResultSource::Local),
+                ("b", ResultSource::Both),
+                ("c", ResultSource::Relay)
+            ]
+        );
+        assert!(hits[1].event.is_some());
+        assert_eq!(
+            merge_results(vec!["a".to_string()], HashMap::new(), vec![event("c")], 1).len(),
+            1
+        );
+    }
+}
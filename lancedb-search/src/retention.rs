@@ -0,0 +1,38 @@
+use crate::config::RetentionConfig;
+use crate::embedding_service::EmbeddingSearchService;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Periodically prunes the index according to the configured retention
+/// policy so it doesn't grow without bound.
+pub struct RetentionTask {
+    embedding_service: Arc<EmbeddingSearchService>,
+    config: RetentionConfig,
+}
+
+impl RetentionTask {
+    pub fn new(embedding_service: Arc<EmbeddingSearchService>, config: RetentionConfig) -> Self {
+        Self {
+            embedding_service,
+            config,
+        }
+    }
+
+    pub async fn start(self) {
+        println!(
+            "Retention task started (max age: {:?} days, max per author: {:?})",
+            self.config.max_age_days, self.config.max_per_author
+        );
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs));
+        loop {
+            interval.tick().await;
+
+            match self.embedding_service.prune(&self.config).await {
+                Ok(0) => {}
+                Ok(deleted) => println!("Retention pruned {} events", deleted),
+                Err(e) => eprintln!("Retention pruning failed: {}", e),
+            }
+        }
+    }
+}
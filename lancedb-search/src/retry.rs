@@ -0,0 +1,180 @@
+use crate::config::RetryConfig;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Exponential backoff before retry number `attempt` (starting at 0),
+/// capped at `max_backoff_ms`, with up to 50% random jitter added so
+/// concurrent callers don't retry in lockstep.
+pub fn backoff(config: &RetryConfig, attempt: u32) -> Duration {
+    let base = config
+        .initial_backoff_ms
+        .saturating_mul(1u64 << attempt.min(20))
+        .min(config.max_backoff_ms);
+    Duration::from_millis(base + jitter(base / 2))
+}
+
+/// HTTP statuses worth retrying: rate limits and server errors.
+const TRANSIENT_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+
+/// Whether an error from the provider is worth retrying: rate limits,
+/// server errors and network failures. The status of an HTTP error is used
+/// when the error still carries it; otherwise the message is inspected.
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    for cause in error.chain() {
+        if let Some(http_error) = cause.downcast_ref::<reqwest::Error>() {
+            if let Some(status) = http_error.status() {
+                return TRANSIENT_STATUSES.contains(&status.as_u16());
+            }
+            if http_error.is_timeout() || http_error.is_connect() {
+                return true;
+            }
+        }
+    }
+
+    let message = error.to_string().to_lowercase();
+    [
+        "rate limit",
+        "too many requests",
+        "internal server error",
+        "bad gateway",
+        "service unavailable",
+        "timed out",
+        "timeout",
+        "connection",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+        || mentions_transient_status(&message)
+}
+
+/// Whether `message` reports a transient status as a word of its own after
+/// `status`, `http`, `code` or `error`, as in "status 503" or "HTTP 429",
+/// so digits inside other numbers such as a dimension of 1500 don't count.
+fn mentions_transient_status(message: &str) -> bool {
+    let words: Vec<&str> = message
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    words.windows(2).any(|pair| {
+        matches!(pair[0], "status" | "http" | "code" | "error")
+            && pair[1]
+                .parse::<u16>()
+                .is_ok_and(|code| TRANSIENT_STATUSES.contains(&code))
+    })
+}
+
+/// Stops calls to a failing dependency for a cooldown period after too
+/// many consecutive failures.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            cooldown,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+            }),
+        }
+    }
+
+    /// Time left until the circuit closes, or `None` when calls may proceed.
+    pub fn open_for(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        state
+            .open_until
+            .and_then(|open_until| open_until.checked_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = 0;
+        state.open_until = None;
+    }
+
+    pub fn record_failure(&self) {
+        if self.threshold == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.threshold {
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+fn jitter(max_ms: u64) -> u64 {
+    if max_ms == 0 {
+        return 0;
+    }
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    nanos % (max_ms + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let config = RetryConfig {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1_000,
+            ..Default::default()
+        };
+
+        let first = backoff(&config, 0);
+        assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(150));
+        let third = backoff(&config, 2);
+        assert!(third >= Duration::from_millis(400) && third <= Duration::from_millis(600));
+        let capped = backoff(&config, 10);
+        assert!(capped >= Duration::from_millis(1_000) && capped <= Duration::from_millis(1_500));
+    }
+
+    #[test]
+    fn test_breaker_opens_after_threshold_and_resets_on_success() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        breaker.record_failure();
+        assert!(breaker.open_for().is_none());
+        breaker.record_failure();
+        assert!(breaker.open_for().is_some());
+
+        breaker.record_success();
+        assert!(breaker.open_for().is_none());
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(is_transient(&anyhow::anyhow!("HTTP 429 Too Many Requests")));
+        assert!(is_transient(&anyhow::anyhow!(
+            "status 503 Service Unavailable"
+        )));
+        assert!(!is_transient(&anyhow::anyhow!("invalid model name")));
+        assert!(is_transient(&anyhow::anyhow!(
+            "HTTP status server error (502 Bad Gateway) for url"
+        )));
+        // Status codes inside other numbers are not statuses
+        assert!(!is_transient(&anyhow::anyhow!(
+            "Embedding model returned 1500 dimensions, expected 768"
+        )));
+        assert!(!is_transient(&anyhow::anyhow!(
+            "input of 5040 tokens exceeds the context length"
+        )));
+    }
+}
@@ -0,0 +1,301 @@
+use crate::config::SavedSearchConfig;
+use crate::embedding_service::EmbeddingSearchService;
+use crate::embeddings::cosine_similarity;
+use crate::nostr::{NostrEvent, NostrEventWithEmbedding, normalize_pubkey};
+use anyhow::Result;
+use nostr_sdk::nips::nip19::ToBech32;
+use nostr_sdk::{Client, EventId, Keys, PublicKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Where matches of a saved search are sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Delivery {
+    /// POST a [`Notification`] as JSON to `url`
+    Webhook { url: String },
+    /// Send a NIP-17 direct message to `pubkey` (hex or npub)
+    DirectMessage { pubkey: String },
+}
+
+/// A query registered with `POST /admin/saved-searches`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SavedSearchRequest {
+    pub query: String,
+    /// Similarity a new event needs to trigger a notification; defaults
+    /// to the server's `SAVED_SEARCH_MIN_SCORE`
+    #[serde(default)]
+    pub min_score: Option<f32>,
+    /// Only match events of these kinds
+    #[serde(default)]
+    pub kinds: Option<Vec<u16>>,
+    pub delivery: Delivery,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SavedSearch {
+    pub id: String,
+    pub query: String,
+    pub min_score: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kinds: Option<Vec<u16>>,
+    pub delivery: Delivery,
+    /// Unix timestamp of registration
+    pub created_at: i64,
+}
+
+/// Body of webhook deliveries.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Notification {
+    pub saved_search_id: String,
+    pub query: String,
+    pub score: f32,
+    pub event: NostrEvent,
+}
+
+/// Persistent queries matched against every newly stored event. Searches
+/// are kept on disk; their query embeddings only in memory, recomputed on
+/// startup so they always come from the current model.
+pub struct SavedSearches {
+    db: sled::Db,
+    config: SavedSearchConfig,
+    searches: RwLock<HashMap<String, (SavedSearch, Vec<f32>)>>,
+    http_client: reqwest::Client,
+    /// Sends direct messages; `None` without `SAVED_SEARCH_NSEC`
+    dm_client: Option<Client>,
+}
+
+impl SavedSearches {
+    pub async fn open(config: SavedSearchConfig, service: &EmbeddingSearchService) -> Result<Self> {
+        let db = sled::open(&config.path).map_err(|e| {
+            anyhow::anyhow!("Failed to open saved searches at {}: {}", config.path, e)
+        })?;
+
+        let mut searches = HashMap::new();
+        for entry in db.iter() {
+            let (_, value) = entry?;
+            let Ok(search) = serde_json::from_slice::<SavedSearch>(&value) else {
+                continue;
+            };
+            let embedding = service.embed_text(&search.query).await?;
+            searches.insert(search.id.clone(), (search, embedding));
+        }
+
+        let dm_client = match &config.nsec {
+            Some(nsec) => {
+                let client = Client::new(Keys::parse(nsec)?);
+                for relay in &config.relays {
+                    client.add_relay(relay.as_str()).await?;
+                }
+                client.connect().await;
+                Some(client)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            db,
+            config,
+            searches: RwLock::new(searches),
+            http_client: reqwest::Client::new(),
+            dm_client,
+        })
+    }
+
+    /// Checks `request` and normalizes its query and recipient.
+    pub fn validate(&self, request: SavedSearchRequest) -> Result<SavedSearchRequest> {
+        let query = request.query.trim().to_string();
+        if query.is_empty() {
+            anyhow::bail!("query must not be empty");
+        }
+        if let Some(min_score) = request.min_score
+            && !(-1.0..=1.0).contains(&min_score)
+        {
+            anyhow::bail!("min_score must be between -1.0 and 1.0");
+        }
+        let delivery = match request.delivery {
+            Delivery::Webhook { url } => {
+                let parsed = url::Url::parse(&url)
+                    .map_err(|e| anyhow::anyhow!("Invalid webhook URL '{}': {}", url, e))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    anyhow::bail!("Webhook URL must use http or https");
+                }
+                Delivery::Webhook { url }
+            }
+            Delivery::DirectMessage { pubkey } => {
+                if self.dm_client.is_none() {
+                    anyhow::bail!("Direct message delivery is disabled; set SAVED_SEARCH_NSEC");
+                }
+                Delivery::DirectMessage {
+                    pubkey: normalize_pubkey(&pubkey)?,
+                }
+            }
+        };
+        Ok(SavedSearchRequest {
+            query,
+            delivery,
+            ..request
+        })
+    }
+
+    pub async fn add(
+        &self,
+        request: SavedSearchRequest,
+        service: &EmbeddingSearchService,
+    ) -> Result<SavedSearch> {
+        let request = self.validate(request)?;
+        let embedding = service.embed_text(&request.query).await?;
+        let search = SavedSearch {
+            id: self.db.generate_id()?.to_string(),
+            query: request.query,
+            min_score: request.min_score.unwrap_or(self.config.min_score),
+            kinds: request.kinds,
+            delivery: request.delivery,
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        self.db
+            .insert(search.id.as_bytes(), serde_json::to_vec(&search)?)?;
+        self.db.flush_async().await?;
+
+        self.searches
+            .write()
+            .unwrap()
+            .insert(search.id.clone(), (search.clone(), embedding));
+        Ok(search)
+    }
+
+    /// Saved searches, oldest first.
+    pub fn list(&self) -> Vec<SavedSearch> {
+        let mut searches: Vec<SavedSearch> = self
+            .searches
+            .read()
+            .unwrap()
+            .values()
+            .map(|(search, _)| search.clone())
+            .collect();
+        searches.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        searches
+    }
+
+    /// Returns whether a search with `id` existed.
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        let removed = self.db.remove(id.as_bytes())?.is_some();
+        self.searches.write().unwrap().remove(id);
+        Ok(removed)
+    }
+
+    /// Saved searches `event` matches, with the similarity of its best
+    /// matching row (chunk).
+    pub fn matches(
+        &self,
+        event: &NostrEvent,
+        rows: &[NostrEventWithEmbedding],
+    ) -> Vec<(SavedSearch, f32)> {
+        let searches = self.searches.read().unwrap();
+        searches
+            .values()
+            .filter(|(search, _)| match &search.kinds {
+                Some(kinds) => kinds.iter().any(|&kind| kind as i32 == event.kind),
+                None => true,
+            })
+            .filter_map(|(search, embedding)| {
+                let score = best_score(embedding, rows)?;
+                (score >= search.min_score).then(|| (search.clone(), score))
+            })
+            .collect()
+    }
+
+    /// Delivers a notification for every saved search `event` matches.
+    /// Failed deliveries are logged and not retried.
+    pub async fn notify_matches(&self, event: &NostrEvent, rows: &[NostrEventWithEmbedding]) {
+        for (search, score) in self.matches(event, rows) {
+            println!(
+                "Event {} matches saved search {} ({:.3})",
+                event.id, search.id, score
+            );
+            if let Err(e) = self.deliver(&search, event, score).await {
+                eprintln!(
+                    "Warning: Failed to notify saved search {}: {}",
+                    search.id, e
+                );
+            }
+        }
+    }
+
+    async fn deliver(&self, search: &SavedSearch, event: &NostrEvent, score: f32) -> Result<()> {
+        match &search.delivery {
+            Delivery::Webhook { url } => {
+                self.http_client
+                    .post(url)
+                    .timeout(Duration::from_secs(self.config.timeout_secs))
+                    .json(&Notification {
+                        saved_search_id: search.id.clone(),
+                        query: search.query.clone(),
+                        score,
+                        event: event.clone(),
+                    })
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            Delivery::DirectMessage { pubkey } => {
+                let Some(client) = &self.dm_client else {
+                    anyhow::bail!("Direct message delivery is disabled; set SAVED_SEARCH_NSEC");
+                };
+                let note = EventId::from_hex(&event.id)?.to_bech32()?;
+                let message = format!("New match for \"{}\": nostr:{}", search.query, note);
+                client
+                    .send_private_msg(PublicKey::from_hex(pubkey)?, message, [])
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Highest similarity between `embedding` and any row's content vector.
+pub fn best_score(embedding: &[f32], rows: &[NostrEventWithEmbedding]) -> Option<f32> {
+    rows.iter()
+        .map(|row| cosine_similarity(embedding, &row.content_embedding))
+        .max_by(f32::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_score_takes_closest_chunk() {
+        let row = |embedding: Vec<f32>| {
+            NostrEventWithEmbedding::new(
+                "id".to_string(),
+                "pubkey".to_string(),
+                0,
+                1,
+                Vec::new(),
+                embedding,
+            )
+        };
+        let rows = vec![row(vec![0.0, 1.0]), row(vec![1.0, 0.1])];
+
+        let score = best_score(&[1.0, 0.0], &rows).unwrap();
+        assert!(score > 0.99);
+        assert_eq!(best_score(&[1.0, 0.0], &[]), None);
+    }
+
+    #[test]
+    fn test_delivery_is_tagged_by_type() {
+        let delivery: Delivery =
+            serde_json::from_str(r#"{"type": "webhook", "url": "https://example.com/hook"}"#)
+                .unwrap();
+        assert_eq!(
+            delivery,
+            Delivery::Webhook {
+                url: "https://example.com/hook".to_string()
+            }
+        );
+    }
+}
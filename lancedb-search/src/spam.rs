@@ -0,0 +1,256 @@
+use crate::config::SpamFilterConfig;
+use crate::nostr::{NostrEvent, NostrEventWithEmbedding};
+use crate::vector_store::VectorStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Heuristic weights; an event's heuristic score is the sum of the rules it
+/// triggers, capped at 1.
+const LINKS_WEIGHT: f32 = 0.4;
+const MENTIONS_WEIGHT: f32 = 0.4;
+const HASHTAGS_WEIGHT: f32 = 0.3;
+const KEYWORD_WEIGHT: f32 = 0.5;
+const SHOUTING_WEIGHT: f32 = 0.2;
+const REPEATED_CHARS_WEIGHT: f32 = 0.2;
+
+/// Share of uppercase letters above which longer content counts as
+/// shouting.
+const SHOUTING_RATIO: f32 = 0.7;
+/// Letters content needs before shouting is judged.
+const SHOUTING_MIN_LETTERS: usize = 20;
+/// Length of a run of one repeated character that counts as filler.
+const REPEATED_CHARS_RUN: usize = 10;
+
+/// How likely an event is spam, and why.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpamVerdict {
+    /// Between 0.0 and 1.0
+    pub score: f32,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ClassifierRequest<'a> {
+    pubkey: &'a str,
+    kind: i32,
+    content: &'a str,
+    tags: &'a [Vec<String>],
+}
+
+#[derive(Deserialize)]
+struct ClassifierResponse {
+    score: f32,
+}
+
+/// Scores events before they are indexed and keeps likely spam in a
+/// quarantine table, where it stays searchable for review but out of the
+/// main index.
+pub struct SpamFilter {
+    config: SpamFilterConfig,
+    quarantine: Box<dyn VectorStore>,
+    http_client: reqwest::Client,
+}
+
+impl SpamFilter {
+    /// `quarantine` must have been opened with the text embedding
+    /// dimensions and model id.
+    pub fn new(config: SpamFilterConfig, quarantine: Box<dyn VectorStore>) -> Self {
+        Self {
+            config,
+            quarantine,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    /// The verdict for `event` when it scores at or above the threshold.
+    /// A failing classifier leaves the decision to the heuristics.
+    pub async fn check(&self, event: &NostrEvent) -> Option<SpamVerdict> {
+        let mut verdict = heuristic_verdict(event, &self.config);
+        if let Some(url) = &self.config.classifier_url {
+            match self.classify(url, event).await {
+                Ok(score) if score > verdict.score => {
+                    verdict.score = score;
+                    verdict
+                        .reasons
+                        .push(format!("classifier score {:.2}", score));
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!(
+                    "Warning: Spam classifier failed for event {}, using heuristics only: {}",
+                    event.id, e
+                ),
+            }
+        }
+        (verdict.score >= self.config.threshold).then_some(verdict)
+    }
+
+    async fn classify(&self, url: &str, event: &NostrEvent) -> Result<f32> {
+        let response: ClassifierResponse = self
+            .http_client
+            .post(url)
+            .timeout(Duration::from_secs(self.config.classifier_timeout_secs))
+            .json(&ClassifierRequest {
+                pubkey: &event.pubkey,
+                kind: event.kind,
+                content: &event.content,
+                tags: &event.tags,
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.score.clamp(0.0, 1.0))
+    }
+
+    pub async fn quarantine(&self, rows: &[NostrEventWithEmbedding]) -> Result<()> {
+        self.quarantine.insert_events(rows).await
+    }
+}
+
+/// Scores `event` with the configured heuristics.
+pub fn heuristic_verdict(event: &NostrEvent, config: &SpamFilterConfig) -> SpamVerdict {
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+    let content = event.content.as_str();
+
+    let links = content
+        .split_whitespace()
+        .filter(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .count();
+    if links > config.max_links {
+        score += LINKS_WEIGHT;
+        reasons.push(format!("{} links", links));
+    }
+
+    let mentions = event
+        .tags
+        .iter()
+        .filter(|tag| tag.first().is_some_and(|name| name == "p"))
+        .count()
+        .max(content.matches("nostr:npub").count() + content.matches("nostr:nprofile").count());
+    if mentions > config.max_mentions {
+        score += MENTIONS_WEIGHT;
+        reasons.push(format!("{} mentions", mentions));
+    }
+
+    let hashtags = event
+        .tags
+        .iter()
+        .filter(|tag| tag.first().is_some_and(|name| name == "t"))
+        .count();
+    if hashtags > config.max_hashtags {
+        score += HASHTAGS_WEIGHT;
+        reasons.push(format!("{} hashtags", hashtags));
+    }
+
+    let lowercase = content.to_lowercase();
+    for keyword in &config.keywords {
+        if lowercase.contains(&keyword.to_lowercase()) {
+            score += KEYWORD_WEIGHT;
+            reasons.push(format!("keyword '{}'", keyword));
+        }
+    }
+
+    let letters: Vec<char> = content.chars().filter(|c| c.is_alphabetic()).collect();
+    let uppercase = letters.iter().filter(|c| c.is_uppercase()).count();
+    if letters.len() >= SHOUTING_MIN_LETTERS
+        && uppercase as f32 / letters.len() as f32 > SHOUTING_RATIO
+    {
+        score += SHOUTING_WEIGHT;
+        reasons.push("mostly uppercase".to_string());
+    }
+
+    if longest_run(content) >= REPEATED_CHARS_RUN {
+        score += REPEATED_CHARS_WEIGHT;
+        reasons.push("repeated characters".to_string());
+    }
+
+    SpamVerdict {
+        score: f32::min(score, 1.0),
+        reasons,
+    }
+}
+
+/// Length of the longest run of one non-whitespace character.
+fn longest_run(content: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for c in content.chars() {
+        if c.is_whitespace() {
+            run = 0;
+            previous = None;
+            continue;
+        }
+        run = if previous == Some(c) { run + 1 } else { 1 };
+        previous = Some(c);
+        longest = longest.max(run);
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SpamFilterConfig {
+        SpamFilterConfig {
+            threshold: 0.7,
+            quarantine_table: "quarantine".to_string(),
+            max_links: 2,
+            max_mentions: 3,
+            max_hashtags: 3,
+            keywords: vec!["Free Giveaway".to_string()],
+            classifier_url: None,
+            classifier_timeout_secs: 5,
+        }
+    }
+
+    fn event(content: &str, tags: Vec<Vec<String>>) -> NostrEvent {
+        NostrEvent {
+            id: "id".to_string(),
+            pubkey: "pubkey".to_string(),
+            created_at: 0,
+            kind: 1,
+            tags,
+            content: content.to_string(),
+            sig: String::new(),
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn test_ordinary_note_scores_zero() {
+        let verdict = heuristic_verdict(
+            &event("Reading about relays today https://example.com", Vec::new()),
+            &config(),
+        );
+        assert_eq!(verdict.score, 0.0);
+        assert!(verdict.reasons.is_empty());
+    }
+
+    #[test]
+    fn test_rules_add_up() {
+        let tags = (0..5)
+            .map(|i| vec!["t".to_string(), format!("tag{}", i)])
+            .collect();
+        let verdict = heuristic_verdict(
+            &event(
+                "free giveaway!!!!!!!!!! https://a.example https://b.example https://c.example",
+                tags,
+            ),
+            &config(),
+        );
+        // links, hashtags, keyword and repeated characters
+        assert_eq!(verdict.score, 1.0);
+        assert_eq!(verdict.reasons.len(), 4);
+    }
+
+    #[test]
+    fn test_longest_run_ignores_whitespace() {
+        assert_eq!(longest_run("aaa  bb"), 3);
+        assert_eq!(longest_run("          "), 0);
+    }
+}
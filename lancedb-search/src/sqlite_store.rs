@@ -0,0 +1,705 @@
+use crate::config::{Distance, IndexConfig};
+use crate::embeddings::cosine_similarity;
+use crate::nostr::NostrEventWithEmbedding;
+use crate::vector_store::{
+    DEFAULT_MODEL_ID, ExportedRow, SearchFilters, SearchHit, TableVersion, VectorColumn,
+    VectorStore,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow,
+};
+use sqlx::{QueryBuilder, Row, Sqlite};
+use std::collections::{HashMap, HashSet};
+
+/// Columns of the events table, in insert order.
+const COLUMNS: [&str; 14] = [
+    "id",
+    "parent_id",
+    "pubkey",
+    "created_at",
+    "kind",
+    "tags",
+    "tag_values",
+    "content_embedding",
+    "summary_embedding",
+    "content",
+    "model_id",
+    "address",
+    "geohash",
+    "duplicate_of",
+];
+
+/// Columns read into a `SearchHit`, besides the distance.
+const HIT_COLUMNS: &str =
+    "parent_id, pubkey, created_at, content_embedding, summary_embedding, content";
+
+/// Rows per insert and ids per `IN (...)` list, keeping the bind
+/// parameters of one statement below SQLite's limit.
+const BATCH_SIZE: usize = 500;
+
+/// Connections in the pool; SQLite serializes writers anyway.
+const MAX_CONNECTIONS: u32 = 4;
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Embeddings are stored as little-endian `f32` blobs.
+fn to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// Distance between two embeddings, lower is closer: Euclidean for L2,
+/// and one minus the cosine similarity or dot product otherwise.
+fn vector_distance(distance: Distance, a: &[f32], b: &[f32]) -> f32 {
+    match distance {
+        Distance::L2 => a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+            .sqrt(),
+        Distance::Cosine => 1.0 - cosine_similarity(a, b),
+        Distance::Dot => 1.0 - a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>(),
+    }
+}
+
+/// Words of `query` as an FTS5 expression matching any of them.
+fn match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// Appends a `WHERE` clause for `filters`, with the same semantics as the
+/// LanceDB predicate built by `SearchFilters::to_sql`.
+fn push_filters(query: &mut QueryBuilder<'_, Sqlite>, filters: &SearchFilters) -> Result<()> {
+    if filters.version.is_some() {
+        anyhow::bail!("SQLite tables keep no versions to search");
+    }
+
+    query.push(" WHERE TRUE");
+    if !filters.authors.is_empty() {
+        query.push(" AND pubkey IN (");
+        let mut authors = query.separated(", ");
+        for author in &filters.authors {
+            authors.push_bind(author.clone());
+        }
+        query.push(")");
+    }
+    if let Some(model_id) = &filters.model_id {
+        query.push(" AND model_id = ").push_bind(model_id.clone());
+    }
+    if let Some(kind) = filters.kind {
+        query.push(" AND kind = ").push_bind(kind as i64);
+    }
+    if let Some(min_created) = filters.min_created_at {
+        query.push(" AND created_at >= ").push_bind(min_created);
+    }
+    if let Some(max_created) = filters.max_created_at {
+        query.push(" AND created_at <= ").push_bind(max_created);
+    }
+    if let Some(geohash) = &filters.geohash {
+        let geohash = geohash.to_lowercase();
+        query
+            .push(" AND substr(geohash, 1, ")
+            .push_bind(geohash.chars().count() as i64)
+            .push(") = ")
+            .push_bind(geohash);
+    }
+    if filters.exclude_duplicates {
+        query.push(" AND duplicate_of IS NULL");
+    }
+
+    let mut tag_names: Vec<&String> = filters.tags.keys().collect();
+    tag_names.sort();
+    for name in tag_names {
+        let values = &filters.tags[name];
+        if values.is_empty() {
+            continue;
+        }
+        query.push(" AND EXISTS (SELECT 1 FROM json_each(tag_values) WHERE value IN (");
+        let mut tags = query.separated(", ");
+        for value in values {
+            tags.push_bind(format!("{}:{}", name, value));
+        }
+        query.push("))");
+    }
+
+    Ok(())
+}
+
+fn hit_from_row(row: &SqliteRow, distance: f32) -> Result<SearchHit> {
+    Ok(SearchHit {
+        id: row.try_get("parent_id")?,
+        distance,
+        pubkey: row.try_get("pubkey")?,
+        created_at: row.try_get("created_at")?,
+        embedding: from_blob(&row.try_get::<Vec<u8>, _>("content_embedding")?),
+        summary_embedding: from_blob(&row.try_get::<Vec<u8>, _>("summary_embedding")?),
+        content: row.try_get("content")?,
+    })
+}
+
+/// Event store in a single SQLite file, for small deployments that don't
+/// want to run any other infrastructure. Vector searches compare the query
+/// against every row matching the filters, which stays fast up to some
+/// hundred thousand events; tables keep no version history.
+pub struct SqliteStore {
+    pool: SqlitePool,
+    table_name: String,
+    dimensions: usize,
+    index_config: IndexConfig,
+    model_id: String,
+}
+
+impl SqliteStore {
+    /// Opens the database file, creating it and the table when they don't
+    /// exist yet.
+    pub async fn open(path: &str, table_name: &str, dimensions: usize) -> Result<Self> {
+        let store = Self::connect(path, table_name, dimensions, false).await?;
+        store.create_table_if_not_exists().await?;
+        Ok(store)
+    }
+
+    /// Opens an existing database file read-only, for read replicas on the
+    /// same host as the writer.
+    pub async fn read_only(path: &str, table_name: &str, dimensions: usize) -> Result<Self> {
+        Self::connect(path, table_name, dimensions, true).await
+    }
+
+    async fn connect(
+        path: &str,
+        table_name: &str,
+        dimensions: usize,
+        read_only: bool,
+    ) -> Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(!read_only)
+            .read_only(read_only)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(MAX_CONNECTIONS)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self {
+            pool,
+            table_name: table_name.to_string(),
+            dimensions,
+            index_config: IndexConfig::default(),
+            model_id: DEFAULT_MODEL_ID.to_string(),
+        })
+    }
+
+    fn table(&self) -> String {
+        quote_identifier(&self.table_name)
+    }
+
+    fn fts_table(&self) -> String {
+        quote_identifier(&format!("{}_fts", self.table_name))
+    }
+
+    async fn create_table_if_not_exists(&self) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY,
+                parent_id TEXT NOT NULL,
+                pubkey TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                kind INTEGER NOT NULL,
+                tags TEXT NOT NULL,
+                tag_values TEXT NOT NULL,
+                content_embedding BLOB NOT NULL,
+                summary_embedding BLOB NOT NULL,
+                content TEXT,
+                model_id TEXT NOT NULL,
+                address TEXT,
+                geohash TEXT,
+                duplicate_of TEXT
+            )",
+            self.table()
+        ))
+        .execute(&self.pool)
+        .await?;
+        for column in ["parent_id", "address", "created_at"] {
+            sqlx::query(&format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                quote_identifier(&format!("{}_{}_idx", self.table_name, column)),
+                self.table(),
+                column
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+
+        self.check_dimensions().await
+    }
+
+    /// Fails early when an existing table was filled by a different
+    /// embedding model, rather than on the first search.
+    async fn check_dimensions(&self) -> Result<()> {
+        let stored: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT length(content_embedding) FROM {} LIMIT 1",
+            self.table()
+        ))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(bytes) = stored
+            && bytes as usize / 4 != self.dimensions
+        {
+            anyhow::bail!(
+                "Table '{}' stores {}-dimensional embeddings but the embedding model produces {}; use a new table or reindex",
+                self.table_name,
+                bytes / 4,
+                self.dimensions
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Rows whose `parent_id` is one of `event_ids`, read `columns` at a
+    /// time in batches.
+    async fn rows_for_events(&self, columns: &str, event_ids: &[String]) -> Result<Vec<SqliteRow>> {
+        let mut rows = Vec::new();
+        for chunk in event_ids.chunks(BATCH_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(format!(
+                "SELECT {} FROM {} WHERE parent_id IN (",
+                columns,
+                self.table()
+            ));
+            let mut ids = query.separated(", ");
+            for id in chunk {
+                ids.push_bind(id.clone());
+            }
+            query.push(")");
+            rows.extend(query.build().fetch_all(&self.pool).await?);
+        }
+        Ok(rows)
+    }
+}
+
+#[async_trait]
+impl VectorStore for SqliteStore {
+    async fn list_versions(&self) -> Result<Vec<TableVersion>> {
+        Ok(Vec::new())
+    }
+
+    async fn rollback(&self, _version: u64) -> Result<()> {
+        anyhow::bail!("SQLite tables keep no versions to roll back to")
+    }
+
+    fn set_index_config(&mut self, index_config: IndexConfig) {
+        self.index_config = index_config;
+    }
+
+    fn set_model_id(&mut self, model_id: &str) {
+        self.model_id = model_id.to_string();
+    }
+
+    async fn insert_events(&self, events: &[NostrEventWithEmbedding]) -> Result<()> {
+        // An upsert can't touch the same row twice in one statement, so
+        // keep only the last copy of each event in the batch.
+        let mut unique: Vec<&NostrEventWithEmbedding> = Vec::with_capacity(events.len());
+        for event in events {
+            match unique.iter_mut().find(|existing| existing.id == event.id) {
+                Some(existing) => *existing = event,
+                None => unique.push(event),
+            }
+        }
+
+        let updates = COLUMNS[1..]
+            .iter()
+            .map(|column| format!("{column} = excluded.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        for chunk in unique.chunks(BATCH_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(format!(
+                "INSERT INTO {} ({}) ",
+                self.table(),
+                COLUMNS.join(", ")
+            ));
+            query.push_values(chunk, |mut row, event| {
+                row.push_bind(event.id.clone())
+                    .push_bind(event.parent_id.clone())
+                    .push_bind(event.pubkey.clone())
+                    .push_bind(event.created_at)
+                    .push_bind(event.kind as i64)
+                    .push_bind(event.tags.clone())
+                    .push_bind(serde_json::to_string(&event.tag_values).unwrap_or_default())
+                    .push_bind(to_blob(&event.content_embedding))
+                    .push_bind(to_blob(&event.summary_embedding))
+                    .push_bind(event.content.clone())
+                    .push_bind(self.model_id.clone())
+                    .push_bind(event.address.clone())
+                    .push_bind(event.geohash.clone())
+                    .push_bind(event.duplicate_of.clone());
+            });
+            query.push(format!(" ON CONFLICT (id) DO UPDATE SET {}", updates));
+            query.build().execute(&self.pool).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn dedupe(&self) -> Result<usize> {
+        // `id` is the primary key, so rows are never duplicated
+        Ok(0)
+    }
+
+    async fn search_similar_with_embeddings(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filters: &SearchFilters,
+        column: VectorColumn,
+    ) -> Result<Vec<SearchHit>> {
+        let filters = filters.scoped_to_model(&self.model_id)?;
+
+        // Score every matching row on the one column, then read the full
+        // rows of the closest only
+        let mut query = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT rowid, {} FROM {}",
+            column.name(),
+            self.table()
+        ));
+        push_filters(&mut query, &filters)?;
+        let mut scored: Vec<(i64, f32)> = Vec::new();
+        {
+            let mut rows = query.build().fetch(&self.pool);
+            while let Some(row) = rows.try_next().await? {
+                let embedding = from_blob(&row.try_get::<Vec<u8>, _>(1)?);
+                let distance =
+                    vector_distance(self.index_config.distance, query_embedding, &embedding);
+                scored.push((row.try_get(0)?, distance));
+            }
+        }
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+        scored.truncate(limit);
+        if scored.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT rowid, {} FROM {} WHERE rowid IN (",
+            HIT_COLUMNS,
+            self.table()
+        ));
+        let mut rowids = query.separated(", ");
+        for (rowid, _) in &scored {
+            rowids.push_bind(*rowid);
+        }
+        query.push(")");
+        let rows: HashMap<i64, SqliteRow> = query
+            .build()
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| Ok((row.try_get(0)?, row)))
+            .collect::<Result<_>>()?;
+
+        scored
+            .iter()
+            .filter_map(|(rowid, distance)| rows.get(rowid).map(|row| (row, *distance)))
+            .map(|(row, distance)| hit_from_row(row, distance))
+            .collect()
+    }
+
+    async fn search_full_text(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>> {
+        let filters = filters.scoped_to_model(&self.model_id)?;
+        let expression = match_expression(query);
+        if expression.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let fts_table = self.fts_table();
+        let mut text_query = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT e.parent_id, e.pubkey, e.created_at, e.content_embedding, e.summary_embedding, e.content
+             FROM {} AS e JOIN {fts} ON {fts}.rowid = e.rowid",
+            self.table(),
+            fts = fts_table
+        ));
+        push_filters(&mut text_query, &filters)?;
+        text_query
+            .push(format!(" AND {} MATCH ", fts_table))
+            .push_bind(expression)
+            .push(format!(" ORDER BY bm25({}) LIMIT ", fts_table))
+            .push_bind(limit as i64);
+
+        let rows = text_query.build().fetch_all(&self.pool).await?;
+        rows.iter().map(|row| hit_from_row(row, 0.0)).collect()
+    }
+
+    async fn get_events(&self, event_ids: &[String]) -> Result<Vec<SearchHit>> {
+        self.rows_for_events(HIT_COLUMNS, event_ids)
+            .await?
+            .iter()
+            .map(|row| hit_from_row(row, 0.0))
+            .collect()
+    }
+
+    async fn get_rows(&self, event_ids: &[String]) -> Result<Vec<ExportedRow>> {
+        let results = self
+            .rows_for_events(
+                "parent_id, pubkey, kind, created_at, tags, model_id, address, content",
+                event_ids,
+            )
+            .await?;
+
+        let mut rows: HashMap<String, ExportedRow> = HashMap::new();
+        for result in results {
+            let parent_id: String = result.try_get("parent_id")?;
+            if rows.contains_key(&parent_id) {
+                continue;
+            }
+            let tags: String = result.try_get("tags")?;
+            let row = ExportedRow {
+                id: parent_id.clone(),
+                parent_id: parent_id.clone(),
+                pubkey: result.try_get("pubkey")?,
+                kind: result.try_get("kind")?,
+                created_at: result.try_get("created_at")?,
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
+                content: result.try_get("content")?,
+                model_id: result.try_get("model_id")?,
+                address: result.try_get("address")?,
+                content_embedding: None,
+                summary_embedding: None,
+            };
+            rows.insert(parent_id, row);
+        }
+        Ok(rows.into_values().collect())
+    }
+
+    async fn count_tag_values(&self, name: &str) -> Result<HashMap<String, u64>> {
+        let prefix = format!("{}:", name);
+        let rows = sqlx::query(&format!(
+            "SELECT lower(substr(tag.value, ?1)) AS tag_value, count(*) AS count
+             FROM {}, json_each(tag_values) AS tag
+             WHERE id = parent_id AND substr(tag.value, 1, ?2) = ?3
+             GROUP BY 1",
+            self.table()
+        ))
+        .bind(prefix.chars().count() as i64 + 1)
+        .bind(prefix.chars().count() as i64)
+        .bind(&prefix)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("tag_value")?,
+                    row.try_get::<i64, _>("count")? as u64,
+                ))
+            })
+            .collect()
+    }
+
+    async fn count_events(&self) -> Result<usize> {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT count(*) FROM {}", self.table()))
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn count_events_for_model(&self, model_id: &str) -> Result<usize> {
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT count(*) FROM {} WHERE model_id = ?",
+            self.table()
+        ))
+        .bind(model_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as usize)
+    }
+
+    async fn delete_older_than(&self, cutoff: i64) -> Result<usize> {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE created_at < ?",
+            self.table()
+        ))
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn delete_events_by_author(&self, event_ids: &[String], pubkey: &str) -> Result<usize> {
+        let mut deleted = 0;
+        for chunk in event_ids.chunks(BATCH_SIZE) {
+            let mut query = QueryBuilder::<Sqlite>::new(format!(
+                "DELETE FROM {} WHERE pubkey = ",
+                self.table()
+            ));
+            query
+                .push_bind(pubkey.to_string())
+                .push(" AND parent_id IN (");
+            let mut ids = query.separated(", ");
+            for id in chunk {
+                ids.push_bind(id.clone());
+            }
+            query.push(")");
+            deleted += query.build().execute(&self.pool).await?.rows_affected() as usize;
+        }
+        Ok(deleted)
+    }
+
+    async fn replace_address(
+        &self,
+        address: &str,
+        parent_id: &str,
+        created_at: i64,
+    ) -> Result<bool> {
+        let mut transaction = self.pool.begin().await?;
+
+        let newer: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (
+                SELECT 1 FROM {} WHERE address = ?1
+                AND (created_at > ?2 OR (created_at = ?2 AND parent_id < ?3))
+            )",
+            self.table()
+        ))
+        .bind(address)
+        .bind(created_at)
+        .bind(parent_id)
+        .fetch_one(&mut *transaction)
+        .await?;
+        if newer {
+            return Ok(false);
+        }
+
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE address = ? AND parent_id != ?",
+            self.table()
+        ))
+        .bind(address)
+        .bind(parent_id)
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(true)
+    }
+
+    async fn prune_per_author(&self, max_per_author: usize) -> Result<usize> {
+        // Chunks of one event share its parent_id and count once
+        let deleted: Vec<String> = sqlx::query_scalar(&format!(
+            "DELETE FROM {table} WHERE parent_id IN (
+                SELECT parent_id FROM (
+                    SELECT parent_id, row_number() OVER (PARTITION BY pubkey ORDER BY created_at DESC) AS rank
+                    FROM (SELECT DISTINCT parent_id, pubkey, created_at FROM {table}) events
+                )
+                WHERE rank > ?
+            )
+            RETURNING parent_id",
+            table = self.table()
+        ))
+        .bind(max_per_author as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(deleted.iter().collect::<HashSet<_>>().len())
+    }
+
+    async fn create_index(&self, _column: VectorColumn) -> Result<()> {
+        // Searches scan the matching rows; there is no vector index
+        Ok(())
+    }
+
+    async fn optimize(&self, _prune_older_than: chrono::Duration) -> Result<()> {
+        // There is no history to prune; reclaim space left by deleted rows
+        // and refresh the query planner's statistics
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        sqlx::query("PRAGMA optimize").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn create_fts_index(&self) -> Result<()> {
+        let table = self.table();
+        let fts = self.fts_table();
+        let trigger = |event: &str| quote_identifier(&format!("{}_fts_{}", self.table_name, event));
+
+        // An external-content FTS5 table kept in sync with `content` by
+        // triggers, rebuilt once for the rows stored before it existed
+        let statements = [
+            format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS {fts} USING fts5(content, content={table}, content_rowid='rowid')"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {} AFTER INSERT ON {table} BEGIN
+                    INSERT INTO {fts}(rowid, content) VALUES (new.rowid, new.content);
+                END",
+                trigger("insert")
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {} AFTER DELETE ON {table} BEGIN
+                    INSERT INTO {fts}({fts}, rowid, content) VALUES ('delete', old.rowid, old.content);
+                END",
+                trigger("delete")
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {} AFTER UPDATE ON {table} BEGIN
+                    INSERT INTO {fts}({fts}, rowid, content) VALUES ('delete', old.rowid, old.content);
+                    INSERT INTO {fts}(rowid, content) VALUES (new.rowid, new.content);
+                END",
+                trigger("update")
+            ),
+            format!("INSERT INTO {fts}({fts}) VALUES ('rebuild')"),
+        ];
+        for statement in statements {
+            sqlx::query(&statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embeddings_round_trip_through_blobs() {
+        let embedding = vec![0.5, -1.25, 3.0];
+        assert_eq!(from_blob(&to_blob(&embedding)), embedding);
+    }
+
+    #[test]
+    fn test_vector_distance_orders_closer_vectors_first() {
+        let query = [1.0, 0.0];
+        for distance in [Distance::L2, Distance::Cosine, Distance::Dot] {
+            let near = vector_distance(distance, &query, &[0.9, 0.1]);
+            let far = vector_distance(distance, &query, &[0.0, 1.0]);
+            assert!(near < far, "{:?}", distance);
+        }
+    }
+
+    #[test]
+    fn test_match_expression_quotes_words() {
+        assert_eq!(
+            match_expression("rust \"async\"  code"),
+            "\"rust\" OR \"\"\"async\"\"\" OR \"code\""
+        );
+        assert_eq!(match_expression("  "), "");
+    }
+}
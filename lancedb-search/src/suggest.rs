@@ -0,0 +1,133 @@
+use crate::embedding_service::EmbeddingSearchService;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+/// What a suggestion was derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionSource {
+    /// A query other users searched for
+    Query,
+    /// A hashtag used by indexed events
+    Hashtag,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Suggestion {
+    pub text: String,
+    pub source: SuggestionSource,
+    /// How often the query was searched or the hashtag used
+    pub count: u64,
+}
+
+/// Hashtag counts of the whole index, most used first. Counting scans the
+/// table, so the result is reused until it is `ttl` old.
+pub struct HashtagCache {
+    ttl: Duration,
+    cached: Mutex<Option<(Instant, Arc<Vec<(String, u64)>>)>>,
+}
+
+impl HashtagCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub async fn get(&self, service: &EmbeddingSearchService) -> Result<Arc<Vec<(String, u64)>>> {
+        let mut cached = self.cached.lock().await;
+        if let Some((counted_at, hashtags)) = cached.as_ref()
+            && counted_at.elapsed() < self.ttl
+        {
+            return Ok(hashtags.clone());
+        }
+
+        let mut hashtags: Vec<(String, u64)> =
+            service.hashtag_counts().await?.into_iter().collect();
+        hashtags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let hashtags = Arc::new(hashtags);
+        *cached = Some((Instant::now(), hashtags.clone()));
+        Ok(hashtags)
+    }
+}
+
+/// Completions of `prefix` from past queries and hashtags, most frequent
+/// first. A prefix starting with `#` only completes hashtags.
+pub fn suggestions(
+    prefix: &str,
+    queries: Vec<(String, u64)>,
+    hashtags: &[(String, u64)],
+    limit: usize,
+) -> Vec<Suggestion> {
+    let prefix = prefix.trim().to_lowercase();
+    let (tag_prefix, include_queries) = match prefix.strip_prefix('#') {
+        Some(tag_prefix) => (tag_prefix, false),
+        None => (prefix.as_str(), true),
+    };
+    if tag_prefix.is_empty() {
+        return Vec::new();
+    }
+
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    if include_queries {
+        suggestions.extend(queries.into_iter().map(|(text, count)| Suggestion {
+            text,
+            source: SuggestionSource::Query,
+            count,
+        }));
+    }
+    suggestions.extend(
+        hashtags
+            .iter()
+            .filter(|(tag, _)| tag.starts_with(tag_prefix))
+            .take(limit)
+            .map(|(tag, count)| Suggestion {
+                text: format!("#{}", tag),
+                source: SuggestionSource::Hashtag,
+                count: *count,
+            }),
+    );
+
+    // Stable, so queries stay ahead of hashtags used equally often
+    suggestions.sort_by(|a, b| b.count.cmp(&a.count));
+    suggestions.truncate(limit);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashtags() -> Vec<(String, u64)> {
+        vec![
+            ("nostr".to_string(), 50),
+            ("nostrdev".to_string(), 5),
+            ("bitcoin".to_string(), 40),
+        ]
+    }
+
+    #[test]
+    fn test_suggestions_merge_queries_and_hashtags() {
+        let queries = vec![("nostr relays".to_string(), 7)];
+        let texts: Vec<String> = suggestions("Nos", queries, &hashtags(), 10)
+            .into_iter()
+            .map(|suggestion| suggestion.text)
+            .collect();
+        assert_eq!(texts, vec!["#nostr", "nostr relays", "#nostrdev"]);
+    }
+
+    #[test]
+    fn test_hash_prefix_only_completes_hashtags() {
+        let queries = vec![("nostr relays".to_string(), 7)];
+        let found = suggestions("#nostr", queries, &hashtags(), 1);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "#nostr");
+        assert_eq!(found[0].source, SuggestionSource::Hashtag);
+        assert!(suggestions("#", Vec::new(), &hashtags(), 10).is_empty());
+    }
+}
@@ -0,0 +1,30 @@
+use crate::chat::ChatClient;
+use crate::config::SummarizationConfig;
+use anyhow::Result;
+
+/// Condenses content that is too long for the embedding model into a
+/// summary with a chat model, so the whole text is represented instead of
+/// only its beginning.
+pub struct ContentSummarizer {
+    chat: ChatClient,
+}
+
+impl ContentSummarizer {
+    pub fn new(config: SummarizationConfig) -> Self {
+        Self {
+            chat: ChatClient::new(config.api_url, config.api_key, config.model),
+        }
+    }
+
+    pub async fn summarize(&self, content: &str) -> Result<String> {
+        let prompt = format!(
+            "Summarize the following Nostr post for a semantic search index. Keep the main topics, names, places and specific details someone might search for. Reply with the summary only.\n\nPost:\n{}",
+            content
+        );
+
+        self.chat
+            .complete(&prompt, 400, 0.2)
+            .await
+            .map_err(|e| anyhow::anyhow!("Summarization failed: {}", e))
+    }
+}
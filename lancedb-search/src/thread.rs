@@ -0,0 +1,123 @@
+use crate::embedding_service::EmbeddingSearchService;
+use crate::nostr::{NostrEvent, thread_references};
+use crate::relay_search::{RelaySearcher, ResultSource};
+use crate::vector_store::ExportedRow;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// An event shown as context of a search result.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ThreadEvent {
+    pub id: String,
+    pub pubkey: String,
+    pub kind: i64,
+    pub created_at: i64,
+    /// Absent for locally found events when content storage is disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Whether the event came from the local index or a relay
+    pub source: ResultSource,
+}
+
+/// The thread a reply belongs to. A direct reply to the root has the same
+/// event as root and parent; either is absent when it couldn't be found.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ThreadContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<ThreadEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<ThreadEvent>,
+}
+
+impl From<ExportedRow> for ThreadEvent {
+    fn from(row: ExportedRow) -> Self {
+        Self {
+            id: row.parent_id,
+            pubkey: row.pubkey,
+            kind: row.kind,
+            created_at: row.created_at,
+            content: row.content,
+            source: ResultSource::Local,
+        }
+    }
+}
+
+impl From<NostrEvent> for ThreadEvent {
+    fn from(event: NostrEvent) -> Self {
+        Self {
+            id: event.id,
+            pubkey: event.pubkey,
+            kind: event.kind as i64,
+            created_at: event.created_at,
+            content: Some(event.content),
+            source: ResultSource::Relay,
+        }
+    }
+}
+
+/// Root and parent of each of `event_ids` that is a reply, keyed by the
+/// reply's id. Referenced events are looked up in the local index first
+/// and on relays otherwise; relay failures only leave context out.
+pub async fn expand_threads(
+    service: &EmbeddingSearchService,
+    relay_searcher: &RelaySearcher,
+    event_ids: &[String],
+) -> Result<HashMap<String, ThreadContext>> {
+    let references: HashMap<String, (Option<String>, Option<String>)> = service
+        .stored_events(event_ids)
+        .await?
+        .into_iter()
+        .map(|row| (row.parent_id, thread_references(&row.tags)))
+        .filter(|(_, (root, parent))| root.is_some() || parent.is_some())
+        .collect();
+    if references.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut wanted: Vec<String> = references
+        .values()
+        .flat_map(|(root, parent)| root.iter().chain(parent.iter()).cloned())
+        .collect();
+    wanted.sort();
+    wanted.dedup();
+
+    let mut found: HashMap<String, ThreadEvent> = service
+        .stored_events(&wanted)
+        .await?
+        .into_iter()
+        .map(|row| (row.parent_id.clone(), ThreadEvent::from(row)))
+        .collect();
+
+    let missing: Vec<String> = wanted
+        .into_iter()
+        .filter(|id| !found.contains_key(id))
+        .collect();
+    if !missing.is_empty() {
+        match relay_searcher.fetch_events(&missing).await {
+            Ok(events) => found.extend(
+                events
+                    .into_iter()
+                    .map(|event| (event.id.clone(), ThreadEvent::from(event))),
+            ),
+            Err(e) => eprintln!("Warning: Failed to fetch thread context from relays: {}", e),
+        }
+    }
+
+    Ok(references
+        .into_iter()
+        .map(|(id, (root, parent))| {
+            let lookup =
+                |reference: Option<String>| reference.and_then(|id| found.get(&id).cloned());
+            (
+                id,
+                ThreadContext {
+                    root: lookup(root),
+                    parent: lookup(parent),
+                },
+            )
+        })
+        .filter(|(_, context)| context.root.is_some() || context.parent.is_some())
+        .collect())
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use tiktoken_rs::CoreBPE;
+
+/// Counts tokens with the `cl100k_base` encoding used by OpenAI embedding
+/// models. For other models the count is an approximation, so leave some
+/// headroom below their real context size.
+pub struct TokenCounter {
+    bpe: CoreBPE,
+    max_tokens: usize,
+}
+
+impl TokenCounter {
+    pub fn new(max_tokens: usize) -> Result<Self> {
+        Ok(Self {
+            bpe: tiktoken_rs::cl100k_base()?,
+            max_tokens,
+        })
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    pub fn fits(&self, text: &str) -> bool {
+        self.count(text) <= self.max_tokens
+    }
+
+    /// Returns the longest prefix of `text`, cut at a character boundary,
+    /// that fits in `max_tokens`.
+    pub fn truncate<'a>(&self, text: &'a str) -> &'a str {
+        if self.fits(text) {
+            return text;
+        }
+
+        let boundaries: Vec<usize> = text
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(std::iter::once(text.len()))
+            .collect();
+
+        // Binary search for the last boundary whose prefix still fits
+        let (mut low, mut high) = (0, boundaries.len() - 1);
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if self.fits(&text[..boundaries[mid]]) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        &text[..boundaries[low]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_keeps_short_text() {
+        let counter = TokenCounter::new(100).unwrap();
+        assert_eq!(counter.truncate("hello world"), "hello world");
+    }
+
+    #[test]
+    fn test_truncate_fits_limit() {
+        let counter = TokenCounter::new(5).unwrap();
+        let text = "the quick brown fox jumps over the lazy dog ".repeat(10);
+
+        let truncated = counter.truncate(&text);
+        assert!(counter.count(truncated) <= 5);
+        assert!(!truncated.is_empty());
+        assert!(text.starts_with(truncated));
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        let counter = TokenCounter::new(3).unwrap();
+        let text = "é".repeat(50);
+        let truncated = counter.truncate(&text);
+        assert!(truncated.chars().all(|c| c == 'é'));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::nostr::NostrEventWithEmbedding;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// Events removed by NIP-09 deletions, remembered so a deleted event that
+/// was still embedding or waiting in a batch when its deletion ran isn't
+/// stored afterwards. Keyed by author, since only an event's author may
+/// delete it. Holds at most `capacity` entries and forgets the oldest
+/// beyond that.
+pub struct Tombstones {
+    capacity: usize,
+    state: Mutex<TombstoneState>,
+}
+
+#[derive(Default)]
+struct TombstoneState {
+    order: VecDeque<(String, String)>,
+    deleted: HashSet<(String, String)>,
+}
+
+impl Tombstones {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(TombstoneState::default()),
+        }
+    }
+
+    /// Records that `pubkey` deleted the events `event_ids`.
+    pub fn record(&self, pubkey: &str, event_ids: &[String]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        for event_id in event_ids {
+            let key = (pubkey.to_string(), event_id.clone());
+            if state.deleted.insert(key.clone()) {
+                state.order.push_back(key);
+            }
+        }
+        while state.order.len() > self.capacity {
+            if let Some(oldest) = state.order.pop_front() {
+                state.deleted.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn is_deleted(&self, pubkey: &str, event_id: &str) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .deleted
+            .contains(&(pubkey.to_string(), event_id.to_string()))
+    }
+
+    /// Drops the rows of deleted events, chunks included.
+    pub fn retain_live(&self, rows: &[NostrEventWithEmbedding]) -> Vec<NostrEventWithEmbedding> {
+        rows.iter()
+            .filter(|row| !self.is_deleted(&row.pubkey, &row.parent_id))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, pubkey: &str) -> NostrEventWithEmbedding {
+        NostrEventWithEmbedding::new(id.to_string(), pubkey.to_string(), 0, 1, vec![], vec![0.0])
+    }
+
+    #[test]
+    fn test_deletion_in_the_same_batch_window_drops_its_target() {
+        let tombstones = Tombstones::new(10);
+        // The deletion is applied while its target is still being embedded
+        tombstones.record("alice", &["a".to_string()]);
+
+        let batch = vec![
+            row("a", "alice").with_chunk(0),
+            row("a", "alice").with_chunk(1),
+            row("b", "alice"),
+            // Only the author can delete an event
+            row("a", "mallory"),
+        ];
+        let kept: Vec<(String, String)> = tombstones
+            .retain_live(&batch)
+            .into_iter()
+            .map(|row| (row.id, row.pubkey))
+            .collect();
+
+        assert_eq!(
+            kept,
+            vec![
+                ("b".to_string(), "alice".to_string()),
+                ("a".to_string(), "mallory".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forgets_oldest_beyond_capacity() {
+        let tombstones = Tombstones::new(2);
+        tombstones.record("alice", &["a".to_string(), "b".to_string()]);
+        tombstones.record("alice", &["c".to_string()]);
+
+        assert!(!tombstones.is_deleted("alice", "a"));
+        assert!(tombstones.is_deleted("alice", "b"));
+        assert!(tombstones.is_deleted("alice", "c"));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::config::{Config, IndexConfig, StoreBackend};
+use crate::lancedb_store::LanceDBStore;
+use crate::nostr::NostrEventWithEmbedding;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Model id written when a store is used without a model registry.
+pub const DEFAULT_MODEL_ID: &str = "default";
+
+/// Metadata filters applied to vector searches. Tag filters are keyed by
+/// single-letter tag name; values for the same tag are OR-ed together while
+/// different tags are AND-ed.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    pub authors: Vec<String>,
+    pub kind: Option<i32>,
+    pub min_created_at: Option<i64>,
+    pub max_created_at: Option<i64>,
+    pub tags: HashMap<String, Vec<String>>,
+    /// Geohash prefix the event's location must fall inside
+    pub geohash: Option<String>,
+    /// Leave out events marked as near-duplicates
+    pub exclude_duplicates: bool,
+    /// Table version to read; the latest version when unset
+    pub version: Option<u64>,
+    /// Embedding model the query vector came from; only vectors stored by
+    /// the same model are compared
+    pub model_id: Option<String>,
+}
+
+impl SearchFilters {
+    /// Restricts the filters to rows written by `model_id`, refusing
+    /// queries whose vector came from a different model.
+    pub fn scoped_to_model(&self, model_id: &str) -> Result<SearchFilters> {
+        if let Some(query_model_id) = &self.model_id
+            && query_model_id != model_id
+        {
+            anyhow::bail!(
+                "Refusing mixed-model query: the query was embedded with '{}' but the store serves '{}'",
+                query_model_id,
+                model_id
+            );
+        }
+
+        Ok(SearchFilters {
+            model_id: Some(model_id.to_string()),
+            ..self.clone()
+        })
+    }
+}
+
+/// The vector columns stored for each event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorColumn {
+    Content,
+    Summary,
+}
+
+impl VectorColumn {
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorColumn::Content => "content_embedding",
+            VectorColumn::Summary => "summary_embedding",
+        }
+    }
+}
+
+/// A committed version of the events table.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TableVersion {
+    pub version: u64,
+    /// Commit time as a unix timestamp
+    pub timestamp: i64,
+}
+
+/// A stored row as written by `export_rows`: one per event, or per chunk
+/// of a chunked event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedRow {
+    pub id: String,
+    /// Event the row belongs to; differs from `id` for chunk rows
+    pub parent_id: String,
+    pub pubkey: String,
+    pub kind: i64,
+    pub created_at: i64,
+    pub tags: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub model_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_embedding: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_embedding: Option<Vec<f32>>,
+}
+
+/// A vector search hit with the stored metadata needed for re-ranking.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub id: String,
+    pub distance: f32,
+    pub pubkey: String,
+    pub created_at: i64,
+    pub embedding: Vec<f32>,
+    pub summary_embedding: Vec<f32>,
+    pub content: Option<String>,
+}
+
+/// Storage backend for event embeddings: inserts, searches, deletions and
+/// index maintenance. The search service and HTTP server only go through
+/// this trait, so the backend is chosen by configuration in `open`.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    async fn list_versions(&self) -> Result<Vec<TableVersion>>;
+
+    /// Makes `version` the latest version again by committing a copy of it.
+    /// Newer versions stay in the history until they are pruned.
+    async fn rollback(&self, version: u64) -> Result<()>;
+
+    fn set_index_config(&mut self, index_config: IndexConfig);
+
+    /// Sets the embedding model id written with new rows and required of
+    /// every search.
+    fn set_model_id(&mut self, model_id: &str);
+
+    /// Upserts events keyed on `id`, so re-processing an event replaces its
+    /// row instead of adding a duplicate.
+    async fn insert_events(&self, events: &[NostrEventWithEmbedding]) -> Result<()>;
+
+    /// Removes duplicate rows left by inserts made before upserts were used,
+    /// keeping one row per event id. Returns the number of rows removed.
+    async fn dedupe(&self) -> Result<usize>;
+
+    /// Nearest neighbours of `query_embedding` in the given vector column,
+    /// with the stored embeddings and metadata of each hit for re-ranking
+    /// or filtering in the caller.
+    async fn search_similar_with_embeddings(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        filters: &SearchFilters,
+        column: VectorColumn,
+    ) -> Result<Vec<SearchHit>>;
+
+    /// Full-text search over the stored `content` column. Requires the FTS
+    /// index created by `create_fts_index`; hits carry no distance.
+    async fn search_full_text(
+        &self,
+        query: &str,
+        limit: usize,
+        filters: &SearchFilters,
+    ) -> Result<Vec<SearchHit>>;
+
+    /// Stored rows of the given events, chunk rows included. Hits carry the
+    /// event id and have no distance.
+    async fn get_events(&self, event_ids: &[String]) -> Result<Vec<SearchHit>>;
+
+    /// Stored rows of `event_ids`, without vectors, one per event; chunked
+    /// events are represented by one of their chunks under the event id.
+    async fn get_rows(&self, event_ids: &[String]) -> Result<Vec<ExportedRow>>;
+
+    /// Counts the events carrying each value of the single-letter tag
+    /// `name`, e.g. hashtags for `t`. Values are lowercased and chunk rows
+    /// are skipped so every event counts once.
+    async fn count_tag_values(&self, name: &str) -> Result<HashMap<String, u64>>;
+
+    async fn count_events(&self) -> Result<usize>;
+
+    /// Number of rows whose vectors were written by `model_id`, to follow a
+    /// migration between models.
+    async fn count_events_for_model(&self, model_id: &str) -> Result<usize>;
+
+    /// Deletes events created before `cutoff` (unix seconds) and returns
+    /// how many were removed.
+    async fn delete_older_than(&self, cutoff: i64) -> Result<usize>;
+
+    /// Deletes the rows, chunk rows included, of the events in `event_ids`
+    /// that were published by `pubkey`, and returns how many rows were
+    /// removed. Used for NIP-09 deletion requests, which only apply to the
+    /// requester's own events.
+    async fn delete_events_by_author(&self, event_ids: &[String], pubkey: &str) -> Result<usize>;
+
+    /// Makes `parent_id` the stored version of a replaceable or addressable
+    /// event: returns `false` when a newer version is already stored, and
+    /// otherwise deletes the rows of older versions so only this one
+    /// remains. Ties on `created_at` go to the lowest id, as in NIP-01.
+    async fn replace_address(
+        &self,
+        address: &str,
+        parent_id: &str,
+        created_at: i64,
+    ) -> Result<bool>;
+
+    /// Keeps only the newest `max_per_author` events of each author and
+    /// returns how many were removed.
+    async fn prune_per_author(&self, max_per_author: usize) -> Result<usize>;
+
+    /// Builds the configured vector index over one embedding column.
+    async fn create_index(&self, column: VectorColumn) -> Result<()>;
+
+    /// Compacts small fragments, removes table versions older than
+    /// `prune_older_than` and folds newly added rows into the indexes.
+    async fn optimize(&self, prune_older_than: chrono::Duration) -> Result<()>;
+
+    /// Creates the full-text index on `content` used by hybrid search.
+    async fn create_fts_index(&self) -> Result<()>;
+}
+
+/// Opens the store for `table_name` in the configured backend, connecting
+/// without creating or migrating the table when `config.read_only` is set.
+/// Rows are written and searched under `model_id`.
+pub async fn open(
+    config: &Config,
+    table_name: &str,
+    dimensions: usize,
+    model_id: &str,
+) -> Result<Box<dyn VectorStore>> {
+    let mut store: Box<dyn VectorStore> = match config.store_backend {
+        StoreBackend::LanceDb => {
+            let store = if config.read_only {
+                LanceDBStore::read_only(
+                    &config.db_path,
+                    table_name,
+                    dimensions,
+                    &config.storage_options,
+                )
+                .await?
+            } else {
+                LanceDBStore::with_storage_options(
+                    &config.db_path,
+                    table_name,
+                    dimensions,
+                    model_id,
+                    &config.storage_options,
+                )
+                .await?
+            };
+            Box::new(store)
+        }
+        StoreBackend::Pgvector => open_pgvector(config, table_name, dimensions).await?,
+        StoreBackend::Sqlite => open_sqlite(config, table_name, dimensions).await?,
+    };
+    store.set_model_id(model_id);
+    Ok(store)
+}
+
+#[cfg(feature = "pgvector")]
+async fn open_pgvector(
+    config: &Config,
+    table_name: &str,
+    dimensions: usize,
+) -> Result<Box<dyn VectorStore>> {
+    use crate::pgvector_store::PgvectorStore;
+
+    let Some(pgvector) = &config.pgvector else {
+        anyhow::bail!("STORE_BACKEND=pgvector needs PGVECTOR_URL");
+    };
+    let store = if config.read_only {
+        PgvectorStore::read_only(pgvector, table_name, dimensions).await?
+    } else {
+        PgvectorStore::connect(pgvector, table_name, dimensions).await?
+    };
+    Ok(Box::new(store))
+}
+
+#[cfg(not(feature = "pgvector"))]
+async fn open_pgvector(
+    _config: &Config,
+    _table_name: &str,
+    _dimensions: usize,
+) -> Result<Box<dyn VectorStore>> {
+    anyhow::bail!("The pgvector store requires building lancedb-search with the `pgvector` feature")
+}
+
+#[cfg(feature = "sqlite")]
+async fn open_sqlite(
+    config: &Config,
+    table_name: &str,
+    dimensions: usize,
+) -> Result<Box<dyn VectorStore>> {
+    use crate::sqlite_store::SqliteStore;
+
+    let store = if config.read_only {
+        SqliteStore::read_only(&config.sqlite_path, table_name, dimensions).await?
+    } else {
+        SqliteStore::open(&config.sqlite_path, table_name, dimensions).await?
+    };
+    Ok(Box::new(store))
+}
+
+#[cfg(not(feature = "sqlite"))]
+async fn open_sqlite(
+    _config: &Config,
+    _table_name: &str,
+    _dimensions: usize,
+) -> Result<Box<dyn VectorStore>> {
+    anyhow::bail!("The SQLite store requires building lancedb-search with the `sqlite` feature")
+}
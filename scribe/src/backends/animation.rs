@@ -0,0 +1,109 @@
+use anyhow::Result;
+use image::AnimationDecoder;
+use std::io::Cursor;
+use tracing::{info, warn};
+
+/// Frames sampled from an animated image, spread evenly over the animation
+const ANIMATION_FRAMES: usize = 4;
+
+const DESCRIBE_PROMPT: &str =
+    "Describe this image in detail. Include objects, people, text, colors, and scene context.";
+
+const DESCRIBE_ANIMATION_PROMPT: &str = "These images are frames sampled in order from one animated image, such as a GIF meme. Describe the animation as a whole, starting with \"Animated image showing\": what happens over time, objects, people, text, colors, and scene context.";
+
+/// What to send a vision model to describe an image: the prompt and the
+/// images as `(mime type, bytes)`.
+pub struct ImageInput {
+    pub prompt: &'static str,
+    pub images: Vec<(String, Vec<u8>)>,
+}
+
+/// Animated GIFs and WebPs are sent as a few frames with a prompt asking
+/// for the animation as a whole, since vision APIs only look at the first
+/// frame or reject them. Everything else, and animations that fail to
+/// decode, are sent as is.
+pub async fn prepare_image_input(url: &str, bytes: Vec<u8>, mime_type: &str) -> ImageInput {
+    let url_lower = url.to_lowercase();
+    let is_gif = url_lower.contains(".gif");
+    if !is_gif && !url_lower.contains(".webp") {
+        return still_image(bytes, mime_type);
+    }
+
+    let frames = {
+        let bytes = bytes.clone();
+        tokio::task::spawn_blocking(move || sample_frames(&bytes, is_gif))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|frames| frames)
+    };
+    match frames {
+        Ok(Some(frames)) => {
+            info!("Describing animated image from {} frames", frames.len());
+            ImageInput {
+                prompt: DESCRIBE_ANIMATION_PROMPT,
+                images: frames
+                    .into_iter()
+                    .map(|frame| ("image/png".to_string(), frame))
+                    .collect(),
+            }
+        }
+        Ok(None) => still_image(bytes, mime_type),
+        Err(e) => {
+            warn!("Failed to sample animation frames from {}: {}", url, e);
+            still_image(bytes, mime_type)
+        }
+    }
+}
+
+fn still_image(bytes: Vec<u8>, mime_type: &str) -> ImageInput {
+    ImageInput {
+        prompt: DESCRIBE_PROMPT,
+        images: vec![(mime_type.to_string(), bytes)],
+    }
+}
+
+/// Decodes the animation twice, once to count its frames and once to
+/// encode the sampled ones as PNG, so only those are held in memory.
+/// Returns `None` for images with a single frame.
+fn sample_frames(bytes: &[u8], is_gif: bool) -> Result<Option<Vec<Vec<u8>>>> {
+    let Some(frames) = decode_frames(bytes, is_gif)? else {
+        return Ok(None);
+    };
+    let frame_count = frames.count();
+    if frame_count <= 1 {
+        return Ok(None);
+    }
+
+    let sampled: Vec<usize> = (0..ANIMATION_FRAMES.min(frame_count))
+        .map(|i| i * frame_count / ANIMATION_FRAMES.min(frame_count))
+        .collect();
+
+    let mut encoded = Vec::with_capacity(sampled.len());
+    let frames = decode_frames(bytes, is_gif)?.into_iter().flatten();
+    for (index, frame) in frames.enumerate() {
+        if !sampled.contains(&index) {
+            continue;
+        }
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(frame?.into_buffer())
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+        encoded.push(png);
+    }
+
+    Ok(Some(encoded))
+}
+
+/// Returns `None` for still WebPs, which share the extension with animated
+/// ones.
+fn decode_frames(bytes: &[u8], is_gif: bool) -> Result<Option<image::Frames<'_>>> {
+    if is_gif {
+        let decoder = image::codecs::gif::GifDecoder::new(Cursor::new(bytes))?;
+        return Ok(Some(decoder.into_frames()));
+    }
+
+    let decoder = image::codecs::webp::WebPDecoder::new(Cursor::new(bytes))?;
+    if !decoder.has_animation() {
+        return Ok(None);
+    }
+    Ok(Some(decoder.into_frames()))
+}
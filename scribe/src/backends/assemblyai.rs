@@ -0,0 +1,273 @@
+use crate::processor::{
+    Chapter, FileType, ProcessedContent, Processor, Segment, Word, generate_summary,
+    get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{debug, info};
+
+const ASSEMBLYAI_API_URL: &str = "https://api.assemblyai.com/v2";
+
+/// How often a queued transcript is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Give up on transcripts that take longer than this to finish
+const MAX_WAIT: Duration = Duration::from_secs(30 * 60);
+
+pub struct AssemblyAIBackend {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl AssemblyAIBackend {
+    pub fn new(api_key: String) -> Self {
+        info!("Initializing AssemblyAI backend");
+
+        Self {
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Uploads a local file to AssemblyAI's storage and returns the URL
+    /// transcripts can read it from.
+    async fn upload(&self, file_path: &str) -> Result<String> {
+        info!("AssemblyAI: Uploading local file: {}", file_path);
+        let file = tokio::fs::File::open(file_path).await?;
+
+        let response = self
+            .client
+            .post(format!("{}/upload", ASSEMBLYAI_API_URL))
+            .header("Authorization", &self.api_key)
+            .body(reqwest::Body::from(file))
+            .send()
+            .await?;
+        let upload: UploadResponse = self.parse_response(response).await?;
+        Ok(upload.upload_url)
+    }
+
+    /// Queues a transcript with speaker labels and chapters, then polls it
+    /// until AssemblyAI has finished.
+    async fn transcribe(&self, url: &str) -> Result<TranscriptResponse> {
+        let audio_url = match url.strip_prefix("file://") {
+            Some(file_path) => self.upload(file_path).await?,
+            None => url.to_string(),
+        };
+
+        info!("AssemblyAI: Requesting transcript for: {}", audio_url);
+        let response = self
+            .client
+            .post(format!("{}/transcript", ASSEMBLYAI_API_URL))
+            .header("Authorization", &self.api_key)
+            .json(&serde_json::json!({
+                "audio_url": audio_url,
+                "speaker_labels": true,
+                "auto_chapters": true,
+                "language_detection": true,
+            }))
+            .send()
+            .await?;
+        let mut transcript: TranscriptResponse = self.parse_response(response).await?;
+
+        let started = std::time::Instant::now();
+        loop {
+            match transcript.status.as_str() {
+                "completed" => return Ok(transcript),
+                "error" => {
+                    return Err(anyhow::anyhow!(
+                        "AssemblyAI transcription failed: {}",
+                        transcript.error.unwrap_or_default()
+                    ));
+                }
+                status => {
+                    if started.elapsed() > MAX_WAIT {
+                        return Err(anyhow::anyhow!(
+                            "AssemblyAI transcript {} still {} after {}s",
+                            transcript.id,
+                            status,
+                            MAX_WAIT.as_secs()
+                        ));
+                    }
+                    debug!("AssemblyAI: Transcript {} is {}", transcript.id, status);
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let response = self
+                .client
+                .get(format!(
+                    "{}/transcript/{}",
+                    ASSEMBLYAI_API_URL, transcript.id
+                ))
+                .header("Authorization", &self.api_key)
+                .send()
+                .await?;
+            transcript = self.parse_response(response).await?;
+        }
+    }
+
+    async fn parse_response<T: serde::de::DeserializeOwned>(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<T> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "AssemblyAI API error ({}): {}",
+                status,
+                error_text
+            ));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl Processor for AssemblyAIBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with AssemblyAI: {}", url);
+
+        match file_type {
+            FileType::Audio | FileType::Video => {
+                let transcript = self.transcribe(url).await?;
+                let text = transcript.text.unwrap_or_default();
+                info!("AssemblyAI: Transcript ready, {} characters", text.len());
+
+                let words = transcript
+                    .words
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|word| Word {
+                        word: word.text,
+                        start_ms: word.start,
+                        end_ms: word.end,
+                    })
+                    .collect();
+                let segments = transcript
+                    .utterances
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|utterance| Segment {
+                        speaker: utterance.speaker,
+                        start_ms: utterance.start,
+                        end_ms: utterance.end,
+                        text: utterance.text,
+                    })
+                    .collect();
+                let chapters: Vec<Chapter> = transcript
+                    .chapters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|chapter| Chapter {
+                        headline: chapter.headline,
+                        summary: chapter.summary,
+                        start_ms: chapter.start,
+                        end_ms: chapter.end,
+                    })
+                    .collect();
+
+                // Generate summary if OpenAI API key is available, otherwise
+                // use the chapter summaries
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_summary(&text, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for transcription");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+                let summary = summary.or_else(|| {
+                    (!chapters.is_empty()).then(|| {
+                        chapters
+                            .iter()
+                            .map(|chapter| chapter.summary.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                });
+
+                Ok(ProcessedContent::Transcript {
+                    text,
+                    language: transcript.language_code,
+                    duration_ms: transcript
+                        .audio_duration
+                        .map(|seconds| (seconds * 1000.0).round() as u64),
+                    summary,
+                    words,
+                    segments,
+                    chapters,
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Image => Err(anyhow::anyhow!(
+                "AssemblyAI backend cannot process images. Use the vision or OpenAI backend instead."
+            )),
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "AssemblyAI backend cannot process YouTube URLs directly. Use the YouTube backend instead."
+            )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "AssemblyAI backend cannot process documents. Use the document backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "assemblyai"
+    }
+}
+
+#[derive(Deserialize)]
+struct UploadResponse {
+    upload_url: String,
+}
+
+#[derive(Deserialize)]
+struct TranscriptResponse {
+    id: String,
+    status: String,
+    error: Option<String>,
+    text: Option<String>,
+    language_code: Option<String>,
+    /// Length of the audio in seconds
+    audio_duration: Option<f64>,
+    words: Option<Vec<AssemblyAIWord>>,
+    utterances: Option<Vec<Utterance>>,
+    chapters: Option<Vec<AssemblyAIChapter>>,
+}
+
+/// Times are in milliseconds.
+#[derive(Deserialize)]
+struct AssemblyAIWord {
+    text: String,
+    start: u64,
+    end: u64,
+}
+
+#[derive(Deserialize)]
+struct Utterance {
+    speaker: Option<String>,
+    text: String,
+    start: u64,
+    end: u64,
+}
+
+#[derive(Deserialize)]
+struct AssemblyAIChapter {
+    headline: String,
+    summary: String,
+    start: u64,
+    end: u64,
+}
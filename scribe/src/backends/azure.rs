@@ -0,0 +1,217 @@
+use crate::processor::{
+    FileType, ProcessedContent, Processor, Word, generate_summary, get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+const API_VERSION: &str = "2024-11-15";
+
+/// Azure AI Speech transcription. Uses the fast transcription REST API,
+/// which recognizes a whole file in one request: unlike the short-audio
+/// endpoint it isn't limited to 60 seconds, so it covers the long files
+/// the Speech SDK would handle with continuous recognition.
+pub struct AzureBackend {
+    api_key: String,
+    region: String,
+    locales: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl AzureBackend {
+    pub fn new(api_key: String, region: String, locales: Vec<String>) -> Self {
+        info!("Initializing Azure Speech backend:");
+        info!("  Region: {}", region);
+        info!("  Locales: {}", locales.join(", "));
+
+        Self {
+            api_key,
+            region,
+            locales,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn transcribe(&self, url: &str) -> Result<TranscriptionResponse> {
+        info!("Azure: Transcribing audio from URL: {}", url);
+        let file_bytes = self.download_file(url).await?;
+        info!("Azure: File downloaded, size: {} bytes", file_bytes.len());
+
+        // With several locales Azure identifies the spoken language
+        let definition = serde_json::json!({ "locales": self.locales });
+        let form = reqwest::multipart::Form::new()
+            .text("definition", definition.to_string())
+            .part(
+                "audio",
+                reqwest::multipart::Part::bytes(file_bytes)
+                    .file_name(self.extract_filename_from_url(url)),
+            );
+
+        info!("Azure: Sending request to fast transcription API");
+        let response = self
+            .client
+            .post(format!(
+                "https://{}.api.cognitive.microsoft.com/speechtotext/transcriptions:transcribe",
+                self.region
+            ))
+            .query(&[("api-version", API_VERSION)])
+            .header("Ocp-Apim-Subscription-Key", &self.api_key)
+            .multipart(form)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "Azure Speech API error ({}): {}",
+                status,
+                error_text
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        info!("Getting file from URL: {}", url);
+
+        if let Some(file_path) = url.strip_prefix("file://") {
+            Ok(tokio::fs::read(file_path).await?)
+        } else {
+            let response = self.client.get(url).send().await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!(
+                    "Failed to download file: HTTP {}",
+                    response.status()
+                ));
+            }
+
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+
+    fn extract_filename_from_url(&self, url: &str) -> String {
+        if let Ok(parsed_url) = url::Url::parse(url)
+            && let Some(mut path) = parsed_url.path_segments()
+            && let Some(filename) = path.next_back()
+            && !filename.is_empty()
+        {
+            return filename.to_string();
+        }
+        "file".to_string()
+    }
+}
+
+#[async_trait]
+impl Processor for AzureBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with Azure Speech: {}", url);
+
+        match file_type {
+            FileType::Audio | FileType::Video => {
+                let transcription = self.transcribe(url).await?;
+
+                let text = transcription
+                    .combined_phrases
+                    .iter()
+                    .map(|phrase| phrase.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                info!("Azure: Transcript ready, {} characters", text.len());
+
+                let language = transcription
+                    .phrases
+                    .iter()
+                    .find_map(|phrase| phrase.locale.clone());
+                let words = transcription
+                    .phrases
+                    .into_iter()
+                    .flat_map(|phrase| phrase.words)
+                    .map(|word| Word {
+                        word: word.text,
+                        start_ms: word.offset_milliseconds,
+                        end_ms: word.offset_milliseconds + word.duration_milliseconds,
+                    })
+                    .collect();
+
+                // Generate summary if OpenAI API key is available
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_summary(&text, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for transcription");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    info!("No OPENAI_API_KEY found, skipping summary generation");
+                    None
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text,
+                    language,
+                    duration_ms: transcription.duration_milliseconds,
+                    summary,
+                    words,
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Image => Err(anyhow::anyhow!(
+                "Azure Speech backend cannot process images. Use the vision or OpenAI backend instead."
+            )),
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "Azure Speech backend cannot process YouTube URLs directly. Use the YouTube backend instead."
+            )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "Azure Speech backend cannot process documents. Use the document backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "azure"
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptionResponse {
+    duration_milliseconds: Option<u64>,
+    #[serde(default)]
+    combined_phrases: Vec<CombinedPhrase>,
+    #[serde(default)]
+    phrases: Vec<Phrase>,
+}
+
+#[derive(Deserialize)]
+struct CombinedPhrase {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Phrase {
+    locale: Option<String>,
+    #[serde(default)]
+    words: Vec<PhraseWord>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhraseWord {
+    text: String,
+    offset_milliseconds: u64,
+    duration_milliseconds: u64,
+}
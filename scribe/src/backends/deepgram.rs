@@ -0,0 +1,213 @@
+use crate::processor::{
+    FileType, ProcessedContent, Processor, Word, generate_summary, get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+const DEEPGRAM_API_URL: &str = "https://api.deepgram.com/v1/listen";
+
+pub struct DeepgramBackend {
+    api_key: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl DeepgramBackend {
+    pub fn new(api_key: String, model: String) -> Self {
+        info!("Initializing Deepgram backend with model: {}", model);
+
+        Self {
+            api_key,
+            model,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sends the audio to Deepgram's pre-recorded API. Remote URLs are
+    /// fetched by Deepgram itself; local files are streamed from disk
+    /// rather than read into memory.
+    async fn transcribe(&self, url: &str) -> Result<DeepgramResponse> {
+        let request = self
+            .client
+            .post(DEEPGRAM_API_URL)
+            .header("Authorization", format!("Token {}", self.api_key))
+            .query(&[
+                ("model", self.model.as_str()),
+                ("smart_format", "true"),
+                ("detect_language", "true"),
+            ]);
+
+        let request = if let Some(file_path) = url.strip_prefix("file://") {
+            info!("Deepgram: Streaming local file: {}", file_path);
+            let file = tokio::fs::File::open(file_path).await?;
+            request
+                .header("Content-Type", mime_type_from_url(url))
+                .body(reqwest::Body::from(file))
+        } else {
+            info!("Deepgram: Sending URL for transcription: {}", url);
+            request.json(&serde_json::json!({ "url": url }))
+        };
+
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "Deepgram API error ({}): {}",
+                status,
+                error_text
+            ));
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+/// Content type of a local media file, from its extension.
+fn mime_type_from_url(url: &str) -> &'static str {
+    let url_lower = url.to_lowercase();
+    if url_lower.ends_with(".mp3") {
+        "audio/mpeg"
+    } else if url_lower.ends_with(".wav") {
+        "audio/wav"
+    } else if url_lower.ends_with(".flac") {
+        "audio/flac"
+    } else if url_lower.ends_with(".ogg") {
+        "audio/ogg"
+    } else if url_lower.ends_with(".m4a") || url_lower.ends_with(".aac") {
+        "audio/mp4"
+    } else if url_lower.ends_with(".webm") {
+        "audio/webm"
+    } else if url_lower.ends_with(".mp4") || url_lower.ends_with(".m4v") {
+        "video/mp4"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+fn seconds_to_ms(seconds: f64) -> u64 {
+    (seconds * 1000.0).round() as u64
+}
+
+#[async_trait]
+impl Processor for DeepgramBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with Deepgram: {}", url);
+
+        match file_type {
+            FileType::Audio | FileType::Video => {
+                let response = self.transcribe(url).await?;
+
+                let channel = response
+                    .results
+                    .channels
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Deepgram response has no channels"))?;
+                let alternative = channel
+                    .alternatives
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("Deepgram response has no transcript"))?;
+                info!(
+                    "Deepgram: Transcript ready, {} characters",
+                    alternative.transcript.len()
+                );
+
+                let words = alternative
+                    .words
+                    .into_iter()
+                    .map(|word| Word {
+                        word: word.punctuated_word.unwrap_or(word.word),
+                        start_ms: seconds_to_ms(word.start),
+                        end_ms: seconds_to_ms(word.end),
+                    })
+                    .collect();
+
+                // Generate summary if OpenAI API key is available
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_summary(&alternative.transcript, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for transcription");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    info!("No OPENAI_API_KEY found, skipping summary generation");
+                    None
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text: alternative.transcript,
+                    language: channel.detected_language,
+                    duration_ms: response.metadata.duration.map(seconds_to_ms),
+                    summary,
+                    words,
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Image => Err(anyhow::anyhow!(
+                "Deepgram backend cannot process images. Use the vision or OpenAI backend instead."
+            )),
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "Deepgram backend cannot process YouTube URLs directly. Use the YouTube backend instead."
+            )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "Deepgram backend cannot process documents. Use the document backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "deepgram"
+    }
+}
+
+#[derive(Deserialize)]
+struct DeepgramResponse {
+    metadata: DeepgramMetadata,
+    results: DeepgramResults,
+}
+
+#[derive(Deserialize)]
+struct DeepgramMetadata {
+    duration: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramResults {
+    channels: Vec<DeepgramChannel>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramChannel {
+    detected_language: Option<String>,
+    alternatives: Vec<DeepgramAlternative>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramAlternative {
+    transcript: String,
+    #[serde(default)]
+    words: Vec<DeepgramWord>,
+}
+
+#[derive(Deserialize)]
+struct DeepgramWord {
+    word: String,
+    punctuated_word: Option<String>,
+    start: f64,
+    end: f64,
+}
@@ -0,0 +1,225 @@
+use super::pdf::PdfBackend;
+use crate::processor::{
+    FileType, Page, ProcessedContent, Processor, generate_document_summary, get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+use std::io::Read;
+use tracing::{debug, info};
+
+/// Long-form documents linked from notes: plain text and markdown are
+/// read as is, ePubs section by section in reading order, and PDFs are
+/// handed to the PDF backend. Long texts are summarized chunk by chunk.
+pub struct DocumentBackend {
+    pdf: PdfBackend,
+    client: reqwest::Client,
+}
+
+impl DocumentBackend {
+    /// `ocr_languages` are the tesseract language codes used for scanned
+    /// PDF pages.
+    pub fn new(ocr_languages: String) -> Self {
+        info!("Initializing document backend");
+
+        Self {
+            pdf: PdfBackend::new(ocr_languages),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        info!("Getting file from URL: {}", url);
+
+        if let Some(file_path) = url.strip_prefix("file://") {
+            Ok(tokio::fs::read(file_path).await?)
+        } else {
+            let response = self.client.get(url).send().await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!(
+                    "Failed to download file: HTTP {}",
+                    response.status()
+                ));
+            }
+
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+}
+
+/// Extracts the text of an ePub's sections in spine (reading) order,
+/// skipping sections without text such as covers.
+fn extract_epub_sections(bytes: &[u8]) -> Result<Vec<Page>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+
+    let container = read_zip_entry(&mut archive, "META-INF/container.xml")?;
+    let opf_path = capture(r#"full-path\s*=\s*"([^"]+)""#, &container)
+        .ok_or_else(|| anyhow::anyhow!("ePub container does not name a package file"))?;
+    let opf = read_zip_entry(&mut archive, &opf_path)?;
+
+    // Manifest hrefs are relative to the package file
+    let base_dir = match opf_path.rfind('/') {
+        Some(pos) => &opf_path[..=pos],
+        None => "",
+    };
+
+    let item_re = Regex::new(r"(?s)<item\b[^>]*>")?;
+    let manifest: Vec<(String, String)> = item_re
+        .find_iter(&opf)
+        .filter_map(|item| {
+            let id = capture(r#"\bid\s*=\s*"([^"]*)""#, item.as_str())?;
+            let href = capture(r#"\bhref\s*=\s*"([^"]*)""#, item.as_str())?;
+            Some((id, href))
+        })
+        .collect();
+
+    let itemref_re = Regex::new(r#"(?s)<itemref\b[^>]*\bidref\s*=\s*"([^"]*)""#)?;
+    let mut pages = Vec::new();
+    for itemref in itemref_re.captures_iter(&opf) {
+        let Some((_, href)) = manifest.iter().find(|(id, _)| *id == itemref[1]) else {
+            continue;
+        };
+        let path = format!("{}{}", base_dir, href.replace("%20", " "));
+        let text = html_to_text(&read_zip_entry(&mut archive, &path)?);
+        if !text.is_empty() {
+            pages.push(Page {
+                number: pages.len() as u32 + 1,
+                text,
+                ocr: false,
+            });
+        }
+    }
+
+    Ok(pages)
+}
+
+fn read_zip_entry(
+    archive: &mut zip::ZipArchive<std::io::Cursor<&[u8]>>,
+    name: &str,
+) -> Result<String> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| anyhow::anyhow!("ePub is missing {}: {}", name, e))?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+fn capture(pattern: &str, text: &str) -> Option<String> {
+    Regex::new(pattern)
+        .ok()?
+        .captures(text)
+        .map(|captures| captures[1].to_string())
+}
+
+/// Reduces an XHTML section to its text, keeping block elements on their
+/// own lines.
+fn html_to_text(html: &str) -> String {
+    let hidden = Regex::new(r"(?is)<(head|script|style)\b.*?</(head|script|style)>").unwrap();
+    let blocks = Regex::new(r"(?i)<br\s*/?>|</(p|div|h[1-6]|li|tr|blockquote|section)>").unwrap();
+    let tags = Regex::new(r"(?s)<[^>]*>").unwrap();
+
+    let text = hidden.replace_all(html, "");
+    let text = blocks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[async_trait]
+impl Processor for DocumentBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with document backend: {}", url);
+
+        match file_type {
+            FileType::Document => {
+                let url_lower = url.to_lowercase();
+                if url_lower.contains(".pdf") {
+                    return self.pdf.process(url).await;
+                }
+
+                info!("Document: Extracting text from URL: {}", url);
+                let bytes = self.download_file(url).await?;
+                info!("Document: File downloaded, size: {} bytes", bytes.len());
+
+                let (text, pages) = if url_lower.contains(".epub") {
+                    let pages = extract_epub_sections(&bytes)?;
+                    info!("Document: ePub has {} sections", pages.len());
+                    let text = pages
+                        .iter()
+                        .map(|page| page.text.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n\n");
+                    (text, pages)
+                } else {
+                    // Plain text and markdown are indexed as written
+                    let text = String::from_utf8_lossy(&bytes).trim().to_string();
+                    (text, Vec::new())
+                };
+                info!("Document: Text ready, {} characters", text.len());
+
+                if text.is_empty() {
+                    return Err(anyhow::anyhow!("No text found in document: {}", url));
+                }
+
+                // Generate summary if OpenAI API key is available
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_document_summary(&text, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for document");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    info!("No OPENAI_API_KEY found, skipping summary generation");
+                    None
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text,
+                    language: None,
+                    duration_ms: None,
+                    summary,
+                    words: Vec::new(),
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages,
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Audio | FileType::Video => Err(anyhow::anyhow!(
+                "Document backend cannot process audio or video. Use the whisper or OpenAI backend instead."
+            )),
+            FileType::Image => Err(anyhow::anyhow!(
+                "Document backend cannot process images. Use the vision or OpenAI backend instead."
+            )),
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "Document backend cannot process YouTube URLs. Use the YouTube backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "document"
+    }
+}
@@ -0,0 +1,138 @@
+use super::openai::transcribe_with_openai_api;
+use crate::processor::{
+    FileType, ProcessedContent, Processor, generate_summary, get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::{debug, info};
+
+/// Whisper hosted on Groq's OpenAI-compatible API, for fast cloud
+/// transcription.
+pub struct GroqBackend {
+    api_key: String,
+    api_url: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl GroqBackend {
+    pub fn new(api_key: String, api_url: String, model: String) -> Self {
+        info!("Initializing Groq backend:");
+        info!("  API URL: {}", api_url);
+        info!("  Model: {}", model);
+
+        Self {
+            api_key,
+            api_url,
+            model,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        info!("Getting file from URL: {}", url);
+
+        if let Some(file_path) = url.strip_prefix("file://") {
+            Ok(tokio::fs::read(file_path).await?)
+        } else {
+            let response = self.client.get(url).send().await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!(
+                    "Failed to download file: HTTP {}",
+                    response.status()
+                ));
+            }
+
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+
+    fn extract_filename_from_url(&self, url: &str) -> String {
+        if let Ok(parsed_url) = url::Url::parse(url)
+            && let Some(mut path) = parsed_url.path_segments()
+            && let Some(filename) = path.next_back()
+            && !filename.is_empty()
+        {
+            return filename.to_string();
+        }
+        "file".to_string()
+    }
+}
+
+#[async_trait]
+impl Processor for GroqBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with Groq: {}", url);
+
+        match file_type {
+            FileType::Audio | FileType::Video => {
+                info!("Groq: Transcribing audio from URL: {}", url);
+                let file_bytes = self.download_file(url).await?;
+                info!("Groq: File downloaded, size: {} bytes", file_bytes.len());
+
+                let mut transcription = transcribe_with_openai_api(
+                    &self.client,
+                    &self.api_url,
+                    &self.api_key,
+                    &self.model,
+                    file_bytes,
+                    self.extract_filename_from_url(url),
+                )
+                .await?;
+                info!(
+                    "Groq: Transcript ready, {} characters",
+                    transcription.text.len()
+                );
+                let words = transcription.timed_words();
+
+                // Generate summary if OpenAI API key is available
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_summary(&transcription.text, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for transcription");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    info!("No OPENAI_API_KEY found, skipping summary generation");
+                    None
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text: transcription.text,
+                    language: transcription.language,
+                    duration_ms: transcription
+                        .duration
+                        .map(|seconds| (seconds * 1000.0).round() as u64),
+                    summary,
+                    words,
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Image => Err(anyhow::anyhow!(
+                "Groq backend cannot process images. Use the vision or OpenAI backend instead."
+            )),
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "Groq backend cannot process YouTube URLs directly. Use the YouTube backend instead."
+            )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "Groq backend cannot process documents. Use the document backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "groq"
+    }
+}
@@ -1,5 +1,16 @@
+mod animation;
+mod assemblyai;
+mod azure;
+mod deepgram;
+mod document;
+mod gemini;
+mod groq;
+mod ocr;
+mod ollama;
 mod openai;
 mod ort;
+mod pdf;
+mod scenes;
 mod vision;
 mod whisper;
 mod youtube;
@@ -9,6 +20,16 @@ use anyhow::Result;
 use std::path::PathBuf;
 use tracing::info;
 
+/// Languages tesseract reads, for images and scanned PDF pages, unless `OCR_LANGUAGES` says otherwise
+const DEFAULT_OCR_LANGUAGES: &str = "eng";
+
+fn ocr_languages() -> String {
+    std::env::var("OCR_LANGUAGES").unwrap_or_else(|_| DEFAULT_OCR_LANGUAGES.to_string())
+}
+
+/// Where a local Ollama server listens unless `OLLAMA_API_URL` says otherwise
+const DEFAULT_OLLAMA_API_URL: &str = "http://localhost:11434";
+
 pub fn create_backend(
     backend_type: &str,
     api_key: Option<String>,
@@ -23,8 +44,112 @@ pub fn create_backend(
             })?;
             Ok(Box::new(openai::OpenAIBackend::new(api_key)))
         }
-        "whisper" => Ok(Box::new(whisper::WhisperBackend::new(model_path))),
-        "ort" => Ok(Box::new(ort::OrtBackend::new())),
+        "deepgram" => {
+            let api_key = std::env::var("DEEPGRAM_API_KEY")
+                .ok()
+                .or(api_key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Deepgram backend requires DEEPGRAM_API_KEY in .env or --api-key"
+                    )
+                })?;
+            let model = std::env::var("DEEPGRAM_MODEL").unwrap_or_else(|_| "nova-2".to_string());
+
+            Ok(Box::new(deepgram::DeepgramBackend::new(api_key, model)))
+        }
+        "assemblyai" => {
+            let api_key = std::env::var("ASSEMBLYAI_API_KEY")
+                .ok()
+                .or(api_key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "AssemblyAI backend requires ASSEMBLYAI_API_KEY in .env or --api-key"
+                    )
+                })?;
+
+            Ok(Box::new(assemblyai::AssemblyAIBackend::new(api_key)))
+        }
+        "azure" => {
+            let api_key = std::env::var("AZURE_SPEECH_KEY")
+                .ok()
+                .or(api_key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Azure backend requires AZURE_SPEECH_KEY in .env or --api-key")
+                })?;
+            let region = std::env::var("AZURE_SPEECH_REGION").map_err(|_| {
+                anyhow::anyhow!("Azure backend requires AZURE_SPEECH_REGION in .env file")
+            })?;
+            let locales = std::env::var("AZURE_SPEECH_LOCALES")
+                .unwrap_or_else(|_| "en-US".to_string())
+                .split(',')
+                .map(|locale| locale.trim().to_string())
+                .filter(|locale| !locale.is_empty())
+                .collect();
+
+            Ok(Box::new(azure::AzureBackend::new(api_key, region, locales)))
+        }
+        "document" => Ok(Box::new(document::DocumentBackend::new(ocr_languages()))),
+        "gemini" => {
+            let api_key = std::env::var("GEMINI_API_KEY")
+                .ok()
+                .or(api_key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Gemini backend requires GEMINI_API_KEY in .env or --api-key")
+                })?;
+            let model =
+                std::env::var("GEMINI_MODEL").unwrap_or_else(|_| "gemini-2.0-flash".to_string());
+
+            Ok(Box::new(gemini::GeminiBackend::new(api_key, model)))
+        }
+        "groq" => {
+            let api_key = std::env::var("GROQ_API_KEY")
+                .ok()
+                .or(api_key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Groq backend requires GROQ_API_KEY in .env or --api-key")
+                })?;
+            let api_url = std::env::var("GROQ_API_URL")
+                .unwrap_or_else(|_| "https://api.groq.com/openai/v1".to_string());
+            let model =
+                std::env::var("GROQ_MODEL").unwrap_or_else(|_| "whisper-large-v3".to_string());
+
+            Ok(Box::new(groq::GroqBackend::new(api_key, api_url, model)))
+        }
+        "ocr" => Ok(Box::new(ocr::OcrBackend::new(None, ocr_languages()))),
+        "ollama" => {
+            let api_url = std::env::var("OLLAMA_API_URL")
+                .unwrap_or_else(|_| DEFAULT_OLLAMA_API_URL.to_string());
+            let vision_model =
+                std::env::var("OLLAMA_VISION_MODEL").unwrap_or_else(|_| "llava".to_string());
+            let summary_model =
+                std::env::var("OLLAMA_SUMMARY_MODEL").unwrap_or_else(|_| "llama3.2".to_string());
+
+            Ok(Box::new(ollama::OllamaBackend::new(
+                api_url,
+                vision_model,
+                summary_model,
+                whisper_model_path(model_path),
+                whisper_options(),
+            )))
+        }
+        "whisper" => Ok(Box::new(whisper::WhisperBackend::new(
+            whisper_model_path(model_path),
+            whisper_options(),
+        ))),
+        "ort" => {
+            let model_dir = std::env::var("ORT_MODEL_DIR")
+                .ok()
+                .map(PathBuf::from)
+                .or(model_path);
+            let language = std::env::var("ORT_WHISPER_LANGUAGE").ok();
+            let n_mels = std::env::var("ORT_WHISPER_N_MELS")
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(80);
+
+            Ok(Box::new(ort::OrtBackend::new(model_dir, language, n_mels)))
+        }
+        "pdf" => Ok(Box::new(pdf::PdfBackend::new(ocr_languages()))),
         "vision" => {
             let api_key = api_key
                 .or_else(|| std::env::var("VISION_API_KEY").ok())
@@ -46,12 +171,74 @@ pub fn create_backend(
         }
         "youtube" => Ok(Box::new(youtube::YouTubeBackend::new())),
         _ => Err(anyhow::anyhow!(
-            "Unknown backend: {}. Available backends: openai, whisper, ort, vision, youtube",
+            "Unknown backend: {}. Available backends: openai, deepgram, assemblyai, azure, document, gemini, groq, ocr, ollama, whisper, ort, pdf, vision, youtube",
             backend_type
         )),
     }
 }
 
+/// Frames described per video unless `VIDEO_SCENE_FRAMES` says otherwise
+const DEFAULT_VIDEO_SCENE_FRAMES: usize = 6;
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Seconds of overlap between chunks of long files unless
+/// `WHISPER_CHUNK_OVERLAP` says otherwise
+const DEFAULT_WHISPER_CHUNK_OVERLAP_SECS: f64 = 2.0;
+
+/// CPU threads for whisper.cpp unless `WHISPER_THREADS` says otherwise
+const DEFAULT_WHISPER_THREADS: i32 = 4;
+
+/// `--model-path` if given, otherwise the cached model of the size named by
+/// `WHISPER_MODEL`, such as `base.en`.
+fn whisper_model_path(model_path: Option<PathBuf>) -> Option<PathBuf> {
+    model_path.or_else(|| {
+        std::env::var("WHISPER_MODEL")
+            .ok()
+            .map(|size| crate::models::model_path(&size))
+    })
+}
+
+fn whisper_options() -> whisper::WhisperOptions {
+    whisper::WhisperOptions {
+        diarize: env_flag("WHISPER_DIARIZE"),
+        chunk_overlap_secs: std::env::var("WHISPER_CHUNK_OVERLAP")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(DEFAULT_WHISPER_CHUNK_OVERLAP_SECS),
+        split_on_silence: env_flag("WHISPER_SPLIT_ON_SILENCE"),
+        use_gpu: !env_flag("WHISPER_CPU_ONLY"),
+        gpu_device: std::env::var("WHISPER_GPU_DEVICE")
+            .ok()
+            .and_then(|device| device.parse().ok())
+            .unwrap_or(0),
+        threads: std::env::var("WHISPER_THREADS")
+            .ok()
+            .and_then(|threads| threads.parse().ok())
+            .filter(|&threads| threads > 0)
+            .unwrap_or(DEFAULT_WHISPER_THREADS),
+    }
+}
+
+/// The backend used to describe images: a configured vision backend, then
+/// a local Ollama vision model, otherwise `hosted`
+fn image_backend_type(hosted: &'static str) -> &'static str {
+    if std::env::var("VISION_API_KEY").is_ok()
+        && std::env::var("VISION_API_URL").is_ok()
+        && std::env::var("VISION_MODEL").is_ok()
+    {
+        "vision"
+    } else if std::env::var("OLLAMA_VISION_MODEL").is_ok() {
+        "ollama"
+    } else {
+        hosted
+    }
+}
+
 /// Automatically select the best backend based on the URL/file type
 pub fn create_backend_auto(
     url: &str,
@@ -60,22 +247,28 @@ pub fn create_backend_auto(
 ) -> Result<Box<dyn Processor>> {
     let file_type = get_file_type_from_url(url);
 
+    // Gemini stands in for OpenAI when only a Gemini key is configured
+    let hosted = if api_key.is_none() && std::env::var("GEMINI_API_KEY").is_ok() {
+        "gemini"
+    } else {
+        "openai"
+    };
+
     let backend_type = match file_type {
-        FileType::Image => {
-            // Check if vision backend is configured, otherwise fall back to OpenAI
-            if std::env::var("VISION_API_KEY").is_ok()
-                && std::env::var("VISION_API_URL").is_ok()
-                && std::env::var("VISION_MODEL").is_ok()
-            {
-                "vision"
-            } else {
-                "openai"
-            }
-        }
+        FileType::Image => image_backend_type(hosted),
         FileType::YouTube => {
             // YouTube URLs are handled by the dedicated YouTube backend
             "youtube"
         }
+        FileType::Document => {
+            // PDFs, ePubs, plain text and markdown are all read locally
+            "document"
+        }
+        FileType::Audio | FileType::Video if std::env::var("DEEPGRAM_API_KEY").is_ok() => {
+            // Deepgram is preferred when configured since it also reports
+            // word timings and the spoken language
+            "deepgram"
+        }
         FileType::Audio | FileType::Video => {
             // Try Whisper first, but check if it's viable
             // let whisper_path = model_path.clone().unwrap_or_else(|| {
@@ -90,8 +283,8 @@ pub fn create_backend_auto(
             }
             #[cfg(not(feature = "whisper"))]
             {
-                info!("Whisper not compiled, using OpenAI for audio/video");
-                "openai"
+                info!("Whisper not compiled, using {} for audio/video", hosted);
+                hosted
             }
         }
         FileType::Unknown => {
@@ -106,5 +299,29 @@ pub fn create_backend_auto(
         "Auto-selected backend '{}' for file type: {:?}",
         backend_type, file_type
     );
-    create_backend(backend_type, api_key, model_path)
+    let backend = create_backend(backend_type, api_key.clone(), model_path.clone())?;
+
+    // Describe what videos show next to what is said when enabled
+    if file_type == FileType::Video && env_flag("VIDEO_SCENES") {
+        let describer = create_backend(image_backend_type(hosted), api_key, model_path)?;
+        let frames = std::env::var("VIDEO_SCENE_FRAMES")
+            .ok()
+            .and_then(|frames| frames.parse().ok())
+            .unwrap_or(DEFAULT_VIDEO_SCENE_FRAMES);
+        info!("Video scenes enabled, describing {} frames", frames);
+        return Ok(Box::new(scenes::VideoScenesBackend::new(
+            backend, describer, frames,
+        )));
+    }
+
+    // Add the text found in images to their description when OCR is enabled
+    if file_type == FileType::Image && env_flag("OCR_ENABLED") {
+        info!("OCR enabled, extracting image text alongside the description");
+        return Ok(Box::new(ocr::OcrBackend::new(
+            Some(backend),
+            ocr_languages(),
+        )));
+    }
+
+    Ok(backend)
 }
@@ -0,0 +1,137 @@
+use crate::processor::{FileType, ProcessedContent, Processor, get_file_type_from_url};
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+/// Extracts text from images with the local `tesseract` command. On its
+/// own it only returns the text; wrapping another image backend adds the
+/// text to that backend's description, which matters for screenshots and
+/// memes whose meaning is mostly in the embedded text.
+pub struct OcrBackend {
+    inner: Option<Box<dyn Processor>>,
+    languages: String,
+    name: String,
+}
+
+impl OcrBackend {
+    /// `languages` are tesseract language codes joined by `+`, e.g.
+    /// `eng+deu`.
+    pub fn new(inner: Option<Box<dyn Processor>>, languages: String) -> Self {
+        let name = match &inner {
+            Some(inner) => format!("{}+ocr", inner.name()),
+            None => "ocr".to_string(),
+        };
+        info!("Initializing OCR backend with languages: {}", languages);
+
+        Self {
+            inner,
+            languages,
+            name,
+        }
+    }
+
+    async fn extract_text(&self, url: &str) -> Result<Option<String>> {
+        info!("OCR: Extracting text from image: {}", url);
+
+        // Remote images are saved to a temporary file for tesseract
+        let temp_file;
+        let file_path = match url.strip_prefix("file://") {
+            Some(file_path) => file_path.to_string(),
+            None => {
+                let response = reqwest::get(url).await?;
+                if !response.status().is_success() {
+                    return Err(anyhow::anyhow!(
+                        "Failed to download file: HTTP {}",
+                        response.status()
+                    ));
+                }
+                let bytes = response.bytes().await?;
+                temp_file = tempfile::NamedTempFile::new()?;
+                tokio::fs::write(temp_file.path(), &bytes).await?;
+                temp_file.path().to_string_lossy().to_string()
+            }
+        };
+
+        let text = run_tesseract(&file_path, &self.languages).await?;
+        info!("OCR: Found {} characters of text", text.len());
+        Ok((!text.is_empty()).then_some(text))
+    }
+}
+
+/// Runs tesseract on a local image and returns the cleaned text, which is
+/// empty if the image has none.
+pub async fn run_tesseract(file_path: &str, languages: &str) -> Result<String> {
+    let output = tokio::process::Command::new("tesseract")
+        .args([file_path, "stdout", "-l", languages])
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to run tesseract, is it installed? {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "tesseract failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(clean_ocr_text(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Drops the blank lines and stray whitespace tesseract leaves between
+/// text blocks.
+fn clean_ocr_text(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[async_trait]
+impl Processor for OcrBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with OCR: {}", url);
+
+        if file_type != FileType::Image {
+            return match &self.inner {
+                Some(inner) => inner.process(url).await,
+                None => Err(anyhow::anyhow!(
+                    "OCR backend can only process images, got: {}",
+                    url
+                )),
+            };
+        }
+
+        let Some(inner) = &self.inner else {
+            return Ok(ProcessedContent::Description {
+                description: String::new(),
+                tags: vec!["ocr".to_string()],
+                ocr_text: self.extract_text(url).await?,
+            });
+        };
+
+        let (content, ocr_text) = tokio::join!(inner.process(url), self.extract_text(url));
+        // A failed OCR pass shouldn't cost the description
+        let ocr_text = ocr_text.unwrap_or_else(|e| {
+            warn!("OCR failed for {}: {}", url, e);
+            None
+        });
+
+        match content? {
+            ProcessedContent::Description {
+                description, tags, ..
+            } => Ok(ProcessedContent::Description {
+                description,
+                tags,
+                ocr_text,
+            }),
+            content => Ok(content),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
@@ -0,0 +1,183 @@
+use super::animation::prepare_image_input;
+use super::whisper::{WhisperBackend, WhisperOptions, WhisperTranscript};
+use crate::processor::{
+    FileType, ProcessedContent, Processor, generate_summary_ollama, get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use serde::Deserialize;
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+/// Fully local processing: images are described by a vision model served
+/// by Ollama, audio and video are transcribed with Whisper and summarized
+/// by a text model on the same Ollama server.
+pub struct OllamaBackend {
+    api_url: String,
+    vision_model: String,
+    summary_model: String,
+    whisper: WhisperBackend,
+    client: reqwest::Client,
+}
+
+impl OllamaBackend {
+    pub fn new(
+        api_url: String,
+        vision_model: String,
+        summary_model: String,
+        model_path: Option<PathBuf>,
+        whisper_options: WhisperOptions,
+    ) -> Self {
+        info!("Initializing Ollama backend:");
+        info!("  API URL: {}", api_url);
+        info!("  Vision model: {}", vision_model);
+        info!("  Summary model: {}", summary_model);
+
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            vision_model,
+            summary_model,
+            whisper: WhisperBackend::new(model_path, whisper_options),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn describe_image(&self, url: &str) -> Result<String> {
+        info!("Ollama: Describing image from URL: {}", url);
+        let image_bytes = self.download_file(url).await?;
+        info!(
+            "Ollama: Image downloaded, size: {} bytes",
+            image_bytes.len()
+        );
+
+        // Animated images are sent as a few sampled frames. Ollama takes
+        // bare base64 images, so the MIME type is unused
+        let input = prepare_image_input(url, image_bytes, "image/png").await;
+        let images: Vec<String> = input
+            .images
+            .iter()
+            .map(|(_, bytes)| STANDARD.encode(bytes))
+            .collect();
+
+        let request_body = serde_json::json!({
+            "model": self.vision_model,
+            "prompt": input.prompt,
+            "images": images,
+            "stream": false
+        });
+
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.api_url))
+            .json(&request_body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "Ollama API error ({}): {}",
+                status,
+                error_text
+            ));
+        }
+
+        let result: GenerateResponse = response.json().await?;
+        let description = result.response.trim().to_string();
+        info!(
+            "Ollama: Description ready, {} characters",
+            description.len()
+        );
+        Ok(description)
+    }
+
+    async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        info!("Getting file from URL: {}", url);
+
+        if let Some(file_path) = url.strip_prefix("file://") {
+            Ok(tokio::fs::read(file_path).await?)
+        } else {
+            let response = self.client.get(url).send().await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!(
+                    "Failed to download file: HTTP {}",
+                    response.status()
+                ));
+            }
+
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+}
+
+#[async_trait]
+impl Processor for OllamaBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with Ollama: {}", url);
+
+        match file_type {
+            FileType::Audio | FileType::Video => {
+                let WhisperTranscript { text, segments } = self.whisper.transcribe_url(url).await?;
+
+                let summary = match generate_summary_ollama(
+                    &text,
+                    &self.api_url,
+                    &self.summary_model,
+                )
+                .await
+                {
+                    Ok(summary) => {
+                        info!("Generated summary for transcription");
+                        Some(summary)
+                    }
+                    Err(e) => {
+                        info!("Failed to generate summary: {}", e);
+                        None
+                    }
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text,
+                    language: Some("auto-detected".to_string()),
+                    duration_ms: None,
+                    summary,
+                    words: Vec::new(),
+                    segments,
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Image => {
+                let description = self.describe_image(url).await?;
+                Ok(ProcessedContent::Description {
+                    description,
+                    tags: vec![],
+                    ocr_text: None,
+                })
+            }
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "Ollama backend cannot process YouTube URLs directly. Use the YouTube backend instead."
+            )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "Ollama backend cannot process documents. Use the document backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "ollama"
+    }
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
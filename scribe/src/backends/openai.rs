@@ -1,5 +1,6 @@
+use super::animation::prepare_image_input;
 use crate::processor::{
-    FileType, ProcessedContent, Processor, generate_summary, get_file_type_from_url,
+    FileType, ProcessedContent, Processor, Word, generate_summary, get_file_type_from_url,
 };
 use anyhow::Result;
 use async_trait::async_trait;
@@ -7,6 +8,53 @@ use base64::Engine;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
+const OPENAI_API_URL: &str = "https://api.openai.com/v1";
+
+/// Uploads audio to an OpenAI-compatible `/audio/transcriptions` endpoint
+/// under `base_url`, as served by OpenAI itself and by Groq.
+pub async fn transcribe_with_openai_api(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    file_bytes: Vec<u8>,
+    file_name: String,
+) -> Result<TranscriptionResponse> {
+    let form = reqwest::multipart::Form::new()
+        .text("model", model.to_string())
+        .text("response_format", "verbose_json")
+        .text("timestamp_granularities[]", "word")
+        .part(
+            "file",
+            reqwest::multipart::Part::bytes(file_bytes)
+                .file_name(file_name)
+                .mime_str("audio/mpeg")?,
+        );
+
+    let response = client
+        .post(format!(
+            "{}/audio/transcriptions",
+            base_url.trim_end_matches('/')
+        ))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .multipart(form)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await?;
+        return Err(anyhow::anyhow!(
+            "Transcription API error ({}): {}",
+            status,
+            error_text
+        ));
+    }
+
+    debug!("Transcription response received, parsing transcript");
+    Ok(response.json().await?)
+}
+
 pub struct OpenAIBackend {
     api_key: String,
     client: reqwest::Client,
@@ -20,33 +68,23 @@ impl OpenAIBackend {
         }
     }
 
-    async fn transcribe_audio(&self, url: &str) -> Result<String> {
+    async fn transcribe_audio(&self, url: &str) -> Result<TranscriptionResponse> {
         info!("OpenAI: Transcribing audio from URL: {}", url);
         let file_bytes = self.download_file(url).await?;
         info!("OpenAI: File downloaded, size: {} bytes", file_bytes.len());
 
-        let form = reqwest::multipart::Form::new()
-            .text("model", "whisper-1")
-            .part(
-                "file",
-                reqwest::multipart::Part::bytes(file_bytes)
-                    .file_name(self.extract_filename_from_url(url))
-                    .mime_str("audio/mpeg")?,
-            );
-
         info!("OpenAI: Sending request to Whisper API");
-        let response = self
-            .client
-            .post("https://api.openai.com/v1/audio/transcriptions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .multipart(form)
-            .send()
-            .await?;
-
-        info!("OpenAI: Response received, parsing transcript");
-        let result: TranscriptionResponse = response.json().await?;
+        let result = transcribe_with_openai_api(
+            &self.client,
+            OPENAI_API_URL,
+            &self.api_key,
+            "whisper-1",
+            file_bytes,
+            self.extract_filename_from_url(url),
+        )
+        .await?;
         info!("OpenAI: Transcript ready, {} characters", result.text.len());
-        Ok(result.text)
+        Ok(result)
     }
 
     async fn describe_image(&self, url: &str) -> Result<String> {
@@ -56,24 +94,32 @@ impl OpenAIBackend {
             "OpenAI: Image downloaded, size: {} bytes",
             image_bytes.len()
         );
-        let base64_image = base64::engine::general_purpose::STANDARD.encode(&image_bytes);
-
         let mime_type = self.get_mime_type_from_url(url);
+        let input = prepare_image_input(url, image_bytes, mime_type).await;
+
+        let mut content = vec![VisionContent::Text {
+            text: input.prompt.to_string(),
+        }];
+        content.extend(
+            input
+                .images
+                .iter()
+                .map(|(mime_type, bytes)| VisionContent::ImageUrl {
+                    image_url: ImageUrl {
+                        url: format!(
+                            "data:{};base64,{}",
+                            mime_type,
+                            base64::engine::general_purpose::STANDARD.encode(bytes)
+                        ),
+                    },
+                }),
+        );
 
         let request_body = VisionRequest {
             model: "gpt-4o-mini".to_string(),
             messages: vec![VisionMessage {
                 role: "user".to_string(),
-                content: vec![
-                    VisionContent::Text {
-                        text: "Describe this image in detail. Include objects, people, text, colors, and scene context.".to_string(),
-                    },
-                    VisionContent::ImageUrl {
-                        image_url: ImageUrl {
-                            url: format!("data:{};base64,{}", mime_type, base64_image),
-                        },
-                    },
-                ],
+                content,
             }],
             max_tokens: 500,
         };
@@ -159,7 +205,9 @@ impl Processor for OpenAIBackend {
 
         match file_type {
             FileType::Audio | FileType::Video => {
-                let text = self.transcribe_audio(url).await?;
+                let mut transcription = self.transcribe_audio(url).await?;
+                let words = transcription.timed_words();
+                let text = transcription.text;
 
                 // Generate summary for the transcription
                 let summary = match generate_summary(&text, &self.api_key).await {
@@ -175,9 +223,16 @@ impl Processor for OpenAIBackend {
 
                 Ok(ProcessedContent::Transcript {
                     text,
-                    language: None,
-                    duration_ms: None,
+                    language: transcription.language,
+                    duration_ms: transcription
+                        .duration
+                        .map(|seconds| (seconds * 1000.0).round() as u64),
                     summary,
+                    words,
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
                 })
             }
             FileType::Image => {
@@ -185,11 +240,15 @@ impl Processor for OpenAIBackend {
                 Ok(ProcessedContent::Description {
                     description,
                     tags: vec![],
+                    ocr_text: None,
                 })
             }
             FileType::YouTube => Err(anyhow::anyhow!(
                 "OpenAI backend cannot process YouTube URLs directly. Use the YouTube backend instead."
             )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "OpenAI backend cannot process documents. Use the document backend instead."
+            )),
             FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
         }
     }
@@ -200,8 +259,37 @@ impl Processor for OpenAIBackend {
 }
 
 #[derive(Deserialize)]
-struct TranscriptionResponse {
-    text: String,
+pub struct TranscriptionResponse {
+    pub text: String,
+    /// Spoken language, e.g. `english`
+    pub language: Option<String>,
+    /// Length of the audio in seconds
+    pub duration: Option<f64>,
+    pub words: Option<Vec<TranscriptionWord>>,
+}
+
+/// Times are in seconds.
+#[derive(Deserialize)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TranscriptionResponse {
+    /// Word timings in the form shared by all backends.
+    pub fn timed_words(&mut self) -> Vec<Word> {
+        self.words
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|word| Word {
+                word: word.word,
+                start_ms: (word.start * 1000.0).round() as u64,
+                end_ms: (word.end * 1000.0).round() as u64,
+            })
+            .collect()
+    }
 }
 
 #[derive(Serialize)]
@@ -1,16 +1,416 @@
-use crate::processor::{FileType, ProcessedContent, Processor, get_file_type_from_url};
+use crate::processor::{
+    FileType, ProcessedContent, Processor, generate_summary, get_file_type_from_url,
+};
 use anyhow::Result;
 use async_trait::async_trait;
+use std::path::PathBuf;
 use tracing::info;
 
-pub struct OrtBackend;
+#[cfg(feature = "ort")]
+use std::sync::{Arc, Mutex};
+
+/// Samples per second Whisper models are trained on
+#[cfg(feature = "ort")]
+const SAMPLE_RATE: usize = 16_000;
+/// Whisper reads audio in fixed 30 second windows
+#[cfg(feature = "ort")]
+const WINDOW_SAMPLES: usize = 30 * SAMPLE_RATE;
+#[cfg(feature = "ort")]
+const N_FFT: usize = 400;
+#[cfg(feature = "ort")]
+const HOP_LENGTH: usize = 160;
+/// Mel frames per window, one per hop
+#[cfg(feature = "ort")]
+const N_FRAMES: usize = WINDOW_SAMPLES / HOP_LENGTH;
+/// Whisper's decoder context is 448 tokens, half of which may be a prompt
+#[cfg(feature = "ort")]
+const MAX_DECODE_TOKENS: usize = 224;
+
+/// Language codes Whisper can detect, in the order of its language tokens
+#[cfg(feature = "ort")]
+const LANGUAGES: &[&str] = &[
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it",
+    "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur",
+    "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr", "az", "sl", "kn",
+    "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw", "gl", "mr", "pa", "si",
+    "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln",
+    "ha", "ba", "jw", "su", "yue",
+];
+
+/// Local transcription with a Whisper model exported to ONNX, e.g. with
+/// `optimum-cli export onnx --model openai/whisper-base`, run by ONNX
+/// Runtime. The model directory holds `encoder_model.onnx`,
+/// `decoder_model.onnx` and `tokenizer.json`. Needs the `ort` feature.
+pub struct OrtBackend {
+    #[cfg_attr(not(feature = "ort"), allow(dead_code))]
+    model_dir: PathBuf,
+    #[cfg_attr(not(feature = "ort"), allow(dead_code))]
+    language: Option<String>,
+    #[cfg_attr(not(feature = "ort"), allow(dead_code))]
+    n_mels: usize,
+    #[cfg(feature = "ort")]
+    model: tokio::sync::OnceCell<Arc<WhisperModel>>,
+}
 
 impl OrtBackend {
-    pub fn new() -> Self {
-        Self
+    /// `language` forces the transcription language instead of detecting
+    /// it; `n_mels` is 80 for most Whisper models and 128 for large-v3.
+    pub fn new(model_dir: Option<PathBuf>, language: Option<String>, n_mels: usize) -> Self {
+        let model_dir = model_dir.unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            PathBuf::from(home).join(".cache/whisper/whisper-base-onnx")
+        });
+        info!("Initializing ORT backend:");
+        info!("  Model directory: {:?}", model_dir);
+        info!("  Language: {}", language.as_deref().unwrap_or("auto"));
+
+        Self {
+            model_dir,
+            language,
+            n_mels,
+            #[cfg(feature = "ort")]
+            model: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    #[cfg(feature = "ort")]
+    async fn transcribe_url(&self, url: &str) -> Result<(String, Option<String>, u64)> {
+        info!("ORT: Transcribing audio from URL: {}", url);
+
+        let model = self
+            .model
+            .get_or_try_init(|| async {
+                let model_dir = self.model_dir.clone();
+                let n_mels = self.n_mels;
+                let model =
+                    tokio::task::spawn_blocking(move || WhisperModel::load(&model_dir, n_mels))
+                        .await??;
+                Ok::<_, anyhow::Error>(Arc::new(model))
+            })
+            .await?
+            .clone();
+
+        // ffmpeg reads remote files from a temporary copy
+        let temp_file;
+        let file_path = match url.strip_prefix("file://") {
+            Some(file_path) => PathBuf::from(file_path),
+            None => {
+                let response = reqwest::get(url).await?;
+                if !response.status().is_success() {
+                    return Err(anyhow::anyhow!(
+                        "Failed to download file: HTTP {}",
+                        response.status()
+                    ));
+                }
+                let bytes = response.bytes().await?;
+                temp_file = tempfile::NamedTempFile::new()?;
+                tokio::fs::write(temp_file.path(), &bytes).await?;
+                temp_file.path().to_path_buf()
+            }
+        };
+
+        let language = self.language.clone();
+        tokio::task::spawn_blocking(move || {
+            let samples = super::whisper::convert_audio_to_pcm(&file_path)?;
+            let duration_ms = (samples.len() * 1000 / SAMPLE_RATE) as u64;
+            info!("ORT: Decoded {} ms of audio", duration_ms);
+
+            let (text, language) = model.transcribe(&samples, language)?;
+            Ok((text, language, duration_ms))
+        })
+        .await?
+    }
+
+    #[cfg(not(feature = "ort"))]
+    async fn transcribe_url(&self, _url: &str) -> Result<(String, Option<String>, u64)> {
+        Err(anyhow::anyhow!(
+            "ORT support not compiled. Build with --features ort to enable ONNX Whisper transcription"
+        ))
     }
 }
 
+/// The encoder and decoder sessions of an ONNX Whisper export and the
+/// matching tokenizer.
+#[cfg(feature = "ort")]
+struct WhisperModel {
+    encoder: Mutex<::ort::session::Session>,
+    decoder: Mutex<::ort::session::Session>,
+    tokenizer: tokenizers::Tokenizer,
+    mel_filters: Vec<f32>,
+    n_mels: usize,
+    start_token: i64,
+    transcribe_token: i64,
+    no_timestamps_token: i64,
+    end_token: i64,
+}
+
+#[cfg(feature = "ort")]
+impl WhisperModel {
+    fn load(model_dir: &std::path::Path, n_mels: usize) -> Result<Self> {
+        use ::ort::session::Session;
+        use ::ort::session::builder::GraphOptimizationLevel;
+
+        if !model_dir.exists() {
+            return Err(anyhow::anyhow!(
+                "ONNX Whisper model not found at {:?}. Export one with `optimum-cli export onnx --model openai/whisper-base <dir>`",
+                model_dir
+            ));
+        }
+        info!("ORT: Loading Whisper model from {:?}", model_dir);
+
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let load_session = |file: &str| -> Result<Session> {
+            Ok(Session::builder()?
+                .with_optimization_level(GraphOptimizationLevel::Level3)?
+                .with_intra_threads(threads)?
+                .commit_from_file(model_dir.join(file))?)
+        };
+        let encoder = load_session("encoder_model.onnx")?;
+        let decoder = load_session("decoder_model.onnx")?;
+
+        let tokenizer = tokenizers::Tokenizer::from_file(model_dir.join("tokenizer.json"))
+            .map_err(|e| anyhow::anyhow!("Failed to load tokenizer.json: {}", e))?;
+        let token = |name: &str| -> Result<i64> {
+            tokenizer
+                .token_to_id(name)
+                .map(i64::from)
+                .ok_or_else(|| anyhow::anyhow!("Tokenizer has no {} token", name))
+        };
+
+        Ok(Self {
+            start_token: token("<|startoftranscript|>")?,
+            transcribe_token: token("<|transcribe|>")?,
+            no_timestamps_token: token("<|notimestamps|>")?,
+            end_token: token("<|endoftext|>")?,
+            encoder: Mutex::new(encoder),
+            decoder: Mutex::new(decoder),
+            tokenizer,
+            mel_filters: mel_filters(n_mels),
+            n_mels,
+        })
+    }
+
+    /// Transcribes the samples window by window, returning the text and
+    /// the language of the first window.
+    fn transcribe(
+        &self,
+        samples: &[f32],
+        language: Option<String>,
+    ) -> Result<(String, Option<String>)> {
+        let mut texts = Vec::new();
+        let mut language = language;
+
+        for (index, window) in samples.chunks(WINDOW_SAMPLES).enumerate() {
+            let features = log_mel_spectrogram(window, &self.mel_filters, self.n_mels);
+            let (hidden_shape, hidden) = self.encode(features)?;
+
+            if language.is_none() {
+                language = self.detect_language(&hidden_shape, &hidden)?;
+                info!(
+                    "ORT: Detected language: {}",
+                    language.as_deref().unwrap_or("unknown")
+                );
+            }
+            let language_token = language
+                .as_deref()
+                .and_then(|code| self.tokenizer.token_to_id(&format!("<|{}|>", code)));
+
+            let mut tokens = vec![self.start_token];
+            tokens.extend(language_token.map(i64::from));
+            tokens.extend([self.transcribe_token, self.no_timestamps_token]);
+            let prompt_len = tokens.len();
+
+            while tokens.len() - prompt_len < MAX_DECODE_TOKENS {
+                let logits = self.decode_step(&tokens, &hidden_shape, &hidden)?;
+                let next = argmax(&logits) as i64;
+                if next == self.end_token {
+                    break;
+                }
+                tokens.push(next);
+            }
+
+            let ids: Vec<u32> = tokens[prompt_len..].iter().map(|&id| id as u32).collect();
+            let text = self
+                .tokenizer
+                .decode(&ids, true)
+                .map_err(|e| anyhow::anyhow!("Failed to decode tokens: {}", e))?;
+            info!(
+                "ORT: Window {} transcribed: {} characters",
+                index + 1,
+                text.len()
+            );
+            if !text.trim().is_empty() {
+                texts.push(text.trim().to_string());
+            }
+        }
+
+        Ok((texts.join(" "), language))
+    }
+
+    fn encode(&self, features: Vec<f32>) -> Result<(Vec<usize>, Vec<f32>)> {
+        let input = ::ort::value::Tensor::from_array(([1, self.n_mels, N_FRAMES], features))?;
+        let mut encoder = self
+            .encoder
+            .lock()
+            .map_err(|_| anyhow::anyhow!("ONNX encoder session poisoned"))?;
+        let outputs = encoder.run(::ort::inputs!["input_features" => input])?;
+        let (shape, hidden) = outputs["last_hidden_state"].try_extract_tensor::<f32>()?;
+
+        Ok((
+            shape.iter().map(|&dim| dim as usize).collect(),
+            hidden.to_vec(),
+        ))
+    }
+
+    /// Runs the decoder over the tokens so far and returns the logits for
+    /// the next token.
+    fn decode_step(
+        &self,
+        tokens: &[i64],
+        hidden_shape: &[usize],
+        hidden: &[f32],
+    ) -> Result<Vec<f32>> {
+        let input_ids = ::ort::value::Tensor::from_array(([1, tokens.len()], tokens.to_vec()))?;
+        let hidden_states = ::ort::value::Tensor::from_array((
+            [hidden_shape[0], hidden_shape[1], hidden_shape[2]],
+            hidden.to_vec(),
+        ))?;
+
+        let mut decoder = self
+            .decoder
+            .lock()
+            .map_err(|_| anyhow::anyhow!("ONNX decoder session poisoned"))?;
+        let outputs = decoder.run(::ort::inputs![
+            "input_ids" => input_ids,
+            "encoder_hidden_states" => hidden_states
+        ])?;
+        let (shape, logits) = outputs["logits"].try_extract_tensor::<f32>()?;
+
+        // Logits are [batch, sequence, vocabulary]; keep the last position
+        let vocab_size = shape[2] as usize;
+        Ok(logits[logits.len() - vocab_size..].to_vec())
+    }
+
+    /// Picks the most likely language token after the start token.
+    fn detect_language(&self, hidden_shape: &[usize], hidden: &[f32]) -> Result<Option<String>> {
+        let logits = self.decode_step(&[self.start_token], hidden_shape, hidden)?;
+
+        Ok(LANGUAGES
+            .iter()
+            .filter_map(|code| {
+                let id = self.tokenizer.token_to_id(&format!("<|{}|>", code))?;
+                Some((*code, *logits.get(id as usize)?))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(code, _)| code.to_string()))
+    }
+}
+
+#[cfg(feature = "ort")]
+fn argmax(values: &[f32]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Slaney-style mel filterbank over the FFT bins, as computed by
+/// `librosa.filters.mel(sr=16000, n_fft=400)` for Whisper, flattened as
+/// `[n_mels][N_FFT / 2 + 1]`.
+#[cfg(feature = "ort")]
+fn mel_filters(n_mels: usize) -> Vec<f32> {
+    const F_SP: f64 = 200.0 / 3.0;
+    const MIN_LOG_HZ: f64 = 1000.0;
+    const MIN_LOG_MEL: f64 = MIN_LOG_HZ / F_SP;
+    let log_step = 6.4f64.ln() / 27.0;
+
+    let hz_to_mel = |hz: f64| {
+        if hz < MIN_LOG_HZ {
+            hz / F_SP
+        } else {
+            MIN_LOG_MEL + (hz / MIN_LOG_HZ).ln() / log_step
+        }
+    };
+    let mel_to_hz = |mel: f64| {
+        if mel < MIN_LOG_MEL {
+            mel * F_SP
+        } else {
+            MIN_LOG_HZ * ((mel - MIN_LOG_MEL) * log_step).exp()
+        }
+    };
+
+    let n_bins = N_FFT / 2 + 1;
+    let max_mel = hz_to_mel(SAMPLE_RATE as f64 / 2.0);
+    let mel_hz: Vec<f64> = (0..n_mels + 2)
+        .map(|i| mel_to_hz(max_mel * i as f64 / (n_mels + 1) as f64))
+        .collect();
+
+    let mut filters = vec![0.0f32; n_mels * n_bins];
+    for mel in 0..n_mels {
+        let (lower, center, upper) = (mel_hz[mel], mel_hz[mel + 1], mel_hz[mel + 2]);
+        let norm = 2.0 / (upper - lower);
+        for bin in 0..n_bins {
+            let hz = bin as f64 * SAMPLE_RATE as f64 / N_FFT as f64;
+            let weight = ((hz - lower) / (center - lower)).min((upper - hz) / (upper - center));
+            filters[mel * n_bins + bin] = (weight.max(0.0) * norm) as f32;
+        }
+    }
+    filters
+}
+
+/// Whisper's input features: the log-mel spectrogram of one window padded
+/// to 30 seconds, flattened as `[n_mels][N_FRAMES]`.
+#[cfg(feature = "ort")]
+fn log_mel_spectrogram(samples: &[f32], mel_filters: &[f32], n_mels: usize) -> Vec<f32> {
+    use rustfft::FftPlanner;
+    use rustfft::num_complex::Complex;
+
+    let mut audio = samples.to_vec();
+    audio.resize(WINDOW_SAMPLES, 0.0);
+
+    // Centered frames, reflect-padded like torch.stft(center=True)
+    let pad = N_FFT / 2;
+    let mut padded = Vec::with_capacity(audio.len() + 2 * pad);
+    padded.extend((1..=pad).rev().map(|i| audio[i]));
+    padded.extend_from_slice(&audio);
+    padded.extend((1..=pad).map(|i| audio[audio.len() - 1 - i]));
+
+    let window: Vec<f32> = (0..N_FFT)
+        .map(|n| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * n as f32 / N_FFT as f32).cos())
+        .collect();
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(N_FFT);
+    let n_bins = N_FFT / 2 + 1;
+
+    let mut features = vec![0.0f32; n_mels * N_FRAMES];
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); N_FFT];
+    let mut power = vec![0.0f32; n_bins];
+    for frame in 0..N_FRAMES {
+        let start = frame * HOP_LENGTH;
+        for (n, value) in buffer.iter_mut().enumerate() {
+            *value = Complex::new(padded[start + n] * window[n], 0.0);
+        }
+        fft.process(&mut buffer);
+        for (bin, value) in power.iter_mut().enumerate() {
+            *value = buffer[bin].norm_sqr();
+        }
+
+        for mel in 0..n_mels {
+            let filter = &mel_filters[mel * n_bins..(mel + 1) * n_bins];
+            let energy: f32 = filter.iter().zip(&power).map(|(w, p)| w * p).sum();
+            features[mel * N_FRAMES + frame] = energy.max(1e-10).log10();
+        }
+    }
+
+    // Clamp the dynamic range to 8 (log10) and scale to roughly [-1, 1]
+    let max = features.iter().copied().fold(f32::MIN, f32::max);
+    for value in &mut features {
+        *value = ((*value).max(max - 8.0) + 4.0) / 4.0;
+    }
+    features
+}
+
 #[async_trait]
 impl Processor for OrtBackend {
     async fn process(&self, url: &str) -> Result<ProcessedContent> {
@@ -19,22 +419,48 @@ impl Processor for OrtBackend {
         let file_type = get_file_type_from_url(url);
 
         match file_type {
-            FileType::Audio | FileType::Video => Ok(ProcessedContent::Transcript {
-                text: format!(
-                    "ORT backend placeholder - would process audio/video: {}",
-                    url
-                ),
-                language: Some("unknown".to_string()),
-                duration_ms: None,
-                summary: None,
-            }),
-            FileType::Image => Ok(ProcessedContent::Description {
-                description: format!("ORT backend placeholder - would process image: {}", url),
-                tags: vec!["ort".to_string(), "placeholder".to_string()],
-            }),
+            FileType::Audio | FileType::Video => {
+                let (text, language, duration_ms) = self.transcribe_url(url).await?;
+                info!("ORT: Transcript ready, {} characters", text.len());
+
+                // Generate summary if OpenAI API key is available
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_summary(&text, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for transcription");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    info!("No OPENAI_API_KEY found, skipping summary generation");
+                    None
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text,
+                    language,
+                    duration_ms: Some(duration_ms),
+                    summary,
+                    words: Vec::new(),
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Image => Err(anyhow::anyhow!(
+                "ORT backend only transcribes audio and video. Use the vision, Ollama or OpenAI backend for images."
+            )),
             FileType::YouTube => Err(anyhow::anyhow!(
                 "ORT backend cannot process YouTube URLs. Use the YouTube backend instead."
             )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "ORT backend cannot process documents. Use the document backend instead."
+            )),
             FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
         }
     }
@@ -0,0 +1,233 @@
+use super::ocr::run_tesseract;
+use crate::processor::{
+    FileType, Page, ProcessedContent, Processor, generate_document_summary, get_file_type_from_url,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+
+/// Extracts the text of PDF documents page by page with poppler's
+/// `pdftotext`. Pages without a text layer, as in scanned documents, are
+/// rendered with `pdftoppm` and read with tesseract instead.
+pub struct PdfBackend {
+    ocr_languages: String,
+    client: reqwest::Client,
+}
+
+impl PdfBackend {
+    /// `ocr_languages` are the tesseract language codes used for scanned
+    /// pages.
+    pub fn new(ocr_languages: String) -> Self {
+        info!("Initializing PDF backend:");
+        info!("  OCR languages: {}", ocr_languages);
+
+        Self {
+            ocr_languages,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn extract_pages(&self, url: &str) -> Result<Vec<Page>> {
+        info!("PDF: Extracting text from document: {}", url);
+
+        // Remote documents are saved to a temporary file for poppler
+        let temp_file;
+        let file_path = match url.strip_prefix("file://") {
+            Some(file_path) => file_path.to_string(),
+            None => {
+                let bytes = self.download_file(url).await?;
+                temp_file = tempfile::NamedTempFile::new()?;
+                tokio::fs::write(temp_file.path(), &bytes).await?;
+                temp_file.path().to_string_lossy().to_string()
+            }
+        };
+
+        let output = run_command("pdftotext", &["-enc", "UTF-8", &file_path, "-"]).await?;
+
+        // pdftotext ends every page with a form feed
+        let mut texts: Vec<&str> = output.split('\x0c').collect();
+        if texts.last().is_some_and(|text| text.trim().is_empty()) {
+            texts.pop();
+        }
+        info!("PDF: Document has {} pages", texts.len());
+
+        let mut pages = Vec::with_capacity(texts.len());
+        for (index, text) in texts.into_iter().enumerate() {
+            let number = index as u32 + 1;
+            let text = text.trim();
+
+            if !text.is_empty() {
+                pages.push(Page {
+                    number,
+                    text: text.to_string(),
+                    ocr: false,
+                });
+                continue;
+            }
+
+            // A page without a text layer is most likely scanned
+            match self.ocr_page(&file_path, number).await {
+                Ok(text) => {
+                    info!(
+                        "PDF: OCR found {} characters on page {}",
+                        text.len(),
+                        number
+                    );
+                    pages.push(Page {
+                        number,
+                        text,
+                        ocr: true,
+                    });
+                }
+                Err(e) => {
+                    warn!("PDF: OCR failed for page {}: {}", number, e);
+                    pages.push(Page {
+                        number,
+                        text: String::new(),
+                        ocr: false,
+                    });
+                }
+            }
+        }
+
+        Ok(pages)
+    }
+
+    async fn ocr_page(&self, file_path: &str, number: u32) -> Result<String> {
+        let temp_dir = tempfile::tempdir()?;
+        let prefix = temp_dir.path().join("page").to_string_lossy().to_string();
+        let number = number.to_string();
+
+        run_command(
+            "pdftoppm",
+            &[
+                "-f",
+                &number,
+                "-l",
+                &number,
+                "-r",
+                "300",
+                "-png",
+                "-singlefile",
+                file_path,
+                &prefix,
+            ],
+        )
+        .await?;
+
+        run_tesseract(&format!("{}.png", prefix), &self.ocr_languages).await
+    }
+
+    async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        info!("Getting file from URL: {}", url);
+
+        let response = self.client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to download file: HTTP {}",
+                response.status()
+            ));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// Runs a poppler command and returns its standard output.
+async fn run_command(program: &str, args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to run {}, is poppler-utils installed? {}",
+                program,
+                e
+            )
+        })?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[async_trait]
+impl Processor for PdfBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with PDF backend: {}", url);
+
+        match file_type {
+            FileType::Document if url.to_lowercase().contains(".pdf") => {
+                let pages = self.extract_pages(url).await?;
+
+                let text = pages
+                    .iter()
+                    .map(|page| page.text.as_str())
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                info!("PDF: Text ready, {} characters", text.len());
+
+                if text.is_empty() {
+                    return Err(anyhow::anyhow!("No text found in document: {}", url));
+                }
+
+                // Generate summary if OpenAI API key is available
+                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                    match generate_document_summary(&text, &api_key).await {
+                        Ok(summary) => {
+                            info!("Generated summary for document");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    info!("No OPENAI_API_KEY found, skipping summary generation");
+                    None
+                };
+
+                Ok(ProcessedContent::Transcript {
+                    text,
+                    language: None,
+                    duration_ms: None,
+                    summary,
+                    words: Vec::new(),
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages,
+                    scenes: Vec::new(),
+                })
+            }
+            FileType::Document => Err(anyhow::anyhow!(
+                "PDF backend can only process PDFs. Use the document backend instead."
+            )),
+            FileType::Audio | FileType::Video => Err(anyhow::anyhow!(
+                "PDF backend cannot process audio or video. Use the whisper or OpenAI backend instead."
+            )),
+            FileType::Image => Err(anyhow::anyhow!(
+                "PDF backend cannot process images. Use the vision or OpenAI backend instead."
+            )),
+            FileType::YouTube => Err(anyhow::anyhow!(
+                "PDF backend cannot process YouTube URLs. Use the YouTube backend instead."
+            )),
+            FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "pdf"
+    }
+}
@@ -0,0 +1,192 @@
+use crate::processor::{FileType, ProcessedContent, Processor, Scene, get_file_type_from_url};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::Path;
+use tracing::{debug, info, warn};
+
+/// Adds what a video shows to its transcript: frames sampled evenly over
+/// the video with ffmpeg are described by an image backend, and the
+/// descriptions are returned as the transcript's scenes. Videos without
+/// speech, like most clips posted as memes, still get scenes.
+pub struct VideoScenesBackend {
+    inner: Box<dyn Processor>,
+    describer: Box<dyn Processor>,
+    frames: usize,
+    name: String,
+}
+
+impl VideoScenesBackend {
+    /// `inner` transcribes the video, `describer` describes single frames.
+    pub fn new(inner: Box<dyn Processor>, describer: Box<dyn Processor>, frames: usize) -> Self {
+        let name = format!("{}+{}-scenes", inner.name(), describer.name());
+        info!(
+            "Initializing video scenes with {} frames described by {}",
+            frames,
+            describer.name()
+        );
+
+        Self {
+            inner,
+            describer,
+            frames,
+            name,
+        }
+    }
+
+    async fn describe_scenes(&self, url: &str) -> Result<Vec<Scene>> {
+        info!(
+            "Scenes: Sampling {} frames from video: {}",
+            self.frames, url
+        );
+
+        // Remote videos are saved to a temporary file for ffmpeg
+        let temp_file;
+        let file_path = match url.strip_prefix("file://") {
+            Some(file_path) => file_path.to_string(),
+            None => {
+                let response = reqwest::get(url).await?;
+                if !response.status().is_success() {
+                    return Err(anyhow::anyhow!(
+                        "Failed to download file: HTTP {}",
+                        response.status()
+                    ));
+                }
+                let bytes = response.bytes().await?;
+                temp_file = tempfile::NamedTempFile::new()?;
+                tokio::fs::write(temp_file.path(), &bytes).await?;
+                temp_file.path().to_string_lossy().to_string()
+            }
+        };
+
+        let duration = probe_duration(&file_path).await?;
+        let frame_dir = tempfile::tempdir()?;
+        let mut scenes = Vec::new();
+
+        for index in 0..self.frames {
+            // The middle of each of `frames` equal stretches of the video
+            let seconds = duration * (index as f64 + 0.5) / self.frames as f64;
+            let frame_path = frame_dir.path().join(format!("scene_{}.jpg", index));
+            extract_frame(&file_path, seconds, &frame_path).await?;
+
+            let frame_url = format!("file://{}", frame_path.to_string_lossy());
+            if let ProcessedContent::Description { description, .. } =
+                self.describer.process(&frame_url).await?
+                && !description.trim().is_empty()
+            {
+                scenes.push(Scene {
+                    start_ms: (seconds * 1000.0) as u64,
+                    description: description.trim().to_string(),
+                });
+            }
+        }
+
+        info!("Scenes: Described {} frames", scenes.len());
+        Ok(scenes)
+    }
+}
+
+async fn probe_duration(file_path: &str) -> Result<f64> {
+    let output = tokio::process::Command::new("ffprobe")
+        .args([
+            "-v",
+            "quiet",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "csv=p=0",
+            file_path,
+        ])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(String::from_utf8(output.stdout)?.trim().parse()?)
+}
+
+async fn extract_frame(file_path: &str, seconds: f64, frame_path: &Path) -> Result<()> {
+    let output = tokio::process::Command::new("ffmpeg")
+        .args([
+            "-ss",
+            &format!("{:.3}", seconds),
+            "-i",
+            file_path,
+            "-frames:v",
+            "1",
+            "-q:v",
+            "2",
+            "-y",
+            &frame_path.to_string_lossy(),
+        ])
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "ffmpeg frame extraction failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl Processor for VideoScenesBackend {
+    async fn process(&self, url: &str) -> Result<ProcessedContent> {
+        let file_type = get_file_type_from_url(url);
+
+        debug!("Processing URL with video scenes: {}", url);
+
+        if file_type != FileType::Video {
+            return self.inner.process(url).await;
+        }
+
+        let (content, scenes) = tokio::join!(self.inner.process(url), self.describe_scenes(url));
+        // Failed scene descriptions shouldn't cost the transcript
+        let scenes = scenes.unwrap_or_else(|e| {
+            warn!("Describing scenes failed for {}: {}", url, e);
+            Vec::new()
+        });
+
+        match content {
+            Ok(mut content) => {
+                if let ProcessedContent::Transcript {
+                    scenes: content_scenes,
+                    ..
+                } = &mut content
+                {
+                    *content_scenes = scenes;
+                }
+                Ok(content)
+            }
+            Err(e) if !scenes.is_empty() => {
+                warn!(
+                    "Transcription failed for {}, keeping the scenes: {}",
+                    url, e
+                );
+                Ok(ProcessedContent::Transcript {
+                    text: String::new(),
+                    language: None,
+                    duration_ms: None,
+                    summary: None,
+                    words: Vec::new(),
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes,
+                })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
@@ -1,3 +1,4 @@
+use super::animation::prepare_image_input;
 use crate::processor::{FileType, ProcessedContent, Processor, get_file_type_from_url};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -180,30 +181,31 @@ impl VisionBackend {
             image_bytes.len()
         );
 
-        // Encode as base64
-        let base64_image = STANDARD.encode(&image_bytes);
-
         // Determine MIME type from URL
         let mime_type = self.get_mime_type_from_url(url);
 
+        // Animated images are sent as a few sampled frames
+        let input = prepare_image_input(url, image_bytes, mime_type).await;
+        let mut content = vec![serde_json::json!({
+            "type": "text",
+            "text": input.prompt
+        })];
+        content.extend(input.images.iter().map(|(mime_type, bytes)| {
+            serde_json::json!({
+                "type": "image_url",
+                "image_url": {
+                    "url": format!("data:{};base64,{}", mime_type, STANDARD.encode(bytes))
+                }
+            })
+        }));
+
         // Create the request payload
         let request_body = serde_json::json!({
             "model": self.model,
             "messages": [
                 {
                     "role": "user",
-                    "content": [
-                        {
-                            "type": "text",
-                            "text": "Describe this image in detail. Include objects, people, text, colors, and scene context."
-                        },
-                        {
-                            "type": "image_url",
-                            "image_url": {
-                                "url": format!("data:{};base64,{}", mime_type, base64_image)
-                            }
-                        }
-                    ]
+                    "content": content
                 }
             ],
             "max_tokens": 500
@@ -314,15 +316,20 @@ impl Processor for VisionBackend {
                 Ok(ProcessedContent::Description {
                     description,
                     tags: vec![],
+                    ocr_text: None,
                 })
             }
             FileType::Audio | FileType::Video => Ok(ProcessedContent::Description {
                 description: "Vision backend cannot process audio/video files".to_string(),
                 tags: vec!["unsupported".to_string()],
+                ocr_text: None,
             }),
             FileType::YouTube => Err(anyhow::anyhow!(
                 "Vision backend cannot process YouTube URLs. Use the YouTube backend instead."
             )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "Vision backend cannot process documents. Use the document backend instead."
+            )),
             FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
         }
     }
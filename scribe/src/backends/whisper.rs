@@ -1,23 +1,205 @@
 #[cfg(feature = "whisper")]
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
+use super::DEFAULT_OLLAMA_API_URL;
+use crate::models;
 use crate::processor::{
-    FileType, ProcessedContent, Processor, generate_summary, get_file_type_from_url,
+    FileType, ProcessedContent, Processor, Segment, generate_summary, generate_summary_ollama,
+    get_file_type_from_url,
 };
 use anyhow::Result;
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
-use tracing::info;
 #[cfg(feature = "whisper")]
+use tracing::debug;
+use tracing::info;
+#[cfg(any(feature = "whisper", feature = "ort"))]
 use tracing::warn;
 
+/// Longest stretch of audio, overlap included, transcribed at once
+#[cfg(feature = "whisper")]
+const MAX_CHUNK_SECS: f64 = 30.0;
+
+/// Bounds on the words compared when removing the text repeated where
+/// overlapping chunks meet; a single matching word is too often chance
+#[cfg(feature = "whisper")]
+const MIN_OVERLAP_WORDS: usize = 2;
+#[cfg(feature = "whisper")]
+const MAX_OVERLAP_WORDS: usize = 40;
+
 pub struct WhisperBackend {
     #[allow(dead_code)]
     model_path: PathBuf,
+    #[allow(dead_code)]
+    options: WhisperOptions,
+    /// Held while a missing model downloads, so concurrent files wait for
+    /// one download instead of each starting their own
+    #[allow(dead_code)]
+    model_download: tokio::sync::Mutex<()>,
+}
+
+/// How the whisper backend transcribes.
+#[derive(Debug, Clone)]
+pub struct WhisperOptions {
+    /// Marks speaker turns with tinydiarize, which needs a `tdrz` model
+    /// such as `ggml-small.en-tdrz.bin`
+    #[allow(dead_code)]
+    pub diarize: bool,
+    /// Seconds of audio each chunk of a long file repeats from the end of
+    /// the previous one, so words cut at a chunk boundary are heard whole
+    #[allow(dead_code)]
+    pub chunk_overlap_secs: f64,
+    /// Ends chunks at pauses found by ffmpeg's silencedetect instead of
+    /// at fixed 30 second marks
+    #[allow(dead_code)]
+    pub split_on_silence: bool,
+    /// Runs the model on the GPU when scribe is built with one of the
+    /// `whisper-cuda`, `whisper-metal` or `whisper-vulkan` features
+    pub use_gpu: bool,
+    /// Index of the GPU to use when the machine has several
+    pub gpu_device: i32,
+    /// CPU threads used for the parts of decoding left on the CPU
+    pub threads: i32,
+}
+
+/// A transcript with the speaker turns found when diarizing.
+pub struct WhisperTranscript {
+    pub text: String,
+    pub segments: Vec<Segment>,
+}
+
+/// One segment of whisper output; `speaker_turn_next` is set by
+/// tinydiarize when a different speaker follows.
+#[cfg(feature = "whisper")]
+struct WhisperSegment {
+    start_ms: u64,
+    end_ms: u64,
+    text: String,
+    speaker_turn_next: bool,
+}
+
+/// Joins whisper segments into one segment per speaker turn. tinydiarize
+/// only marks where the speaker changes, so turns carry no speaker label.
+#[cfg(feature = "whisper")]
+fn speaker_turns(pieces: &[WhisperSegment]) -> Vec<Segment> {
+    let mut turns = Vec::new();
+    let mut current: Option<Segment> = None;
+
+    for piece in pieces {
+        let text = piece.text.trim();
+        if !text.is_empty() {
+            let turn = current.get_or_insert_with(|| Segment {
+                speaker: None,
+                start_ms: piece.start_ms,
+                end_ms: piece.end_ms,
+                text: String::new(),
+            });
+            if !turn.text.is_empty() {
+                turn.text.push(' ');
+            }
+            turn.text.push_str(text);
+            turn.end_ms = piece.end_ms;
+        }
+        if piece.speaker_turn_next {
+            turns.extend(current.take());
+        }
+    }
+
+    turns.extend(current);
+    turns
+}
+
+/// Plans the `(start, end)` seconds of each chunk of a long file. Chunks
+/// after the first start `overlap` seconds early, and with `silences` they
+/// end at the last pause in their second half.
+#[cfg(feature = "whisper")]
+fn plan_chunks(duration: f64, overlap: f64, silences: &[f64]) -> Vec<(f64, f64)> {
+    let mut chunks = Vec::new();
+    let mut start = 0.0;
+
+    while start < duration {
+        let lead = if start > 0.0 { overlap } else { 0.0 };
+        let limit = start - lead + MAX_CHUNK_SECS;
+        let mut end = limit.min(duration);
+
+        if end < duration
+            && let Some(&pause) = silences
+                .iter()
+                .filter(|&&pause| pause > start + (limit - start) / 2.0 && pause < limit)
+                .last()
+        {
+            end = pause;
+        }
+
+        chunks.push((start - lead, end));
+        start = end;
+    }
+
+    chunks
 }
 
+/// Lowercases a word and drops its punctuation, so the same word matches
+/// wherever it falls in a sentence.
 #[cfg(feature = "whisper")]
-fn convert_audio_to_pcm(file_path: &Path) -> Result<Vec<f32>> {
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The number of leading words of `next` that repeat the end of
+/// `previous`.
+#[cfg(feature = "whisper")]
+fn overlapping_words(previous: &[String], next: &[String]) -> usize {
+    let longest = previous.len().min(next.len()).min(MAX_OVERLAP_WORDS);
+    (MIN_OVERLAP_WORDS..=longest)
+        .rev()
+        .find(|&count| previous[previous.len() - count..] == next[..count])
+        .unwrap_or(0)
+}
+
+/// Removes the text a chunk repeats from the end of the segments before
+/// it.
+#[cfg(feature = "whisper")]
+fn remove_repeated_words(previous: &[WhisperSegment], segments: &mut Vec<WhisperSegment>) {
+    let mut tail: Vec<String> = previous
+        .iter()
+        .rev()
+        .flat_map(|segment| segment.text.split_whitespace().rev())
+        .take(MAX_OVERLAP_WORDS)
+        .map(normalize_word)
+        .collect();
+    tail.reverse();
+    let head: Vec<String> = segments
+        .iter()
+        .flat_map(|segment| segment.text.split_whitespace())
+        .take(MAX_OVERLAP_WORDS)
+        .map(normalize_word)
+        .collect();
+
+    let mut repeated = overlapping_words(&tail, &head);
+    if repeated == 0 {
+        return;
+    }
+    debug!("Removing {} words repeated by the chunk overlap", repeated);
+
+    for segment in segments.iter_mut() {
+        if repeated == 0 {
+            break;
+        }
+        let words: Vec<&str> = segment.text.split_whitespace().collect();
+        let dropped = repeated.min(words.len());
+        segment.text = words[dropped..].join(" ");
+        repeated -= dropped;
+    }
+    segments.retain(|segment| !segment.text.is_empty() || segment.speaker_turn_next);
+}
+
+/// Decodes an audio or video file to the 16kHz mono samples Whisper models
+/// expect
+#[cfg(any(feature = "whisper", feature = "ort"))]
+pub fn convert_audio_to_pcm(file_path: &Path) -> Result<Vec<f32>> {
     use std::process::Command;
 
     // Use ffmpeg to convert to raw PCM: 16kHz, mono, f32le
@@ -61,90 +243,165 @@ fn convert_audio_to_pcm(file_path: &Path) -> Result<Vec<f32>> {
 }
 
 impl WhisperBackend {
-    pub fn new(model_path: Option<PathBuf>) -> Self {
-        let model_path = model_path.unwrap_or_else(|| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-            PathBuf::from(home).join(".cache/whisper/ggml-large-v3.bin")
-        });
+    pub fn new(model_path: Option<PathBuf>, options: WhisperOptions) -> Self {
+        let model_path = model_path.unwrap_or_else(|| models::model_path(models::DEFAULT_MODEL));
+
+        info!("Initializing Whisper backend:");
+        info!("  Model: {:?}", model_path);
+        if options.use_gpu {
+            info!("  GPU device: {}", options.gpu_device);
+        } else {
+            info!("  GPU: disabled");
+        }
+        info!("  Threads: {}", options.threads);
+
+        Self {
+            model_path,
+            options,
+            model_download: tokio::sync::Mutex::new(()),
+        }
+    }
 
-        Self { model_path }
+    /// Downloads the model if it is missing and is one of the ggml models
+    /// whisper.cpp publishes.
+    #[cfg(feature = "whisper")]
+    async fn ensure_model(&self) -> Result<()> {
+        let _download = self.model_download.lock().await;
+        if self.model_path.exists() {
+            return Ok(());
+        }
+
+        match models::model_size(&self.model_path) {
+            Some(size) => {
+                info!(
+                    "Whisper model not found at {:?}, downloading it",
+                    self.model_path
+                );
+                models::pull(size, &self.model_path).await
+            }
+            None => Err(anyhow::anyhow!(
+                "Whisper model not found at {:?}. Please download a ggml model from https://huggingface.co/ggerganov/whisper.cpp or run `scribe models pull <size>`",
+                self.model_path
+            )),
+        }
     }
 
     #[cfg(feature = "whisper")]
-    pub async fn transcribe_file(&self, file_path: &Path) -> Result<String> {
+    pub async fn transcribe_file(&self, file_path: &Path) -> Result<WhisperTranscript> {
         info!("Starting transcription of file: {:?}", file_path);
 
         // First, check the duration of the audio/video file
         let duration = self.get_file_duration(file_path).await?;
         info!("File duration: {:.2} seconds", duration);
 
-        if duration <= 30.0 {
+        let pieces = if duration <= MAX_CHUNK_SECS {
             // File is short enough, process directly
             info!("File is short (<= 30s), processing directly");
-            self.transcribe_single_file(file_path).await
+            self.transcribe_single_file(file_path).await?
         } else {
             // File is too long, split into chunks
-            info!("File is long (> 30s), splitting into 30-second chunks");
-            self.transcribe_chunked_file(file_path, duration).await
-        }
+            info!("File is long (> 30s), splitting into chunks of up to 30 seconds");
+            self.transcribe_chunked_file(file_path, duration).await?
+        };
+
+        let text = pieces
+            .iter()
+            .map(|piece| piece.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let segments = if self.options.diarize {
+            let turns = speaker_turns(&pieces);
+            info!("Diarization found {} speaker turns", turns.len());
+            turns
+        } else {
+            Vec::new()
+        };
+
+        Ok(WhisperTranscript { text, segments })
     }
 
     #[cfg(feature = "whisper")]
-    async fn transcribe_single_file(&self, file_path: &Path) -> Result<String> {
+    async fn transcribe_single_file(&self, file_path: &Path) -> Result<Vec<WhisperSegment>> {
         let model_path = self.model_path.clone();
         let file_path = file_path.to_path_buf();
+        let diarize = self.options.diarize;
+        let use_gpu = self.options.use_gpu;
+        let gpu_device = self.options.gpu_device;
+        let threads = self.options.threads;
 
         tokio::task::spawn_blocking(move || {
             // Convert audio file to PCM samples using ffmpeg
             let audio_data = convert_audio_to_pcm(&file_path)?;
 
-            let ctx = WhisperContext::new_with_params(
-                &model_path.to_string_lossy(),
-                WhisperContextParameters::default(),
-            )?;
+            let mut ctx_params = WhisperContextParameters::default();
+            ctx_params.use_gpu(use_gpu).gpu_device(gpu_device);
+            let ctx = WhisperContext::new_with_params(&model_path.to_string_lossy(), ctx_params)?;
 
             let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-            params.set_n_threads(4);
+            params.set_n_threads(threads);
             params.set_translate(false);
             params.set_language(Some("auto"));
             params.set_print_special(false);
             params.set_print_progress(false);
             params.set_print_realtime(false);
             params.set_print_timestamps(false);
+            params.set_tdrz_enable(diarize);
 
             let mut state = ctx.create_state()?;
             state.full(params, &audio_data)?;
 
             let num_segments = state.full_n_segments()?;
-            let mut text = String::new();
+            let mut segments = Vec::new();
 
             for i in 0..num_segments {
-                let segment = state.full_get_segment_text(i)?;
-                text.push_str(&segment);
-                text.push(' ');
+                // Segment timestamps are in 10ms units
+                segments.push(WhisperSegment {
+                    start_ms: state.full_get_segment_t0(i)?.max(0) as u64 * 10,
+                    end_ms: state.full_get_segment_t1(i)?.max(0) as u64 * 10,
+                    text: state.full_get_segment_text(i)?,
+                    speaker_turn_next: diarize && state.full_get_segment_speaker_turn_next(i),
+                });
             }
 
-            Ok(text.trim().to_string())
+            Ok(segments)
         })
         .await?
     }
 
     #[cfg(feature = "whisper")]
-    async fn transcribe_chunked_file(&self, file_path: &Path, duration: f64) -> Result<String> {
-        let chunk_duration = 30.0; // 30 seconds per chunk
-        let num_chunks = (duration / chunk_duration).ceil() as usize;
+    async fn transcribe_chunked_file(
+        &self,
+        file_path: &Path,
+        duration: f64,
+    ) -> Result<Vec<WhisperSegment>> {
+        // Capped so every chunk still adds mostly new audio
+        let overlap = self
+            .options
+            .chunk_overlap_secs
+            .clamp(0.0, MAX_CHUNK_SECS / 3.0);
+        let silences = if self.options.split_on_silence {
+            match self.detect_pauses(file_path).await {
+                Ok(silences) => silences,
+                Err(e) => {
+                    warn!("Silence detection failed, using fixed split points: {}", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let chunks = plan_chunks(duration, overlap, &silences);
 
         info!(
-            "Splitting into {} chunks of {} seconds each",
-            num_chunks, chunk_duration
+            "Splitting into {} chunks with {:.1}s overlap",
+            chunks.len(),
+            overlap
         );
 
-        let mut all_transcriptions = Vec::new();
-
-        for chunk_index in 0..num_chunks {
-            let start_time = chunk_index as f64 * chunk_duration;
-            let end_time = ((chunk_index + 1) as f64 * chunk_duration).min(duration);
+        let mut all_segments = Vec::new();
 
+        for (chunk_index, &(start_time, end_time)) in chunks.iter().enumerate() {
             info!(
                 "Processing chunk {} ({:.1}s - {:.1}s)",
                 chunk_index + 1,
@@ -158,13 +415,20 @@ impl WhisperBackend {
                 .await?;
 
             // Transcribe the chunk
-            let chunk_transcription = self.transcribe_single_file(chunk_file.path()).await?;
-
-            let transcription_len = chunk_transcription.len();
-            if !chunk_transcription.trim().is_empty() {
-                all_transcriptions.push(chunk_transcription);
+            let mut chunk_segments = self.transcribe_single_file(chunk_file.path()).await?;
+            if overlap > 0.0 {
+                remove_repeated_words(&all_segments, &mut chunk_segments);
             }
 
+            let transcription_len: usize = chunk_segments.iter().map(|s| s.text.len()).sum();
+            // Chunk timestamps start at zero; shift them to the whole file
+            let offset_ms = (start_time * 1000.0) as u64;
+            all_segments.extend(chunk_segments.into_iter().map(|segment| WhisperSegment {
+                start_ms: segment.start_ms + offset_ms,
+                end_ms: segment.end_ms + offset_ms,
+                ..segment
+            }));
+
             info!(
                 "Chunk {} transcribed: {} characters",
                 chunk_index + 1,
@@ -172,14 +436,62 @@ impl WhisperBackend {
             );
         }
 
-        // Combine all transcriptions
-        let combined_transcription = all_transcriptions.join(" ");
         info!(
-            "Combined transcription: {} characters total",
-            combined_transcription.len()
+            "Combined transcription: {} segments total",
+            all_segments.len()
         );
 
-        Ok(combined_transcription)
+        Ok(all_segments)
+    }
+
+    /// Returns the middle of each pause in the audio, in seconds.
+    #[cfg(feature = "whisper")]
+    async fn detect_pauses(&self, file_path: &Path) -> Result<Vec<f64>> {
+        use std::process::Command;
+
+        let output = Command::new("ffmpeg")
+            .args([
+                "-hide_banner",
+                "-nostats",
+                "-i",
+                file_path.to_str().unwrap(),
+                "-af",
+                "silencedetect=noise=-35dB:d=0.4",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "ffmpeg silencedetect failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        // silencedetect logs `silence_start: 1.2` and then
+        // `silence_end: 2.0 | silence_duration: 0.8`
+        let log = String::from_utf8_lossy(&output.stderr);
+        let value = |line: &str, key: &str| -> Option<f64> {
+            let rest = &line[line.find(key)? + key.len()..];
+            rest.split_whitespace().next()?.parse().ok()
+        };
+
+        let mut pauses = Vec::new();
+        let mut pause_start = None;
+        for line in log.lines() {
+            if let Some(start) = value(line, "silence_start:") {
+                pause_start = Some(start);
+            } else if let Some(end) = value(line, "silence_end:")
+                && let Some(start) = pause_start.take()
+            {
+                pauses.push((start + end) / 2.0);
+            }
+        }
+
+        info!("Found {} pauses to split at", pauses.len());
+        Ok(pauses)
     }
 
     #[cfg(feature = "whisper")]
@@ -256,25 +568,19 @@ impl WhisperBackend {
 
     #[cfg(not(feature = "whisper"))]
     #[allow(dead_code)]
-    pub async fn transcribe_file(&self, _file_path: &Path) -> Result<String> {
+    pub async fn transcribe_file(&self, _file_path: &Path) -> Result<WhisperTranscript> {
         Err(anyhow::anyhow!(
             "Whisper support not compiled. Build with --features whisper (requires libclang-dev)"
         ))
     }
 
-    async fn transcribe_url(&self, url: &str) -> Result<String> {
+    pub async fn transcribe_url(&self, url: &str) -> Result<WhisperTranscript> {
         info!("Whisper backend: processing audio from URL: {}", url);
 
-        // Check if we have a working Whisper model
-        if !self.model_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Whisper model not found at {:?}. Please download a model from https://huggingface.co/ggerganov/whisper.cpp or use OpenAI backend for transcription",
-                self.model_path
-            ));
-        }
-
         #[cfg(feature = "whisper")]
         {
+            // Check if we have a working Whisper model
+            self.ensure_model().await?;
             info!("Whisper model found at: {:?}", self.model_path);
 
             // Download the audio file from URL
@@ -306,7 +612,7 @@ impl WhisperBackend {
 
             info!(
                 "Transcription completed, {} characters",
-                transcription.len()
+                transcription.text.len()
             );
             Ok(transcription)
         }
@@ -338,27 +644,33 @@ impl Processor for WhisperBackend {
         info!("Whisper backend processing: {}", url);
 
         #[cfg(feature = "whisper")]
-        {
-            if !self.model_path.exists() {
-                warn!(
-                    "Whisper model not found at {:?}. Please download a ggml model from https://huggingface.co/ggerganov/whisper.cpp",
-                    self.model_path
-                );
-                return Err(anyhow::anyhow!(
-                    "Whisper model not found at {:?}",
-                    self.model_path
-                ));
-            }
+        if let Err(e) = self.ensure_model().await {
+            warn!("{}", e);
+            return Err(e);
         }
 
         let file_type = get_file_type_from_url(url);
 
         match file_type {
             FileType::Audio | FileType::Video => {
-                let text = self.transcribe_url(url).await?;
-
-                // Generate summary if OpenAI API key is available
-                let summary = if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+                let WhisperTranscript { text, segments } = self.transcribe_url(url).await?;
+
+                // Generate summary with a local Ollama model when one is
+                // configured, otherwise if OpenAI API key is available
+                let summary = if let Ok(model) = std::env::var("OLLAMA_SUMMARY_MODEL") {
+                    let api_url = std::env::var("OLLAMA_API_URL")
+                        .unwrap_or_else(|_| DEFAULT_OLLAMA_API_URL.to_string());
+                    match generate_summary_ollama(&text, &api_url, &model).await {
+                        Ok(summary) => {
+                            info!("Generated summary for transcription");
+                            Some(summary)
+                        }
+                        Err(e) => {
+                            info!("Failed to generate summary: {}", e);
+                            None
+                        }
+                    }
+                } else if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
                     match generate_summary(&text, &api_key).await {
                         Ok(summary) => {
                             info!("Generated summary for transcription");
@@ -379,15 +691,24 @@ impl Processor for WhisperBackend {
                     language: Some("auto-detected".to_string()),
                     duration_ms: None,
                     summary,
+                    words: Vec::new(),
+                    segments,
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
                 })
             }
             FileType::Image => Ok(ProcessedContent::Description {
                 description: "Whisper cannot process image files".to_string(),
                 tags: vec!["unsupported".to_string()],
+                ocr_text: None,
             }),
             FileType::YouTube => Err(anyhow::anyhow!(
                 "Whisper backend cannot process YouTube URLs directly. Use the YouTube backend instead."
             )),
+            FileType::Document => Err(anyhow::anyhow!(
+                "Whisper backend cannot process documents. Use the document backend instead."
+            )),
             FileType::Unknown => Err(anyhow::anyhow!("Unsupported file type for URL: {}", url)),
         }
     }
@@ -396,3 +717,73 @@ impl Processor for WhisperBackend {
         "whisper"
     }
 }
+
+#[cfg(all(test, feature = "whisper"))]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str) -> WhisperSegment {
+        WhisperSegment {
+            start_ms: 0,
+            end_ms: 0,
+            text: text.to_string(),
+            speaker_turn_next: false,
+        }
+    }
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(normalize_word).collect()
+    }
+
+    #[test]
+    fn test_plan_chunks_without_overlap() {
+        assert_eq!(
+            plan_chunks(70.0, 0.0, &[]),
+            vec![(0.0, 30.0), (30.0, 60.0), (60.0, 70.0)]
+        );
+    }
+
+    #[test]
+    fn test_plan_chunks_with_overlap() {
+        assert_eq!(
+            plan_chunks(70.0, 2.0, &[]),
+            vec![(0.0, 30.0), (28.0, 58.0), (56.0, 70.0)]
+        );
+    }
+
+    #[test]
+    fn test_plan_chunks_ends_at_last_pause_in_second_half() {
+        assert_eq!(
+            plan_chunks(70.0, 0.0, &[5.0, 20.0, 25.0]),
+            vec![(0.0, 25.0), (25.0, 55.0), (55.0, 70.0)]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let previous = words("the quick brown fox");
+        assert_eq!(overlapping_words(&previous, &words("Brown fox, jumps")), 2);
+        assert_eq!(
+            overlapping_words(&previous, &words("quick brown fox jumps")),
+            3
+        );
+        // A single matching word is not taken as overlap
+        assert_eq!(overlapping_words(&previous, &words("fox jumps")), 0);
+        assert_eq!(overlapping_words(&previous, &words("jumps over")), 0);
+    }
+
+    #[test]
+    fn test_remove_repeated_words() {
+        let previous = vec![segment("The quick"), segment("brown fox.")];
+        let mut segments = vec![segment("Brown fox"), segment("jumps over")];
+        remove_repeated_words(&previous, &mut segments);
+        assert_eq!(
+            segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(),
+            vec!["jumps over"]
+        );
+
+        let mut segments = vec![segment("fox jumps over")];
+        remove_repeated_words(&previous, &mut segments);
+        assert_eq!(segments[0].text, "fox jumps over");
+    }
+}
@@ -305,6 +305,11 @@ impl Processor for YouTubeBackend {
                     language: Some("auto-detected".to_string()),
                     duration_ms: None,
                     summary,
+                    words: Vec::new(),
+                    segments: Vec::new(),
+                    chapters: Vec::new(),
+                    pages: Vec::new(),
+                    scenes: Vec::new(),
                 })
             }
             _ => Err(anyhow::anyhow!(
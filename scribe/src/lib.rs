@@ -1,9 +1,10 @@
 pub mod backends;
+pub mod models;
 pub mod processor;
 
 // Re-export commonly used types
 pub use backends::{create_backend, create_backend_auto};
 pub use processor::{
-    FileType, ProcessedContent, ProcessingResult, Processor,
-    get_file_type_from_url, process_single_url_direct
+    Chapter, FileType, Page, ProcessedContent, ProcessingResult, Processor, Scene, Segment,
+    Word, get_file_type_from_url, process_single_url_direct
 };
\ No newline at end of file
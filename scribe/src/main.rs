@@ -1,5 +1,6 @@
 mod backends;
 mod config;
+mod models;
 mod processor;
 mod watcher;
 
@@ -39,6 +40,22 @@ enum Commands {
         /// File to process
         file: PathBuf,
     },
+    /// Manage the ggml models used by the whisper backend
+    Models {
+        #[command(subcommand)]
+        command: ModelCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ModelCommands {
+    /// Download a model into the cache directory
+    Pull {
+        /// Model size, such as base.en or large-v3
+        size: String,
+    },
+    /// List the models that can be downloaded
+    List,
 }
 
 #[tokio::main]
@@ -172,6 +189,20 @@ async fn main() -> Result<()> {
             println!("\n=== Processing Result ===");
             println!("{}", serde_json::to_string_pretty(&result.content)?);
         }
+        Commands::Models { command } => match command {
+            ModelCommands::Pull { size } => {
+                let path = args.model_path.unwrap_or_else(|| models::model_path(&size));
+                models::pull(&size, &path).await?;
+                println!("{}", path.display());
+            }
+            ModelCommands::List => {
+                for (size, _) in models::MODELS {
+                    let path = models::model_path(size);
+                    let status = if path.exists() { "downloaded" } else { "" };
+                    println!("{:<16}{}", size, status);
+                }
+            }
+        },
     }
 
     Ok(())
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+/// Where whisper.cpp publishes its ggml models
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// The model used when neither `--model-path` nor `WHISPER_MODEL` picks one
+pub const DEFAULT_MODEL: &str = "large-v3";
+
+/// The ggml Whisper models scribe can download, with the SHA-1 checksums
+/// whisper.cpp publishes for them.
+pub const MODELS: &[(&str, &str)] = &[
+    ("tiny", "bd577a113a864445d4c299885e0cb97d4ba92b5f"),
+    ("tiny.en", "c78c86eb1a8faa21b369bcd33207cc90d64ae9df"),
+    ("base", "465707469ff3a37a2b9b8d8f89f2f99de7299dac"),
+    ("base.en", "137c40403d78fd54d454da0f9bd998f78703390c"),
+    ("small", "55356645c2b361a969dfd0ef2c5a50d530afd8d5"),
+    ("small.en", "db8a495a91d927739e50b3fc1cc4c6b8f6c2d022"),
+    ("small.en-tdrz", "b6c6e7e89af1a35c08e6de56b66ca6a02a2fdfa1"),
+    ("medium", "fd9727b6e1217c2f614f9b698455c4ffd82463b4"),
+    ("medium.en", "8c30f0e44ce9560643ebd10bbe50cd20eafd3723"),
+    ("large-v1", "b1caaf735c4cc1429223d5a74f0f4d0b9b59a299"),
+    ("large-v2", "0f4c8e34f21cf1a914c59d8b3ce882345ad349d6"),
+    ("large-v3", "ad82bf6a9043ceed055076d0fd39f5f186ff8062"),
+    ("large-v3-turbo", "4af2b29d7ec73d781377bfd1758ca957a807e941"),
+];
+
+/// The directory models are downloaded to, `~/.cache/whisper`.
+pub fn cache_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".cache/whisper")
+}
+
+/// The cached file of the model of the given size, such as `base.en`.
+pub fn model_path(size: &str) -> PathBuf {
+    cache_dir().join(format!("ggml-{}.bin", size))
+}
+
+/// The size of a known model from its file name, so a missing
+/// `ggml-base.en.bin` anywhere can still be downloaded.
+#[allow(dead_code)]
+pub fn model_size(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_str()?;
+    MODELS
+        .iter()
+        .map(|(size, _)| *size)
+        .find(|size| file_name == format!("ggml-{}.bin", size))
+}
+
+/// Downloads the model of the given size to `path`, unless it is already
+/// there. The file only appears once its checksum has been verified.
+pub async fn pull(size: &str, path: &Path) -> Result<()> {
+    let Some((_, checksum)) = MODELS.iter().find(|(known, _)| *known == size) else {
+        return Err(anyhow::anyhow!(
+            "Unknown Whisper model: {}. Available models: {}",
+            size,
+            MODELS
+                .iter()
+                .map(|(size, _)| *size)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    };
+
+    if path.exists() {
+        info!("Whisper model {} already downloaded: {:?}", size, path);
+        return Ok(());
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    tokio::fs::create_dir_all(dir).await?;
+
+    let url = format!("{}/ggml-{}.bin", MODEL_BASE_URL, size);
+    info!("Downloading Whisper model {} from {}", size, url);
+
+    let mut response = reqwest::get(&url).await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to download model: HTTP {}",
+            response.status()
+        ));
+    }
+
+    // Written next to the model so the final rename can't cross filesystems
+    let partial = tempfile::NamedTempFile::new_in(dir)?;
+    let mut file = tokio::fs::File::create(partial.path()).await?;
+    let mut hasher = Sha1::new();
+    let mut downloaded = 0u64;
+    let mut next_report = 0u64;
+
+    while let Some(chunk) = response.chunk().await? {
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+
+        if downloaded >= next_report {
+            info!("Downloaded {} MB", downloaded / 1_000_000);
+            next_report += 100_000_000;
+        }
+    }
+    file.flush().await?;
+
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if digest != *checksum {
+        return Err(anyhow::anyhow!(
+            "Checksum mismatch for Whisper model {}: expected {}, got {}",
+            size,
+            checksum,
+            digest
+        ));
+    }
+
+    partial.persist(path)?;
+    info!(
+        "Whisper model {} saved to {:?} ({} MB)",
+        size,
+        path,
+        downloaded / 1_000_000
+    );
+    Ok(())
+}
@@ -22,13 +22,77 @@ pub enum ProcessedContent {
         language: Option<String>,
         duration_ms: Option<u64>,
         summary: Option<String>,
+        /// Word-level timings, for backends that report them
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        words: Vec<Word>,
+        /// Speaker turns, for backends that diarize
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        segments: Vec<Segment>,
+        /// Topic sections, for backends that detect chapters
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        chapters: Vec<Chapter>,
+        /// Per-page text, for documents
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pages: Vec<Page>,
+        /// What is shown over the course of a video, from sampled frames
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        scenes: Vec<Scene>,
     },
     Description {
         description: String,
         tags: Vec<String>,
+        /// Text found in the image by OCR, e.g. in screenshots and memes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ocr_text: Option<String>,
     },
 }
 
+/// A transcribed word with its position in the audio.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Word {
+    pub word: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// A stretch of the transcript spoken by one speaker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    /// Speaker label assigned by the backend, e.g. `A`
+    pub speaker: Option<String>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// A section of the audio about one topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    pub headline: String,
+    pub summary: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// The text of one page of a document, or one section of an ebook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page {
+    /// 1-based page or section number
+    pub number: u32,
+    pub text: String,
+    /// Whether the text was recovered by OCR because the page had no text
+    /// layer, e.g. a scanned page
+    #[serde(default)]
+    pub ocr: bool,
+}
+
+/// A description of what a video shows at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    pub start_ms: u64,
+    pub description: String,
+}
+
 /// File type classification for URL-based processing
 #[derive(Debug, Clone, PartialEq)]
 pub enum FileType {
@@ -36,6 +100,7 @@ pub enum FileType {
     Video,
     Image,
     YouTube,
+    Document,
     Unknown,
 }
 
@@ -113,6 +178,15 @@ pub fn get_file_type_from_url(url: &str) -> FileType {
         return FileType::Image;
     }
 
+    // Document extensions
+    if url_lower.contains(".pdf")
+        || url_lower.contains(".epub")
+        || url_lower.contains(".txt")
+        || url_lower.contains(".md")
+    {
+        return FileType::Document;
+    }
+
     FileType::Unknown
 }
 
@@ -123,6 +197,7 @@ pub fn get_file_type_string(url: &str) -> String {
         FileType::Video => "video".to_string(),
         FileType::Image => "image".to_string(),
         FileType::YouTube => "youtube".to_string(),
+        FileType::Document => "document".to_string(),
         FileType::Unknown => {
             // Try to extract extension from URL path
             if let Ok(parsed_url) = Url::parse(url)
@@ -143,14 +218,19 @@ pub trait Processor: Send + Sync {
     fn name(&self) -> &str;
 }
 
+/// Prompt asking a chat model for a searchable summary of a transcript
+pub fn summary_prompt(transcript: &str) -> String {
+    format!(
+        "Please create a descriptive and comprehensive summary of the following transcript. Focus on key topics, important details, and themes that would help someone find this content through search. Use descriptive language and include specific details mentioned in the content. Make the summary searchable by including relevant keywords and context.\n\nTranscript:\n{}",
+        transcript
+    )
+}
+
 /// Generate a descriptive summary of transcribed content for better searchability
 pub async fn generate_summary(transcript: &str, api_key: &str) -> Result<String> {
     let client = reqwest::Client::new();
 
-    let prompt = format!(
-        "Please create a descriptive and comprehensive summary of the following transcript. Focus on key topics, important details, and themes that would help someone find this content through search. Use descriptive language and include specific details mentioned in the content. Make the summary searchable by including relevant keywords and context.\n\nTranscript:\n{}",
-        transcript
-    );
+    let prompt = summary_prompt(transcript);
 
     let payload = serde_json::json!({
         "model": "gpt-3.5-turbo",
@@ -188,6 +268,108 @@ pub async fn generate_summary(transcript: &str, api_key: &str) -> Result<String>
     Ok(summary)
 }
 
+/// Longest stretch of text sent to the summary model in one request
+const MAX_SUMMARY_INPUT_CHARS: usize = 12_000;
+
+/// Most chunks of a long document that are summarized, to bound the number
+/// of requests for books
+const MAX_SUMMARY_CHUNKS: usize = 16;
+
+/// Splits text into chunks of at most `max_chars` characters, breaking
+/// between paragraphs where possible.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for mut paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        // Paragraphs longer than a chunk are split at character boundaries
+        while let Some((split, _)) = paragraph.char_indices().nth(max_chars) {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            chunks.push(paragraph[..split].to_string());
+            paragraph = &paragraph[split..];
+        }
+
+        if !current.is_empty()
+            && current.chars().count() + paragraph.chars().count() + 2 > max_chars
+        {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Generate a summary of text of any length: short text is summarized
+/// directly, long documents by summarizing each chunk and then the chunk
+/// summaries
+pub async fn generate_document_summary(text: &str, api_key: &str) -> Result<String> {
+    let chunks = chunk_text(text, MAX_SUMMARY_INPUT_CHARS);
+    if chunks.len() <= 1 {
+        return generate_summary(text, api_key).await;
+    }
+
+    if chunks.len() > MAX_SUMMARY_CHUNKS {
+        info!(
+            "Summarizing the first {} of {} chunks",
+            MAX_SUMMARY_CHUNKS,
+            chunks.len()
+        );
+    }
+
+    let mut summaries = Vec::new();
+    for chunk in chunks.iter().take(MAX_SUMMARY_CHUNKS) {
+        summaries.push(generate_summary(chunk, api_key).await?);
+    }
+
+    generate_summary(&summaries.join("\n\n"), api_key).await
+}
+
+/// Generate the summary with a model served by a local Ollama server, for
+/// setups that run without API keys
+pub async fn generate_summary_ollama(
+    transcript: &str,
+    api_url: &str,
+    model: &str,
+) -> Result<String> {
+    let client = reqwest::Client::new();
+
+    let payload = serde_json::json!({
+        "model": model,
+        "prompt": summary_prompt(transcript),
+        "stream": false
+    });
+
+    let response = client
+        .post(format!("{}/api/generate", api_url.trim_end_matches('/')))
+        .json(&payload)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await?;
+        return Err(anyhow::anyhow!("Ollama API error: {}", error_text));
+    }
+
+    let response_json: serde_json::Value = response.json().await?;
+
+    let summary = response_json["response"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Failed to extract summary from Ollama response"))?
+        .trim()
+        .to_string();
+
+    Ok(summary)
+}
+
 pub async fn process_urls(mut rx: mpsc::Receiver<String>, backend: &dyn Processor) {
     info!("Processor started with backend: {}", backend.name());
 
@@ -266,6 +448,11 @@ pub fn format_as_markdown(result: &ProcessingResult) -> String {
             language,
             duration_ms,
             summary,
+            segments,
+            chapters,
+            pages,
+            scenes,
+            ..
         } => {
             if let Some(summary_text) = summary {
                 markdown.push_str("### Summary\n\n");
@@ -273,6 +460,30 @@ pub fn format_as_markdown(result: &ProcessingResult) -> String {
                 markdown.push_str("\n\n");
             }
 
+            if !chapters.is_empty() {
+                markdown.push_str("### Chapters\n\n");
+                for chapter in chapters {
+                    markdown.push_str(&format!(
+                        "- **{}** {}\n",
+                        format_timestamp(chapter.start_ms),
+                        chapter.headline
+                    ));
+                }
+                markdown.push('\n');
+            }
+
+            if !scenes.is_empty() {
+                markdown.push_str("### Scenes\n\n");
+                for scene in scenes {
+                    markdown.push_str(&format!(
+                        "- **{}** {}\n",
+                        format_timestamp(scene.start_ms),
+                        scene.description
+                    ));
+                }
+                markdown.push('\n');
+            }
+
             markdown.push_str("### Transcript\n\n");
             if let Some(lang) = language {
                 markdown.push_str(&format!("**Language**: {}\n\n", lang));
@@ -287,13 +498,46 @@ pub fn format_as_markdown(result: &ProcessingResult) -> String {
                 ));
             }
             markdown.push_str("---\n\n");
-            markdown.push_str(text);
-            markdown.push('\n');
+            if !pages.is_empty() {
+                for page in pages {
+                    markdown.push_str(&format!("#### Page {}", page.number));
+                    if page.ocr {
+                        markdown.push_str(" (OCR)");
+                    }
+                    markdown.push_str("\n\n");
+                    markdown.push_str(&page.text);
+                    markdown.push_str("\n\n");
+                }
+            } else if segments.is_empty() {
+                markdown.push_str(text);
+                markdown.push('\n');
+            } else {
+                for segment in segments {
+                    markdown.push_str(&format!(
+                        "**{}** ({}): {}\n\n",
+                        segment.speaker.as_deref().unwrap_or("Speaker"),
+                        format_timestamp(segment.start_ms),
+                        segment.text
+                    ));
+                }
+            }
         }
-        ProcessedContent::Description { description, tags } => {
-            markdown.push_str("### Image Description\n\n");
-            markdown.push_str(description);
-            markdown.push_str("\n\n");
+        ProcessedContent::Description {
+            description,
+            tags,
+            ocr_text,
+        } => {
+            if !description.is_empty() {
+                markdown.push_str("### Image Description\n\n");
+                markdown.push_str(description);
+                markdown.push_str("\n\n");
+            }
+
+            if let Some(ocr_text) = ocr_text {
+                markdown.push_str("### Text in Image\n\n");
+                markdown.push_str(ocr_text);
+                markdown.push_str("\n\n");
+            }
 
             if !tags.is_empty() {
                 markdown.push_str("### Tags\n\n");
@@ -306,3 +550,9 @@ pub fn format_as_markdown(result: &ProcessingResult) -> String {
 
     markdown
 }
+
+/// Formats a position in the audio as `m:ss`.
+fn format_timestamp(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,88 @@
+use serde::Deserialize;
+
+/// Parses an optional value given either as its JSON type or as a string,
+/// e.g. `limit=10` in a query string and `"limit": 10` in a JSON body.
+pub fn deserialize_optional_from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrValue<T> {
+        String(String),
+        Value(T),
+    }
+
+    match Option::<StringOrValue<T>>::deserialize(deserializer)? {
+        Some(StringOrValue::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+        Some(StringOrValue::Value(v)) => Ok(Some(v)),
+        None => Ok(None),
+    }
+}
+
+/// A list given either as one comma separated string or as an array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum StringOrList {
+    String(String),
+    List(Vec<String>),
+}
+
+impl StringOrList {
+    /// Splits comma separated strings so query strings and JSON arrays
+    /// produce the same list.
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            StringOrList::String(s) => s
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect(),
+            StringOrList::List(list) => list,
+        }
+    }
+}
+
+/// Parses an optional list given as a comma separated string or an array.
+pub fn deserialize_optional_string_list<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<StringOrList>::deserialize(deserializer)?.map(StringOrList::into_vec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default, deserialize_with = "deserialize_optional_from_str")]
+        limit: Option<usize>,
+        #[serde(default, deserialize_with = "deserialize_optional_string_list")]
+        authors: Option<Vec<String>>,
+    }
+
+    #[test]
+    fn test_strings_and_typed_values_parse_alike() {
+        let from_query: Params =
+            serde_json::from_str(r#"{"limit": "10", "authors": "a, b,"}"#).unwrap();
+        let from_json: Params =
+            serde_json::from_str(r#"{"limit": 10, "authors": ["a", "b"]}"#).unwrap();
+
+        assert_eq!(from_query.limit, Some(10));
+        assert_eq!(from_json.limit, Some(10));
+        assert_eq!(from_query.authors, from_json.authors);
+        assert!(serde_json::from_str::<Params>(r#"{"limit": "ten"}"#).is_err());
+        assert!(
+            serde_json::from_str::<Params>("{}")
+                .unwrap()
+                .limit
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod de;
+pub mod nostr;
+pub mod url_extractor;
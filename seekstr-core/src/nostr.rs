@@ -0,0 +1,221 @@
+use anyhow::Result;
+use nostr_sdk::secp256k1::schnorr::Signature;
+use nostr_sdk::{Event, EventId, Kind, PublicKey, Tag, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+/// NIP-09 event deletion request.
+pub const DELETION_KIND: i32 = 5;
+
+/// A Nostr event in its NIP-01 wire form, as exchanged over HTTP and
+/// stored in the index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct NostrEvent {
+    pub id: String,
+    pub pubkey: String,
+    pub created_at: i64,
+    pub kind: i32,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+    pub sig: String,
+    /// Summary or description of the event's media generated by scribe.
+    /// Embedded into its own vector space when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+impl NostrEvent {
+    /// Checks that `id` is the hash of the event and `sig` a valid Schnorr
+    /// signature of it by `pubkey`.
+    pub fn verify(&self) -> Result<()> {
+        let event = Event::try_from(self)?;
+        event
+            .verify()
+            .map_err(|e| anyhow::anyhow!("Event {} failed verification: {}", self.id, e))
+    }
+
+    /// Ids of the events a NIP-09 deletion request (kind 5) asks to remove;
+    /// empty for any other kind.
+    pub fn deletion_targets(&self) -> Vec<String> {
+        if self.kind != DELETION_KIND {
+            return Vec::new();
+        }
+
+        self.tags
+            .iter()
+            .filter(|tag| tag.len() >= 2 && tag[0] == "e")
+            .map(|tag| tag[1].clone())
+            .collect()
+    }
+
+    /// `kind:pubkey:d-tag` for replaceable (0, 3, 10000-19999) and
+    /// addressable (30000-39999) events, identifying the slot a newer
+    /// version replaces; `None` for regular events. Replaceable events use
+    /// an empty d-tag.
+    pub fn address(&self) -> Option<String> {
+        let kind = self.nostr_kind();
+        if kind.is_replaceable() {
+            Some(format!("{}:{}:", self.kind, self.pubkey))
+        } else if kind.is_addressable() {
+            let d_tag = self
+                .tags
+                .iter()
+                .find(|tag| tag.first().is_some_and(|name| name == "d"))
+                .and_then(|tag| tag.get(1))
+                .map(String::as_str)
+                .unwrap_or_default();
+            Some(format!("{}:{}:{}", self.kind, self.pubkey, d_tag))
+        } else {
+            None
+        }
+    }
+
+    /// The event kind with nostr's range semantics (replaceable,
+    /// ephemeral, addressable).
+    pub fn nostr_kind(&self) -> Kind {
+        Kind::from(self.kind as u16)
+    }
+}
+
+impl From<&Event> for NostrEvent {
+    fn from(event: &Event) -> Self {
+        Self {
+            id: event.id.to_hex(),
+            pubkey: event.pubkey.to_hex(),
+            created_at: event.created_at.as_u64() as i64,
+            kind: event.kind.as_u16() as i32,
+            tags: event
+                .tags
+                .iter()
+                .map(|tag| tag.as_slice().to_vec())
+                .collect(),
+            content: event.content.clone(),
+            sig: event.sig.to_string(),
+            summary: None,
+        }
+    }
+}
+
+impl From<Event> for NostrEvent {
+    fn from(event: Event) -> Self {
+        Self::from(&event)
+    }
+}
+
+impl TryFrom<&NostrEvent> for Event {
+    type Error = anyhow::Error;
+
+    /// Parses the wire fields into nostr types; the id and signature are
+    /// not checked, see `NostrEvent::verify`.
+    fn try_from(event: &NostrEvent) -> Result<Self> {
+        let id = EventId::from_hex(&event.id)
+            .map_err(|e| anyhow::anyhow!("Invalid event id '{}': {}", event.id, e))?;
+        let pubkey = PublicKey::from_hex(&event.pubkey)
+            .map_err(|e| anyhow::anyhow!("Invalid pubkey '{}': {}", event.pubkey, e))?;
+        let created_at = u64::try_from(event.created_at)
+            .map_err(|_| anyhow::anyhow!("Invalid created_at {}", event.created_at))?;
+        let kind = u16::try_from(event.kind)
+            .map_err(|_| anyhow::anyhow!("Invalid kind {}", event.kind))?;
+        let tags = event
+            .tags
+            .iter()
+            .map(|tag| Tag::parse(tag.clone()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid tag: {}", e))?;
+        let sig = Signature::from_str(&event.sig)
+            .map_err(|e| anyhow::anyhow!("Invalid signature: {}", e))?;
+
+        Ok(Event::new(
+            id,
+            pubkey,
+            Timestamp::from(created_at),
+            Kind::from(kind),
+            tags,
+            event.content.clone(),
+            sig,
+        ))
+    }
+}
+
+impl TryFrom<NostrEvent> for Event {
+    type Error = anyhow::Error;
+
+    fn try_from(event: NostrEvent) -> Result<Self> {
+        Event::try_from(&event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_event() -> NostrEvent {
+        let keys = nostr_sdk::Keys::generate();
+        let event = nostr_sdk::EventBuilder::text_note("hello nostr")
+            .sign_with_keys(&keys)
+            .unwrap();
+        NostrEvent::from(event)
+    }
+
+    #[test]
+    fn test_event_round_trips_through_nostr_types() {
+        let event = signed_event();
+        let converted = NostrEvent::from(Event::try_from(&event).unwrap());
+
+        assert_eq!(converted.id, event.id);
+        assert_eq!(converted.sig, event.sig);
+        assert_eq!(converted.tags, event.tags);
+        assert_eq!(converted.nostr_kind(), event.nostr_kind());
+    }
+
+    #[test]
+    fn test_verify_accepts_signed_event() {
+        assert!(signed_event().verify().is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_event() {
+        let mut event = signed_event();
+        event.content = "edited".to_string();
+        assert!(event.verify().is_err());
+
+        let mut event = signed_event();
+        event.sig = "00".repeat(64);
+        assert!(event.verify().is_err());
+    }
+
+    #[test]
+    fn test_address_of_replaceable_and_addressable_events() {
+        let mut event = signed_event();
+        assert_eq!(event.address(), None);
+
+        event.kind = 0;
+        assert_eq!(event.address(), Some(format!("0:{}:", event.pubkey)));
+        event.kind = 10002;
+        assert_eq!(event.address(), Some(format!("10002:{}:", event.pubkey)));
+
+        event.kind = 30023;
+        event.tags = vec![vec!["d".to_string(), "my-article".to_string()]];
+        assert_eq!(
+            event.address(),
+            Some(format!("30023:{}:my-article", event.pubkey))
+        );
+    }
+
+    #[test]
+    fn test_deletion_targets() {
+        let mut event = signed_event();
+        event.tags = vec![
+            vec!["e".to_string(), "abc".to_string()],
+            vec!["k".to_string(), "1".to_string()],
+            vec!["e".to_string()],
+        ];
+        assert!(event.deletion_targets().is_empty());
+
+        event.kind = DELETION_KIND;
+        assert_eq!(event.deletion_targets(), vec!["abc"]);
+    }
+}
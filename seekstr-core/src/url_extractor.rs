@@ -199,6 +199,30 @@ pub fn extract_all_urls(event: &NostrEvent) -> Vec<String> {
     unique_urls
 }
 
+/// Words of surrounding text allowed for content to still count as just a
+/// media link.
+const MAX_CAPTION_WORDS: usize = 3;
+
+/// Returns the first URL of `content` when the content is little more than
+/// that link, e.g. a bare image or video URL with a short caption. Such
+/// content says nothing useful about the media when embedded.
+pub fn media_only_url(content: &str) -> Option<String> {
+    let mut urls = Vec::new();
+    let mut caption_words = 0;
+    for word in content.split_whitespace() {
+        if is_http_url(word) {
+            urls.push(word);
+        } else {
+            caption_words += 1;
+        }
+    }
+
+    match urls.first() {
+        Some(url) if caption_words <= MAX_CAPTION_WORDS => Some(url.to_string()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +252,7 @@ mod tests {
             ],
             content: "test content".to_string(),
             sig: "test_sig".to_string(),
+            summary: None,
         }
     }
 
@@ -269,4 +294,21 @@ mod tests {
         let hashes = extract_imeta_hashes(&event);
         assert!(hashes.contains(&"abcd1234hash".to_string()));
     }
+
+    #[test]
+    fn test_media_only_url() {
+        assert_eq!(
+            media_only_url("https://example.com/cat.jpg"),
+            Some("https://example.com/cat.jpg".to_string())
+        );
+        assert_eq!(
+            media_only_url("look at this https://example.com/clip.mp4"),
+            Some("https://example.com/clip.mp4".to_string())
+        );
+        assert_eq!(
+            media_only_url("a long post about cats that happens to link https://example.com"),
+            None
+        );
+        assert_eq!(media_only_url("no links here"), None);
+    }
 }